@@ -0,0 +1,61 @@
+// Times the interpreter on every example before and after `optimize`.
+// Run with `cargo bench --bench optimize`.
+use arrow::core::v3;
+use arrow::eval::make_sdf;
+use arrow::optimize::optimize;
+use arrow::pratt::parse;
+use arrow::scene::library;
+use std::time::Instant;
+
+const SAMPLES: usize = 1000;
+const RUNS: usize = 5;
+
+fn main() {
+    std::panic::set_hook(Box::new(|_| {}));
+    let library = library();
+    let pts: Vec<_> = (0..SAMPLES)
+        .map(|i| {
+            let t = i as f32;
+            v3(
+                (t * 1.7).sin() * 20.0,
+                (t * 0.9).cos() * 20.0,
+                t * 0.02 - 20.0,
+            )
+        })
+        .collect();
+    println!(
+        "{:<14}{:>12}{:>12}{:>10}",
+        "example", "original", "optimized", "speedup"
+    );
+    for scene in library.iter() {
+        let (name, mut input) = (&scene.name, scene.source.as_str());
+        let Ok(ast) = std::panic::catch_unwind(move || {
+            let ast = parse(&mut input);
+            make_sdf(&ast, 0.1, 0.2, v3(0.0, 0.0, 0.0));
+            ast
+        }) else {
+            continue;
+        };
+        let opt = optimize(&ast);
+        // The fastest of a few runs is the least noisy estimate.
+        let time = |ast: &arrow::ast::Statement| {
+            (0..RUNS)
+                .map(|_| {
+                    let start = Instant::now();
+                    let sum: f32 = pts.iter().map(|p| make_sdf(ast, 0.1, 0.2, *p)).sum();
+                    std::hint::black_box(sum);
+                    start.elapsed()
+                })
+                .min()
+                .unwrap()
+        };
+        let (a, b) = (time(&ast), time(&opt));
+        println!(
+            "{:<14}{:>10.1}ms{:>10.1}ms{:>9.2}x",
+            name,
+            a.as_secs_f64() * 1e3,
+            b.as_secs_f64() * 1e3,
+            a.as_secs_f64() / b.as_secs_f64()
+        );
+    }
+}
@@ -1,6 +1,6 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Statement {
     Assign { var: String, rhs: Box<Expr> },
     AssignToArray { vars: Vec<String>, rhs: Box<Expr> },
@@ -10,7 +10,7 @@ pub enum Statement {
     Empty,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Expr {
     Number(f32),
     BinaryOp(BinOp),
@@ -21,7 +21,7 @@ pub enum Expr {
     Assign(AssignExpr),
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BinOp {
     Add(Box<Expr>, Box<Expr>),
     Sub(Box<Expr>, Box<Expr>),
@@ -38,13 +38,13 @@ pub enum BinOp {
     Pow(Box<Expr>, Box<Expr>),
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AssignExpr {
     Inc(String),
     Dec(String),
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum FunctionName {
     Sin,
     Cos,
@@ -89,6 +89,74 @@ pub enum FunctionName {
     Torus,
     Box2,
     Box3,
+    Cone,
+    CappedCone,
+    RoundCone,
+    Ellipsoid,
+    HexPrism,
+    TriPrism,
+    Octahedron,
+    Pyramid,
+    Link,
+    SolidAngle,
+    CappedTorus,
+    UdTriangle,
+    UdQuad,
+    Hexagon,
+    Segment,
+    Triangle2,
+    Extrude,
+    Twist,
+    Bend,
+    Elongate,
+    Onion,
+    Revolve,
+    Repeat,
+    Polar,
+    PolyMin,
+    PolyMax,
+    CubicMin,
+    CubicMax,
+    ExpMin,
+    ExpMax,
+    RootMin,
+    RootMax,
+    CircularMin,
+    CircularMax,
+    ChamferMin,
+    ChamferMax,
+    StairsMin,
+    StairsMax,
+    ColumnsMin,
+    ColumnsMax,
+    Groove,
+    Tongue,
+    Pipe,
+    Translate,
+    Rotate,
+    Scale,
+    Mandelbulb,
+    MandelbulbTrap,
+    Mandelbox,
+    MandelboxTrap,
+    Menger,
+    MengerTrap,
+    Sierpinski,
+    SierpinskiTrap,
+    Kifs,
+    KifsTrap,
+    SeededValue,
+    Perlin,
+    Simplex,
+    Worley,
+    Cellular,
+    Ridged,
+    Billow,
+    Warp,
+    PerlinGradient,
+    SimplexGradient,
+    Bound,
+    Closest,
     Rot0,
     Rot1,
     Rot,
@@ -104,3 +172,96 @@ pub enum FunctionName {
     FakeSine,
     Hash,
 }
+
+pub(crate) fn operands(op: &BinOp) -> (&Expr, &Expr) {
+    match op {
+        BinOp::Add(a, b)
+        | BinOp::Sub(a, b)
+        | BinOp::Mul(a, b)
+        | BinOp::Div(a, b)
+        | BinOp::Eq(a, b)
+        | BinOp::NotEq(a, b)
+        | BinOp::Greater(a, b)
+        | BinOp::GreaterEq(a, b)
+        | BinOp::Less(a, b)
+        | BinOp::LessEq(a, b)
+        | BinOp::And(a, b)
+        | BinOp::Or(a, b)
+        | BinOp::Pow(a, b) => (a, b),
+    }
+}
+
+pub(crate) fn rebuild(op: &BinOp, a: Expr, b: Expr) -> BinOp {
+    let (a, b) = (Box::new(a), Box::new(b));
+    match op {
+        BinOp::Add(_, _) => BinOp::Add(a, b),
+        BinOp::Sub(_, _) => BinOp::Sub(a, b),
+        BinOp::Mul(_, _) => BinOp::Mul(a, b),
+        BinOp::Div(_, _) => BinOp::Div(a, b),
+        BinOp::Eq(_, _) => BinOp::Eq(a, b),
+        BinOp::NotEq(_, _) => BinOp::NotEq(a, b),
+        BinOp::Greater(_, _) => BinOp::Greater(a, b),
+        BinOp::GreaterEq(_, _) => BinOp::GreaterEq(a, b),
+        BinOp::Less(_, _) => BinOp::Less(a, b),
+        BinOp::LessEq(_, _) => BinOp::LessEq(a, b),
+        BinOp::And(_, _) => BinOp::And(a, b),
+        BinOp::Or(_, _) => BinOp::Or(a, b),
+        BinOp::Pow(_, _) => BinOp::Pow(a, b),
+    }
+}
+
+pub(crate) fn children(e: &Expr) -> Vec<&Expr> {
+    match e {
+        Expr::Number(_) | Expr::Variable(_) | Expr::Assign(_) => vec![],
+        Expr::Negate(a) => vec![a],
+        Expr::BinaryOp(op) => {
+            let (a, b) = operands(op);
+            vec![a, b]
+        }
+        Expr::Function { args, .. } => args.iter().collect(),
+        Expr::TernaryOp(c, t, f) => vec![c, t, f],
+    }
+}
+
+pub(crate) fn map_children<F: FnMut(&Expr) -> Expr>(e: &Expr, mut f: F) -> Expr {
+    match e {
+        Expr::Number(_) | Expr::Variable(_) | Expr::Assign(_) => e.clone(),
+        Expr::Negate(a) => Expr::Negate(Box::new(f(a))),
+        Expr::BinaryOp(op) => {
+            let (a, b) = operands(op);
+            let a = f(a);
+            let b = f(b);
+            Expr::BinaryOp(rebuild(op, a, b))
+        }
+        Expr::Function { name, args } => Expr::Function {
+            name: name.clone(),
+            args: args.iter().map(f).collect(),
+        },
+        Expr::TernaryOp(c, t, f0) => {
+            let c = f(c);
+            let t = f(t);
+            let e = f(f0);
+            Expr::TernaryOp(Box::new(c), Box::new(t), Box::new(e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pratt::parse;
+    use crate::scene::library;
+
+    #[test]
+    fn serializes() {
+        let library = library();
+        for name in ["asurf", "pawns", "sponge", "apollonius"] {
+            let ast = parse(&mut library[name].source.as_str());
+            let json: Statement =
+                serde_json::from_str(&serde_json::to_string(&ast).unwrap()).unwrap();
+            let ron: Statement = ron::from_str(&ron::to_string(&ast).unwrap()).unwrap();
+            assert_eq!(json, ast, "{}", name);
+            assert_eq!(ron, ast, "{}", name);
+        }
+    }
+}
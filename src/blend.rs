@@ -0,0 +1,214 @@
+// Ways of joining two distances `a` and `b` other than a sharp `min`. The
+// smooth minimums fillet the seam, `k` being about how far from it they
+// reach: `poly` is Inigo Quilez's quadratic one, `cubic` is smoother,
+// `exponential` and `root` reach everywhere but are the same in any order,
+// `circular` is a quarter circle, and `round` is hg_sdf's round union.
+// `chamfer`, `stairs` and `columns` are hg_sdf's other unions, see
+// https://mercury.sexy/hg_sdf. All of these are unions; `max` turns any of
+// them into an intersection by joining the outsides instead.
+use glam::Vec2;
+use std::f32::consts::FRAC_1_SQRT_2;
+
+pub fn poly(a: f32, b: f32, k: f32) -> f32 {
+    let h = (k - (a - b).abs()).max(0.0);
+    a.min(b) - h * h * 0.25 / k
+}
+
+pub fn cubic(a: f32, b: f32, k: f32) -> f32 {
+    let h = (k - (a - b).abs()).max(0.0) / k;
+    a.min(b) - h * h * h * k / 6.0
+}
+
+pub fn exponential(a: f32, b: f32, k: f32) -> f32 {
+    // Relative to the minimum so the powers can't underflow.
+    let m = a.min(b);
+    m - k * ((-(a - m) / k).exp2() + (-(b - m) / k).exp2()).log2()
+}
+
+pub fn root(a: f32, b: f32, k: f32) -> f32 {
+    0.5 * (a + b - ((a - b) * (a - b) + k * k).sqrt())
+}
+
+pub fn circular(a: f32, b: f32, k: f32) -> f32 {
+    let h = (k - (a - b).abs()).max(0.0) / k;
+    a.min(b) - k * 0.5 * (1.0 + h - (1.0 - h * (h - 2.0)).sqrt())
+}
+
+pub fn round(a: f32, b: f32, r: f32) -> f32 {
+    if a < r && b < r {
+        r - Vec2::new(r - a, r - b).length()
+    } else {
+        a.min(b)
+    }
+}
+
+// The DSL's `rG`, which isn't `round` turned into an intersection, but which
+// scenes depend on as it is.
+pub fn round_max(a: f32, b: f32, r: f32) -> f32 {
+    if -a < r && -b < r {
+        r - Vec2::new(r + a, r + b).length()
+    } else {
+        a.max(b)
+    }
+}
+
+pub fn chamfer(a: f32, b: f32, r: f32) -> f32 {
+    a.min(b).min((a - r + b) * FRAC_1_SQRT_2)
+}
+
+// `n` steps down from one surface to the other.
+pub fn stairs(a: f32, b: f32, r: f32, n: f32) -> f32 {
+    let s = r / n;
+    let u = b - r;
+    a.min(b)
+        .min(0.5 * (u + a + (modulo(u - a + s, 2.0 * s) - s).abs()))
+}
+
+// `n` columns along the seam.
+pub fn columns(a: f32, b: f32, r: f32, n: f32) -> f32 {
+    if a >= r || b >= r {
+        return a.min(b);
+    }
+    let radius = r * 2f32.sqrt() / ((n - 1.0) * 2.0 + 2f32.sqrt());
+    let mut x = (a + b) * FRAC_1_SQRT_2;
+    let mut y = (b - a) * FRAC_1_SQRT_2;
+    x += radius * 2f32.sqrt() - FRAC_1_SQRT_2 * r;
+    if modulo(n, 2.0) == 1.0 {
+        y += radius;
+    }
+    y = modulo(y + radius, 2.0 * radius) - radius;
+    (Vec2::new(x, y).length() - radius).min(x).min(a).min(b)
+}
+
+// `b` cuts a groove `ra` deep and `rb` wide into `a`.
+pub fn groove(a: f32, b: f32, ra: f32, rb: f32) -> f32 {
+    a.max((a + ra).min(rb - b.abs()))
+}
+
+// `b` raises a tongue `ra` high and `rb` wide out of `a`.
+pub fn tongue(a: f32, b: f32, ra: f32, rb: f32) -> f32 {
+    a.min((a - ra).max(b.abs() - rb))
+}
+
+// A pipe of radius `r` along where the surfaces cross.
+pub fn pipe(a: f32, b: f32, r: f32) -> f32 {
+    Vec2::new(a, b).length() - r
+}
+
+// The intersection that joins the outsides the way `union` joins the
+// insides.
+pub fn max(union: impl Fn(f32, f32) -> f32, a: f32, b: f32) -> f32 {
+    -union(-a, -b)
+}
+
+// Joins all but the last `params` of `xs`, which are the parameters of
+// `blend`, like the DSL's `rU(a, b, c, r)`.
+pub fn fold(xs: &[f32], params: usize, blend: impl Fn(f32, f32, &[f32]) -> f32) -> f32 {
+    let (ds, ps) = xs.split_at(xs.len() - params);
+    ds.iter()
+        .copied()
+        .reduce(|a, b| blend(a, b, ps))
+        .expect("a blend needs a distance")
+}
+
+fn modulo(a: f32, b: f32) -> f32 {
+    a - b * (a / b).floor()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blends() {
+        let local = [poly, cubic, circular, round, chamfer];
+        for blend in local {
+            // Far apart they are the minimum, near the seam they are below it.
+            assert_eq!(blend(1.0, 5.0, 1.0), 1.0);
+            assert!(blend(0.2, 0.2, 1.0) < 0.2);
+            assert_eq!(max(|a, b| blend(a, b, 1.0), -5.0, -1.0), -1.0);
+            assert!(max(|a, b| blend(a, b, 1.0), 0.2, 0.2) > 0.2);
+        }
+        for blend in [exponential, root] {
+            assert!((blend(1.0, 50.0, 0.1) - 1.0).abs() < 1e-3);
+            assert!(blend(0.2, 0.2, 1.0) < 0.2);
+        }
+        assert_eq!(exponential(1e6, 2e6, 0.1), 1e6);
+        assert_eq!(stairs(1.0, 5.0, 0.5, 3.0), 1.0);
+        assert!(stairs(0.2, 0.2, 0.5, 3.0) < 0.2);
+        assert_eq!(columns(1.0, 5.0, 0.5, 3.0), 1.0);
+        assert!(columns(0.1, 0.1, 0.5, 3.0) <= 0.1);
+        assert_eq!(pipe(3.0, 4.0, 1.0), 4.0);
+        // A groove is cut out of the surface, a tongue stands on it.
+        assert_eq!(groove(0.0, 0.0, 0.5, 0.1), 0.1);
+        assert_eq!(tongue(0.0, 0.0, 0.5, 0.1), -0.1);
+        assert_eq!(groove(0.0, 1.0, 0.5, 0.1), 0.0);
+        let xs = [3.0, 1.0, 2.0, 0.1];
+        assert_eq!(fold(&xs, 1, |a, b, p| poly(a, b, p[0])), 1.0);
+        assert_eq!(fold(&xs[..2], 0, |a, b, _| a.min(b)), 1.0);
+    }
+
+    fn grid() -> impl Iterator<Item = (f32, f32)> {
+        (0..21).flat_map(|i| (0..21).map(move |j| (i as f32 * 0.25 - 2.5, j as f32 * 0.25 - 2.5)))
+    }
+
+    #[test]
+    fn smooth_unions() {
+        // Below the minimum by at most how much each fillets at the seam,
+        // where they are furthest below it, the same either way round, and
+        // no steeper than the distances so they can still be marched.
+        let smooth = [poly, cubic, circular, exponential, root];
+        let most = [0.25, 1.0 / 6.0, 1.0 - FRAC_1_SQRT_2, 1.0, 0.5];
+        let k = 0.8;
+        for (blend, most) in smooth.into_iter().zip(most) {
+            assert!((blend(1.0, 1.0, k) - (1.0 - most * k)).abs() < 1e-5);
+            for (a, b) in grid() {
+                let d = blend(a, b, k);
+                assert!(d <= a.min(b) + 1e-5 && d >= a.min(b) - most * k - 1e-5);
+                assert!((d - blend(b, a, k)).abs() < 1e-5);
+                assert!((blend(a + 0.01, b, k) - d).abs() <= 0.01 + 1e-5);
+                assert!((blend(a, b + 0.01, k) - d).abs() <= 0.01 + 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn round_unions() {
+        // A quarter circle of radius `r` inside the corner where both are
+        // within `r`.
+        assert!((round(0.0, 0.0, 1.0) - (1.0 - 2f32.sqrt())).abs() < 1e-6);
+        assert_eq!(round(0.5, 1.0, 1.0), 0.5);
+        assert_eq!(round(-0.5, 3.0, 1.0), -0.5);
+        // `rG` rounds the inside corner of the intersection.
+        assert_eq!(round_max(0.5, -3.0, 1.0), 0.5);
+        assert!((round_max(0.0, 0.0, 1.0) - (1.0 - 2f32.sqrt())).abs() < 1e-6);
+        assert_eq!(round_max(-3.0, -2.0, 1.0), -2.0);
+    }
+
+    #[test]
+    fn chamfer_stairs_columns() {
+        // The chamfer is a 45 degree cut `r` from the corner.
+        assert!((chamfer(0.0, 0.0, 1.0) + FRAC_1_SQRT_2).abs() < 1e-6);
+        assert!(chamfer(0.5, 0.5, 1.0).abs() < 1e-6);
+        // Outside both they fill in the corner, by no more than `r`.
+        for (a, b) in grid().filter(|&(a, b)| a >= 0.0 && b >= 0.0) {
+            let m = a.min(b);
+            for d in [
+                chamfer(a, b, 1.0),
+                stairs(a, b, 1.0, 4.0),
+                columns(a, b, 1.0, 4.0),
+            ] {
+                assert!(d <= m && d >= m - 1.0, "{} {} {}", a, b, d);
+            }
+        }
+    }
+
+    #[test]
+    fn intersections() {
+        for (a, b) in grid() {
+            assert_eq!(max(|a, b| a.min(b), a, b), a.max(b));
+            let d = max(|a, b| poly(a, b, 0.5), a, b);
+            assert!(d >= a.max(b) && d <= a.max(b) + 0.125 + 1e-6);
+        }
+    }
+}
@@ -0,0 +1,690 @@
+// Boxes that a surface is known to be inside, so the marcher can skip rays
+// that miss them and start the others where they go in. A bound comes from
+// the scene's settings, from `estimate`, which runs a DSL program on whole
+// boxes of points at once with interval arithmetic, or from `sample`, which
+// evaluates any distance on a grid. Within a program `bound(b, e)` is the
+// distance `b` to a shape around `e`, like `bx3(...)`, until that is close,
+// so the expensive `e` is only evaluated near it.
+use crate::ast::{AssignExpr, BinOp, Expr, FunctionName, Statement};
+use crate::core::Sdf;
+use crate::eval::{eval_constant, Value};
+use crate::functions;
+use glam::{Mat2, Mat3, Quat, Vec3};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+
+// How close to its bound `bound(b, e)` switches to `e`, well over the
+// marcher's `EPSILON` so rays don't stop on the bound.
+pub const MARGIN: f32 = 0.1;
+
+// How far from the origin `estimate` and `sample` look. A surface that
+// reaches that far isn't bounded.
+const EXTENT: f32 = 100.0;
+const DEPTH: u32 = 7;
+const MAX_CELLS: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Bound {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+// Where the scene's bound comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bounding {
+    // `estimate`, if it can tell.
+    #[default]
+    Auto,
+    // `sample`, for distances it can't tell about that are never more than
+    // the distance to the surface.
+    Sample,
+    Off,
+    Box(Bound),
+}
+
+impl Bound {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Bound { min, max }
+    }
+
+    pub fn contains(&self, p: Vec3) -> bool {
+        p.cmpge(self.min).all() && p.cmple(self.max).all()
+    }
+
+    pub fn distance(&self, p: Vec3) -> f32 {
+        let q = (p - (self.min + self.max) * 0.5).abs() - (self.max - self.min) * 0.5;
+        q.max_element().min(0.0) + q.max(Vec3::ZERO).length()
+    }
+
+    // How far along the ray it goes in and comes out, if it goes through,
+    // going in at 0 if it starts inside.
+    pub fn ray(&self, ro: Vec3, rd: Vec3) -> Option<(f32, f32)> {
+        let t0 = (self.min - ro) / rd;
+        let t1 = (self.max - ro) / rd;
+        let near = t0.min(t1).max_element().max(0.0);
+        let far = t0.max(t1).min_element();
+        (near <= far).then_some((near, far))
+    }
+
+    pub fn union(self, other: Bound) -> Bound {
+        Bound::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    fn octants(self) -> impl Iterator<Item = Bound> {
+        let half = (self.max - self.min) * 0.5;
+        (0..8).map(move |i| {
+            let min =
+                self.min + half * Vec3::new((i & 1) as f32, (i >> 1 & 1) as f32, (i >> 2) as f32);
+            Bound::new(min, min + half)
+        })
+    }
+
+    // Whether it reaches the edge of where the estimates look.
+    fn unbounded(&self) -> bool {
+        self.min.min_element() <= -EXTENT || self.max.max_element() >= EXTENT
+    }
+
+    // A bound on the surface of the program `ast` with `params`, from the
+    // boxes that interval arithmetic can't rule out, or nothing if it can't
+    // rule out enough of them.
+    pub fn estimate<'a, P>(ast: &Statement, params: P) -> Option<Bound>
+    where
+        P: IntoIterator<Item = (&'a str, f32)>,
+    {
+        let params: Vec<(&str, f32)> = params.into_iter().collect();
+        let outside = |cell: &Bound| {
+            let mut ranges = Ranges::default();
+            for &(name, value) in &params {
+                ranges.env.insert(name.to_string(), Range::point(value));
+            }
+            for (name, lo, hi) in [
+                ("x", cell.min.x, cell.max.x),
+                ("y", cell.min.y, cell.max.y),
+                ("z", cell.min.z, cell.max.z),
+            ] {
+                ranges
+                    .env
+                    .insert(name.to_string(), Range::Scalar(Interval::new(lo, hi)));
+            }
+            ranges.run(ast);
+            ranges.last.scalar().lo > 0.0
+        };
+        let mut cells = vec![Bound::new(Vec3::splat(-EXTENT), Vec3::splat(EXTENT))];
+        for depth in 0..=DEPTH {
+            cells.retain(|cell| !outside(cell));
+            if depth == DEPTH || cells.len() * 8 > MAX_CELLS {
+                break;
+            }
+            cells = cells.into_iter().flat_map(Bound::octants).collect();
+        }
+        cells
+            .into_iter()
+            .reduce(Bound::union)
+            .filter(|bound| !bound.unbounded())
+    }
+
+    // A bound on the surface of `sdf` from its values on a grid, which is
+    // only right if it is never more than the distance to the surface.
+    pub fn sample(sdf: &(impl Fn(Vec3) -> f32 + Sync + ?Sized), n: u32) -> Option<Bound> {
+        let coarse = Bound::new(Vec3::splat(-EXTENT), Vec3::splat(EXTENT)).sample_in(sdf, n)?;
+        if coarse.unbounded() {
+            return None;
+        }
+        coarse.sample_in(sdf, n)
+    }
+
+    // The cells of an `n` by `n` by `n` grid over the bound that the surface
+    // of `sdf` can be in.
+    fn sample_in(&self, sdf: &(impl Fn(Vec3) -> f32 + Sync + ?Sized), n: u32) -> Option<Bound> {
+        let size = (self.max - self.min) / n as f32;
+        let reach = size.length() * 0.5;
+        (0..n * n * n)
+            .into_par_iter()
+            .filter_map(|i| {
+                let min = self.min
+                    + size * Vec3::new((i % n) as f32, (i / n % n) as f32, (i / (n * n)) as f32);
+                (sdf(min + size * 0.5) <= reach).then(|| Bound::new(min, min + size))
+            })
+            .reduce_with(Bound::union)
+    }
+}
+
+// `sdf` near `bound`, which its surface must be inside, and the cheaper
+// distance to the bound further away.
+pub fn bounded(sdf: Sdf, bound: Bound) -> Sdf {
+    Box::new(move |p| {
+        let b = bound.distance(p);
+        if b > MARGIN {
+            b
+        } else {
+            sdf(p)
+        }
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Interval {
+    lo: f32,
+    hi: f32,
+}
+
+impl Interval {
+    const ALL: Interval = Interval {
+        lo: f32::NEG_INFINITY,
+        hi: f32::INFINITY,
+    };
+
+    // Anything, if an end isn't a number, e.g. from infinity less infinity.
+    fn new(lo: f32, hi: f32) -> Self {
+        if lo.is_nan() || hi.is_nan() {
+            Interval::ALL
+        } else {
+            Interval { lo, hi }
+        }
+    }
+
+    fn point(&self) -> Option<f32> {
+        (self.lo == self.hi).then_some(self.lo)
+    }
+
+    fn hull(self, other: Interval) -> Interval {
+        Interval::new(self.lo.min(other.lo), self.hi.max(other.hi))
+    }
+
+    fn add(self, other: Interval) -> Interval {
+        Interval::new(self.lo + other.lo, self.hi + other.hi)
+    }
+
+    fn neg(self) -> Interval {
+        Interval::new(-self.hi, -self.lo)
+    }
+
+    fn sub(self, other: Interval) -> Interval {
+        self.add(other.neg())
+    }
+
+    fn mul(self, other: Interval) -> Interval {
+        let ps = [
+            self.lo * other.lo,
+            self.lo * other.hi,
+            self.hi * other.lo,
+            self.hi * other.hi,
+        ];
+        if ps.iter().any(|p| p.is_nan()) {
+            return Interval::ALL;
+        }
+        Interval::new(
+            ps.into_iter().fold(f32::INFINITY, f32::min),
+            ps.into_iter().fold(f32::NEG_INFINITY, f32::max),
+        )
+    }
+
+    fn div(self, other: Interval) -> Interval {
+        if other.lo <= 0.0 && other.hi >= 0.0 {
+            return Interval::ALL;
+        }
+        self.mul(Interval::new(1.0 / other.hi, 1.0 / other.lo))
+    }
+
+    fn abs(self) -> Interval {
+        if self.lo >= 0.0 {
+            self
+        } else if self.hi <= 0.0 {
+            self.neg()
+        } else {
+            Interval::new(0.0, self.hi.max(-self.lo))
+        }
+    }
+
+    fn sq(self) -> Interval {
+        let a = self.abs();
+        Interval::new(a.lo * a.lo, a.hi * a.hi)
+    }
+
+    // `f` of each end, for `f` that never decreases.
+    fn map(self, f: impl Fn(f32) -> f32) -> Interval {
+        Interval::new(f(self.lo), f(self.hi))
+    }
+
+    fn min(self, other: Interval) -> Interval {
+        Interval::new(self.lo.min(other.lo), self.hi.min(other.hi))
+    }
+
+    fn max(self, other: Interval) -> Interval {
+        Interval::new(self.lo.max(other.lo), self.hi.max(other.hi))
+    }
+
+    fn length(xs: &[Interval]) -> Interval {
+        xs.iter()
+            .fold(Interval::new(0.0, 0.0), |sum, x| sum.add(x.sq()))
+            .map(f32::sqrt)
+    }
+}
+
+// What a value of the program can be over a box of points.
+#[derive(Debug, Clone, PartialEq, Default)]
+enum Range {
+    Scalar(Interval),
+    // Nothing if it could be either.
+    Bool(Option<bool>),
+    Vector(Vec<Interval>),
+    #[default]
+    Unknown,
+}
+
+impl Range {
+    fn point(x: f32) -> Range {
+        Range::Scalar(Interval::new(x, x))
+    }
+
+    fn scalar(&self) -> Interval {
+        match self {
+            Range::Scalar(x) => *x,
+            Range::Bool(Some(b)) => Interval::new(*b as u8 as f32, *b as u8 as f32),
+            Range::Bool(None) => Interval::new(0.0, 1.0),
+            _ => Interval::ALL,
+        }
+    }
+
+    fn hull(self, other: Range) -> Range {
+        match (self, other) {
+            (Range::Scalar(a), Range::Scalar(b)) => Range::Scalar(a.hull(b)),
+            (Range::Bool(a), Range::Bool(b)) if a == b => Range::Bool(a),
+            (Range::Bool(_), Range::Bool(_)) => Range::Bool(None),
+            (Range::Vector(a), Range::Vector(b)) if a.len() == b.len() => {
+                Range::Vector(a.into_iter().zip(b).map(|(a, b)| a.hull(b)).collect())
+            }
+            _ => Range::Unknown,
+        }
+    }
+
+    fn value(value: Value) -> Range {
+        match value {
+            Value::ScalarVal(x) => Range::point(x),
+            Value::BoolVal(b) => Range::Bool(Some(b)),
+            Value::Vec2Val(v) => Range::Vector(v.to_array().map(|x| Interval::new(x, x)).to_vec()),
+            Value::Vec3Val(v) => Range::Vector(v.to_array().map(|x| Interval::new(x, x)).to_vec()),
+        }
+    }
+}
+
+// Runs a program on intervals the way `eval` runs it on numbers, with `last`
+// the value of the program.
+#[derive(Default)]
+struct Ranges {
+    env: HashMap<String, Range>,
+    last: Range,
+}
+
+impl Ranges {
+    fn run(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Assign { var, rhs } => {
+                self.last = self.expr(rhs);
+                self.env.insert(var.clone(), self.last.clone());
+            }
+            Statement::AssignToArray { vars, rhs } => {
+                self.last = self.expr(rhs);
+                for (i, var) in vars.iter().enumerate() {
+                    let x = match &self.last {
+                        Range::Vector(xs) if i < xs.len() => Range::Scalar(xs[i]),
+                        _ => Range::Unknown,
+                    };
+                    self.env.insert(var.clone(), x);
+                }
+            }
+            Statement::AssignFromArray { vars, rhs } => {
+                let values: Vec<Range> = rhs.iter().map(|r| self.expr(r)).collect();
+                if let Some(last) = values.last() {
+                    self.last = last.clone();
+                }
+                for (var, value) in vars.iter().zip(values) {
+                    self.env.insert(var.clone(), value);
+                }
+            }
+            Statement::Sequence(stmts) => stmts.iter().for_each(|s| self.run(s)),
+            Statement::Return(expr) => self.last = self.expr(expr),
+            Statement::Empty => {}
+        }
+    }
+
+    fn scalar(&mut self, e: &Expr) -> Interval {
+        self.expr(e).scalar()
+    }
+
+    fn expr(&mut self, e: &Expr) -> Range {
+        match e {
+            Expr::Number(x) => Range::point(*x),
+            Expr::Variable(name) => self.env.get(name).cloned().unwrap_or(Range::Unknown),
+            Expr::Negate(a) => Range::Scalar(self.scalar(a).neg()),
+            Expr::BinaryOp(op) => self.binop(op),
+            Expr::Function { name, args } => self.function(name, args),
+            Expr::TernaryOp(c, t, f) => match self.expr(c) {
+                Range::Bool(Some(true)) => self.expr(t),
+                Range::Bool(Some(false)) => self.expr(f),
+                _ => {
+                    let t = self.expr(t);
+                    t.hull(self.expr(f))
+                }
+            },
+            // Inside a ternary it may not happen, so it is either.
+            Expr::Assign(AssignExpr::Inc(var) | AssignExpr::Dec(var)) => {
+                let step = if matches!(e, Expr::Assign(AssignExpr::Inc(_))) {
+                    1.0
+                } else {
+                    -1.0
+                };
+                let old = self
+                    .env
+                    .get(var)
+                    .cloned()
+                    .unwrap_or(Range::Unknown)
+                    .scalar();
+                let new = old.add(Interval::new(step, step));
+                self.env.insert(var.clone(), Range::Scalar(old.hull(new)));
+                Range::Scalar(new)
+            }
+        }
+    }
+
+    fn binop(&mut self, op: &BinOp) -> Range {
+        use BinOp::*;
+        let (a, b) = match op {
+            Add(a, b)
+            | Sub(a, b)
+            | Mul(a, b)
+            | Div(a, b)
+            | Pow(a, b)
+            | Eq(a, b)
+            | NotEq(a, b)
+            | Greater(a, b)
+            | GreaterEq(a, b)
+            | Less(a, b)
+            | LessEq(a, b)
+            | And(a, b)
+            | Or(a, b) => (self.expr(a), self.expr(b)),
+        };
+        let (x, y) = (a.scalar(), b.scalar());
+        // Whether `x < y`, or `x <= y` if `or_equal`, for all of them.
+        let less = |x: Interval, y: Interval, or_equal: bool| {
+            if x.hi < y.lo || (or_equal && x.hi <= y.lo) {
+                Some(true)
+            } else if x.lo > y.hi || (!or_equal && x.lo >= y.hi) {
+                Some(false)
+            } else {
+                None
+            }
+        };
+        let truth = |r: &Range| match r {
+            Range::Bool(b) => *b,
+            _ => None,
+        };
+        match op {
+            Add(..) => Range::Scalar(x.add(y)),
+            Sub(..) => Range::Scalar(x.sub(y)),
+            Mul(..) => Range::Scalar(x.mul(y)),
+            Div(..) => Range::Scalar(x.div(y)),
+            Pow(..) => match (x.point(), y.point()) {
+                (Some(x), Some(y)) => Range::point(x.powf(y)),
+                (_, Some(y)) if x.lo >= 0.0 && y >= 0.0 => Range::Scalar(x.map(|x| x.powf(y))),
+                _ => Range::Unknown,
+            },
+            Less(..) => Range::Bool(less(x, y, false)),
+            LessEq(..) => Range::Bool(less(x, y, true)),
+            Greater(..) => Range::Bool(less(y, x, false)),
+            GreaterEq(..) => Range::Bool(less(y, x, true)),
+            Eq(..) | NotEq(..) => {
+                let eq = match (x.point(), y.point()) {
+                    (Some(x), Some(y)) => Some(x == y),
+                    _ if x.hi < y.lo || y.hi < x.lo => Some(false),
+                    _ => None,
+                };
+                Range::Bool(if matches!(op, Eq(..)) {
+                    eq
+                } else {
+                    eq.map(|eq| !eq)
+                })
+            }
+            And(..) => Range::Bool(match (truth(&a), truth(&b)) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            }),
+            Or(..) => Range::Bool(match (truth(&a), truth(&b)) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            }),
+        }
+    }
+
+    fn function(&mut self, name: &FunctionName, args: &[Expr]) -> Range {
+        use FunctionName::*;
+        // `bound` is the one function that doesn't need all of its arguments.
+        if *name == FunctionName::Bound {
+            let b = self.scalar(&args[0]);
+            let e = self.expr(&args[1]);
+            return if b.lo > MARGIN {
+                Range::Scalar(b)
+            } else if b.hi <= MARGIN {
+                e
+            } else {
+                Range::Scalar(b.hull(e.scalar()))
+            };
+        }
+        let xs: Vec<Interval> = args.iter().map(|a| self.scalar(a)).collect();
+        // Like the optimizer, leave out the ones that read `a0` and `a1`.
+        let constant = !matches!(name, Rot0 | Rot1);
+        if let Some(points) = xs
+            .iter()
+            .map(Interval::point)
+            .collect::<Option<Vec<f32>>>()
+            .filter(|_| constant)
+        {
+            let args = points.into_iter().map(Expr::Number).collect();
+            return Range::value(eval_constant(&Expr::Function {
+                name: name.clone(),
+                args,
+            }));
+        }
+        // These never decrease as any of the distances grow, so with known
+        // parameters their ends are the blends of the ends.
+        let monotone: Option<fn(Vec<f32>) -> f32> = match name {
+            RoundMin => Some(functions::round_min),
+            PolyMin => Some(functions::poly_union),
+            PolyMax => Some(functions::poly_intersect),
+            CubicMin => Some(functions::cubic_union),
+            CubicMax => Some(functions::cubic_intersect),
+            RootMin => Some(functions::root_union),
+            RootMax => Some(functions::root_intersect),
+            ChamferMin => Some(functions::chamfer_union),
+            ChamferMax => Some(functions::chamfer_intersect),
+            _ => None,
+        };
+        if let Some(blend) = monotone {
+            let (ds, r) = xs.split_at(xs.len() - 1);
+            let Some(r) = r[0].point() else {
+                return Range::Unknown;
+            };
+            let end = |end: fn(&Interval) -> f32| blend(ds.iter().map(end).chain([r]).collect());
+            return Range::Scalar(Interval::new(end(|x| x.lo), end(|x| x.hi)));
+        }
+        // The closest distance, and any of the ids.
+        if *name == Closest {
+            let (ids, ds): (Vec<_>, Vec<_>) = xs.chunks(2).map(|pair| (pair[0], pair[1])).unzip();
+            let hull = |xs: Vec<Interval>, f: fn(Interval, Interval) -> Interval| {
+                xs.into_iter().reduce(f).unwrap_or(Interval::ALL)
+            };
+            return Range::Vector(vec![hull(ds, Interval::min), hull(ids, Interval::hull)]);
+        }
+        let scalar = match name {
+            Abs => xs[0].abs(),
+            Sqrt => xs[0].map(|x| x.max(0.0).sqrt()),
+            Exp => xs[0].map(f32::exp),
+            Exp2 => xs[0].map(f32::exp2),
+            Log if xs[0].lo > 0.0 => xs[0].map(f32::ln),
+            Log2 if xs[0].lo > 0.0 => xs[0].map(f32::log2),
+            Floor => xs[0].map(f32::floor),
+            Ceil => xs[0].map(f32::ceil),
+            Trunc => xs[0].map(f32::trunc),
+            Round => xs[0].map(f32::round),
+            Atan => xs[0].map(f32::atan),
+            Sinh => xs[0].map(f32::sinh),
+            Tanh => xs[0].map(f32::tanh),
+            Asinh => xs[0].map(f32::asinh),
+            Sin | Cos => Interval::new(-1.0, 1.0),
+            Min | Union => xs
+                .iter()
+                .copied()
+                .reduce(Interval::min)
+                .unwrap_or(Interval::ALL),
+            Max | Intersect => xs
+                .iter()
+                .copied()
+                .reduce(Interval::max)
+                .unwrap_or(Interval::ALL),
+            Clamp => xs[0].max(xs[1]).min(xs[2]),
+            Mix => xs[0]
+                .mul(Interval::new(1.0, 1.0).sub(xs[2]))
+                .add(xs[1].mul(xs[2])),
+            Length => Interval::length(&xs),
+            Box3 => {
+                let b = |i: usize| xs[if xs.len() > 4 { i } else { 3 }];
+                let q: Vec<Interval> = (0..3).map(|i| xs[i].abs().sub(b(3 + i))).collect();
+                let outside: Vec<Interval> =
+                    q.iter().map(|q| q.max(Interval::new(0.0, 0.0))).collect();
+                let inside = q[0].max(q[1]).max(q[2]).min(Interval::new(0.0, 0.0));
+                Interval::length(&outside).add(inside)
+            }
+            Torus => {
+                let ring = Interval::length(&xs[..2]).sub(xs[3]);
+                Interval::length(&[ring, xs[2]]).sub(xs[4])
+            }
+            Translate => {
+                return Range::Vector((0..3).map(|i| xs[i].sub(xs[3 + i])).collect());
+            }
+            Scale => return Range::Vector(xs[..3].iter().map(|x| x.div(xs[3])).collect()),
+            Rotate | Rot0 | Rot1 | Rot => return self.rotation(name, &xs),
+            _ => return Range::Unknown,
+        };
+        Range::Scalar(scalar)
+    }
+}
+
+impl Ranges {
+    // A turn of the point at the start of `xs` by the rest of them, exactly
+    // if they are known.
+    fn rotation(&self, name: &FunctionName, xs: &[Interval]) -> Range {
+        let n = if *name == FunctionName::Rotate { 3 } else { 2 };
+        let known: Option<Vec<f32>> = xs[n..].iter().map(Interval::point).collect();
+        let param = |p: &str| match self.env.get(p) {
+            Some(Range::Scalar(a)) => a.point(),
+            _ => None,
+        };
+        let rows: Option<Vec<Vec<f32>>> = known.and_then(|k| match name {
+            FunctionName::Rotate => {
+                let axis = Vec3::new(k[0], k[1], k[2]).normalize();
+                let m = Mat3::from_quat(Quat::from_axis_angle(axis, -k[3]));
+                Some((0..3).map(|i| m.row(i).to_array().to_vec()).collect())
+            }
+            FunctionName::Rot => Some(vec![vec![k[0], k[1]], vec![-k[1], k[0]]]),
+            _ => {
+                let p = if *name == FunctionName::Rot0 {
+                    "a0"
+                } else {
+                    "a1"
+                };
+                let a = k.first().copied().or_else(|| param(p))?;
+                let m = Mat2::from_angle(a * TAU);
+                Some((0..2).map(|i| m.row(i).to_array().to_vec()).collect())
+            }
+        });
+        match rows {
+            Some(rows) => Range::Vector(
+                rows.iter()
+                    .map(|row| {
+                        row.iter()
+                            .zip(xs)
+                            .fold(Interval::new(0.0, 0.0), |sum, (&m, x)| {
+                                sum.add(x.mul(Interval::new(m, m)))
+                            })
+                    })
+                    .collect(),
+            ),
+            // Turning something keeps it as far from the origin.
+            None => {
+                let r = Interval::length(&xs[..n]).hi;
+                Range::Vector(vec![Interval::new(-r, r); n])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pratt::parse;
+
+    #[test]
+    fn rays() {
+        let bound = Bound::new(Vec3::splat(-1.0), Vec3::splat(1.0));
+        let ro = Vec3::new(0.0, 0.0, -5.0);
+        assert_eq!(bound.ray(ro, Vec3::Z), Some((4.0, 6.0)));
+        assert_eq!(bound.ray(ro, Vec3::Y), None);
+        assert_eq!(bound.ray(Vec3::ZERO, Vec3::X), Some((0.0, 1.0)));
+        assert_eq!(bound.distance(ro), 4.0);
+        assert!(bound.contains(Vec3::splat(0.5)) && !bound.contains(ro));
+    }
+
+    #[test]
+    fn estimates() {
+        let estimate = |source: &str| Bound::estimate(&parse(&mut &*source), [("a0", 0.1)]);
+        let ball = estimate("L(x-3,y,z)-1").unwrap();
+        assert!(
+            ball.contains(Vec3::new(4.0, 0.0, 0.0)) && ball.contains(Vec3::new(3.0, -1.0, 0.0))
+        );
+        assert!(ball.max.x < 6.0 && ball.min.x > 0.0);
+        let both = estimate("U(bx3(x,y+2,z,1,2,3),don(x-5,y,z,2,.5))").unwrap();
+        assert!(
+            both.contains(Vec3::new(-1.0, -4.0, -3.0)) && both.contains(Vec3::new(7.5, 0.0, 0.5))
+        );
+        let tagged = estimate("[d,id]=iU(0,bx3(x,y+2,z,1,2,3),1,don(x-5,y,z,2,.5)),d").unwrap();
+        assert_eq!(tagged, both);
+        let blend = estimate("rU(L(x,y,z)-1,L(x-2,y,z)-1,.5)").unwrap();
+        assert!(
+            blend.contains(Vec3::new(-1.0, 0.0, 0.0)) && blend.contains(Vec3::new(3.0, 0.0, 0.0))
+        );
+        let turned = estimate("[a,b,c]=rotate(x,y,z,1,0,0,.5),bx3(a,b,c,2)").unwrap();
+        assert!(turned.contains(Vec3::new(2.0, 2.0, 2.0)));
+        assert!(turned.max.max_element() < 6.0);
+        let fractal = estimate("bound(bx3(x,y,z-1,1.5),mbulb(x,y,z-1,8,4))").unwrap();
+        assert!(fractal.contains(Vec3::new(1.2, 0.0, 1.0)));
+        assert!(fractal.max.z < 5.0 && fractal.min.z > -3.0);
+        // Planes go on forever and fractals could be anything.
+        assert_eq!(estimate("y+1"), None);
+        assert_eq!(estimate("mbulb(x,y,z,8,4)"), None);
+        assert_eq!(estimate("r0(x,y)"), None);
+    }
+
+    #[test]
+    fn samples() {
+        let ball: Sdf = Box::new(|p: Vec3| (p - Vec3::X * 3.0).length() - 1.0);
+        let bound = Bound::sample(&ball, 32).unwrap();
+        assert!(
+            bound.contains(Vec3::new(2.0, 0.0, 0.0)) && bound.contains(Vec3::new(3.0, 0.9, 0.0))
+        );
+        assert!(bound.max.x < 4.5 && bound.min.x > 1.5);
+        let plane: Sdf = Box::new(|p: Vec3| p.y);
+        assert_eq!(Bound::sample(&plane, 16), None);
+        let bounded = bounded(ball, bound);
+        assert_eq!(bounded(Vec3::new(3.0, 0.0, 0.0)), -1.0);
+        assert_eq!(
+            bounded(Vec3::new(-10.0, 0.0, 0.0)),
+            bound.distance(Vec3::new(-10.0, 0.0, 0.0))
+        );
+    }
+}
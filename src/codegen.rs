@@ -1,16 +1,178 @@
-use crate::ast::{AssignExpr, BinOp, Expr, FunctionName, Statement};
+use crate::ast::{children, operands, AssignExpr, BinOp, Expr, FunctionName, Statement};
+use crate::optimize::{defines, exprs, lower_increments};
 use pretty::RcDoc;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::process::{Command, Stdio};
 
-pub fn generate_code(ast: &Statement, a0: f32, a1: f32) -> String {
-    let doc = RcDoc::text("pub fn signed_distance_fucntion(p: Vec3) -> f32 {")
-        .append(RcDoc::line())
-        .append(RcDoc::text("let Vec3 {x, y, z} = p;"))
-        .append(RcDoc::line())
-        .append(RcDoc::text(format!("let (a0, a1) = ({}, {});", a0, a1)))
-        .append(RcDoc::line())
-        .append(ast.to_doc())
+// Operator precedence as in Rust. Unary minus binds tighter than any binary
+// operator and a method call receiver tighter still.
+const NEGATE: u8 = 6;
+const RECEIVER: u8 = 7;
+
+// Keywords that the DSL allows as variable names.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where",
+    "while", "yield",
+];
+
+// Constants clippy wants to see by name.
+const CONSTS: &[(f32, &str)] = &[
+    (std::f32::consts::PI, "PI"),
+    (std::f32::consts::TAU, "TAU"),
+    (std::f32::consts::E, "E"),
+    (std::f32::consts::FRAC_PI_2, "FRAC_PI_2"),
+    (std::f32::consts::FRAC_PI_3, "FRAC_PI_3"),
+    (std::f32::consts::FRAC_PI_4, "FRAC_PI_4"),
+    (std::f32::consts::FRAC_PI_6, "FRAC_PI_6"),
+    (std::f32::consts::FRAC_PI_8, "FRAC_PI_8"),
+    (std::f32::consts::FRAC_1_PI, "FRAC_1_PI"),
+    (std::f32::consts::SQRT_2, "SQRT_2"),
+    (std::f32::consts::FRAC_1_SQRT_2, "FRAC_1_SQRT_2"),
+    (std::f32::consts::LN_2, "LN_2"),
+    (std::f32::consts::LN_10, "LN_10"),
+];
+
+// DSL names can't contain `_`, so `aB` can become the snake case `a_b` without
+// running into another name.
+fn ident(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        return format!("r#{}", name);
+    }
+    let mut s = String::new();
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            s.push('_');
+        }
+        s.push(c.to_ascii_lowercase());
+    }
+    s
+}
+
+// Whether the program reads the value `var` has on entry before assigning it.
+fn reads_input(stmts: &[Statement], var: &str) -> bool {
+    fn reads(e: &Expr, var: &str) -> bool {
+        match e {
+            Expr::Variable(v) => v == var,
+            Expr::Function {
+                name: FunctionName::Rot0,
+                ..
+            } if var == "a0" => true,
+            Expr::Function {
+                name: FunctionName::Rot1,
+                ..
+            } if var == "a1" => true,
+            e => children(e).into_iter().any(|c| reads(c, var)),
+        }
+    }
+    for stmt in stmts {
+        if exprs(stmt).into_iter().any(|e| reads(e, var)) {
+            return true;
+        }
+        if defines(stmt).iter().any(|v| *v == var) {
+            return false;
+        }
+    }
+    false
+}
+
+fn unassigned<'a>(e: &'a Expr, assigned: &BTreeSet<&str>) -> Option<&'a str> {
+    match e {
+        Expr::Variable(v) | Expr::Assign(AssignExpr::Inc(v) | AssignExpr::Dec(v))
+            if !assigned.contains(v.as_str()) =>
+        {
+            Some(v)
+        }
+        e => children(e)
+            .into_iter()
+            .find_map(|c| unassigned(c, assigned)),
+    }
+}
+
+// A function `name(p: Vec3) -> f32` that computes the same value as the
+// program, i.e. the value of its last statement.
+pub fn generate_code(ast: &Statement, name: &str, a0: f32, a1: f32) -> String {
+    let signature = format!("pub fn {}(p: Vec3) -> f32 {{", ident(name));
+    function(ast, signature, a0, a1, false)
+}
+
+// Like `generate_code`, but the function also passes the value of every
+// variable to `trace` as soon as it is assigned.
+pub fn generate_traced_code(ast: &Statement, name: &str, a0: f32, a1: f32) -> String {
+    let signature = format!(
+        "pub fn {}(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {{",
+        ident(name)
+    );
+    function(ast, signature, a0, a1, true)
+}
+
+fn function(ast: &Statement, signature: String, a0: f32, a1: f32, traced: bool) -> String {
+    let ast = lower_increments(ast);
+    let Statement::Sequence(stmts) = &ast else {
+        unreachable!("lowered programs are sequences")
+    };
+    // Rust won't compile a read of a variable that was never assigned, the
+    // interpreter fails on it too, so say which one it is.
+    let mut assigned: BTreeSet<&str> = ["x", "y", "z", "a0", "a1"].into();
+    for stmt in stmts {
+        for e in exprs(stmt) {
+            if let Some(var) = unassigned(e, &assigned) {
+                panic!("`{}` is read before it's assigned", var);
+            }
+        }
+        assigned.extend(defines(stmt).into_iter().map(String::as_str));
+    }
+    // Only bind what's read so the generated code has no unused variables.
+    let point = ["x", "y", "z"].map(|v| {
+        if reads_input(stmts, v) {
+            v.to_string()
+        } else {
+            format!("{}: _", v)
+        }
+    });
+    let mut body = RcDoc::text(format!("let Vec3 {{ {} }} = p;", point.join(", ")));
+    for (var, value) in [("a0", a0), ("a1", a1)] {
+        if reads_input(stmts, var) {
+            body = body
+                .append(RcDoc::line())
+                .append(RcDoc::text(format!("let {} = {:?}f32;", var, value)));
+        }
+    }
+    let signature = if traced && stmts.iter().all(|stmt| defines(stmt).is_empty()) {
+        signature.replacen("trace:", "_trace:", 1)
+    } else {
+        signature
+    };
+    for (i, stmt) in stmts.iter().enumerate() {
+        body = body
+            .append(RcDoc::line())
+            .append(statement_doc(stmt, i + 1 == stmts.len()));
+        if traced {
+            for var in defines(stmt) {
+                body = body.append(RcDoc::line()).append(RcDoc::text(format!(
+                    "trace(\"{}\", {}.into());",
+                    var,
+                    ident(var)
+                )));
+            }
+        }
+    }
+    match stmts.last() {
+        Some(Statement::Assign { var, .. }) => {
+            body = body.append(RcDoc::line()).append(RcDoc::text(ident(var)));
+        }
+        Some(Statement::AssignFromArray { vars, .. }) => {
+            let var = vars.last().unwrap();
+            body = body.append(RcDoc::line()).append(RcDoc::text(ident(var)));
+        }
+        _ => {}
+    }
+    let doc = RcDoc::text(signature)
+        .append(RcDoc::line().append(body).nest(4))
         .append(RcDoc::line())
-        .nest(4)
         .append(RcDoc::text("}"));
 
     let mut w = Vec::new();
@@ -18,39 +180,195 @@ pub fn generate_code(ast: &Statement, a0: f32, a1: f32) -> String {
     String::from_utf8(w).unwrap()
 }
 
+// A self contained module for the program: the imports it needs, the
+// function from `generate_code` and a `scene()` for `sdfs`.
+pub fn generate_module(ast: &Statement, name: &str, a0: f32, a1: f32) -> String {
+    module(ast, name, a0, a1, false)
+}
+
+// A module as from `generate_module` with an additional `traced` function
+// from `generate_traced_code`, which `name` calls with a trace that does
+// nothing.
+pub fn generate_traced_module(ast: &Statement, name: &str, a0: f32, a1: f32) -> String {
+    module(ast, name, a0, a1, true)
+}
+
+fn module(ast: &Statement, name: &str, a0: f32, a1: f32, traced: bool) -> String {
+    let mut uses: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for (path, item) in [("arrow::sdfs", "Scene"), ("glam", "Vec3")] {
+        uses.entry(path).or_default().insert(item);
+    }
+    if traced {
+        uses.entry("arrow::eval").or_default().insert("Value");
+    }
+    let mut stmts = vec![ast];
+    while let Some(stmt) = stmts.pop() {
+        match stmt {
+            Statement::Sequence(ss) => stmts.extend(ss),
+            Statement::Assign { rhs, .. } | Statement::AssignToArray { rhs, .. } => {
+                add_imports(rhs, &mut uses)
+            }
+            Statement::AssignFromArray { rhs, .. } => {
+                rhs.iter().for_each(|e| add_imports(e, &mut uses))
+            }
+            Statement::Return(e) => add_imports(e, &mut uses),
+            Statement::Empty => {}
+        }
+    }
+
+    let mut code = format!(
+        "// Generated by `arrow::codegen` from the `{}` scene.\n",
+        name
+    );
+    code.push('\n');
+    for (path, items) in uses {
+        let items: Vec<&str> = items.into_iter().collect();
+        code.push_str(&format!("use {}::{{{}}};\n", path, items.join(", ")));
+    }
+    code.push('\n');
+    if traced {
+        code.push_str(&format!(
+            "pub fn {}(p: Vec3) -> f32 {{\n    traced(p, &mut |_, _| {{}})\n}}\n\n",
+            ident(name)
+        ));
+        code.push_str(&generate_traced_code(ast, "traced", a0, a1));
+    } else {
+        code.push_str(&generate_code(ast, name, a0, a1));
+    }
+    code.push_str(&format!(
+        "\n\npub fn scene() -> Scene {{\n    Scene {{ name: {:?}, sdf: {} }}\n}}\n",
+        name,
+        ident(name)
+    ));
+    format_code(&code)
+}
+
+fn add_imports<'a>(e: &Expr, uses: &mut BTreeMap<&'a str, BTreeSet<&'a str>>) {
+    let mut add = |path, item| {
+        uses.entry(path).or_default().insert(item);
+    };
+    match e {
+        Expr::Number(_) | Expr::Variable(_) | Expr::Assign(_) => {}
+        Expr::Negate(a) => add_imports(a, uses),
+        Expr::BinaryOp(op) => {
+            let (a, b) = operands(op);
+            add_imports(a, uses);
+            add_imports(b, uses);
+        }
+        Expr::TernaryOp(c, t, f) => {
+            add_imports(c, uses);
+            add_imports(t, uses);
+            add_imports(f, uses);
+        }
+        Expr::Function { name, args } => {
+            // The crate's macros expand to code that names these unqualified.
+            let n = args.len();
+            match name.rust_name().strip_suffix('!') {
+                Some(m) => {
+                    add("arrow", m);
+                    match name {
+                        FunctionName::Length | FunctionName::Normalize if n == 2 => {
+                            add("glam", "Vec2")
+                        }
+                        FunctionName::Length | FunctionName::Normalize => add("arrow::core", "v3"),
+                        FunctionName::Distance | FunctionName::Dot if n == 4 => add("glam", "Vec2"),
+                        FunctionName::AddMul if n < 6 => add("glam", "Vec2"),
+                        FunctionName::Box2 => add("glam", "Vec2"),
+                        FunctionName::Box3 => {
+                            add("arrow::core", "v3");
+                            add("arrow::core", "I");
+                            add("arrow::core", "ZERO3");
+                            add("arrow::sdf", "sd_box");
+                        }
+                        FunctionName::ValueNoise => add("arrow::noise", "fbm_value"),
+                        FunctionName::Bound => add("arrow::bound", "MARGIN"),
+                        _ => {}
+                    }
+                }
+                None => add("arrow::functions", name.rust_name()),
+            }
+            args.iter().for_each(|a| add_imports(a, uses));
+        }
+    }
+}
+
+// Runs the code through rustfmt if it is installed, otherwise returns it as is.
+pub fn format_code(code: &str) -> String {
+    let child = Command::new("rustfmt")
+        .args(["--edition", "2021"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(mut child) = child else {
+        return code.to_string();
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        if stdin.write_all(code.as_bytes()).is_err() {
+            return code.to_string();
+        }
+    }
+    match child.wait_with_output() {
+        Ok(out) if out.status.success() => {
+            String::from_utf8(out.stdout).unwrap_or_else(|_| code.to_string())
+        }
+        _ => code.to_string(),
+    }
+}
+
+fn list<'a>(docs: impl Iterator<Item = RcDoc<'a, ()>>) -> RcDoc<'a, ()> {
+    RcDoc::intersperse(docs, RcDoc::text(", "))
+}
+
+// Only the last statement is the value of a sequence.
+fn statement_doc(stmt: &Statement, last: bool) -> RcDoc<'_, ()> {
+    match stmt {
+        Statement::Return(expr) if !last => RcDoc::text("let _ = ")
+            .append(expr.to_doc(0))
+            .append(RcDoc::text(";")),
+        stmt => stmt.to_doc(),
+    }
+}
+
 impl Statement {
-    pub fn to_doc(&self) -> RcDoc<()> {
+    pub fn to_doc(&self) -> RcDoc<'_, ()> {
         match *self {
             Statement::Assign { ref var, ref rhs } => RcDoc::text("let ")
-                .append(RcDoc::as_string(var))
+                .append(RcDoc::text(ident(var)))
                 .append(RcDoc::text(" = "))
                 .append(rhs.to_doc(0))
                 .append(RcDoc::text(";")),
 
-            Statement::AssignToArray { ref vars, ref rhs } => RcDoc::text("let [")
-                .append(RcDoc::intersperse(
-                    vars.iter().map(|arg| RcDoc::text(arg)),
-                    RcDoc::text(", "),
-                ))
-                .append(RcDoc::text("] = "))
-                .append(rhs.to_doc(0))
-                .append(RcDoc::text(";")),
+            // The vector valued functions return glam vectors, rotations
+            // return arrays.
+            Statement::AssignToArray { ref vars, ref rhs } => {
+                let to_array = matches!(
+                    **rhs,
+                    Expr::Function {
+                        name: FunctionName::Cross | FunctionName::Normalize | FunctionName::AddMul,
+                        ..
+                    }
+                );
+                RcDoc::text("let [")
+                    .append(list(vars.iter().map(|v| RcDoc::text(ident(v)))))
+                    .append(RcDoc::text("] = "))
+                    .append(rhs.to_doc(if to_array { RECEIVER } else { 0 }))
+                    .append(RcDoc::text(if to_array { ".to_array();" } else { ";" }))
+            }
 
             Statement::AssignFromArray { ref vars, ref rhs } => RcDoc::text("let [")
-                .append(RcDoc::intersperse(
-                    vars.iter().map(|arg| RcDoc::text(arg)),
-                    RcDoc::text(", "),
-                ))
+                .append(list(vars.iter().map(|v| RcDoc::text(ident(v)))))
                 .append(RcDoc::text("] = ["))
-                .append(RcDoc::intersperse(
-                    rhs.iter().map(|arg| arg.to_doc(0)),
-                    RcDoc::text(", "),
-                ))
+                .append(list(rhs.iter().map(|arg| arg.to_doc(0))))
                 .append(RcDoc::text("];")),
 
-            Statement::Sequence(ref stmts) => {
-                RcDoc::intersperse(stmts.iter().map(|stmt| stmt.to_doc()), RcDoc::line())
-            }
+            Statement::Sequence(ref stmts) => RcDoc::intersperse(
+                stmts
+                    .iter()
+                    .enumerate()
+                    .map(|(i, stmt)| statement_doc(stmt, i + 1 == stmts.len())),
+                RcDoc::line(),
+            ),
 
             Statement::Return(ref expr) => expr.to_doc(0),
 
@@ -60,277 +378,327 @@ impl Statement {
 
     pub fn to_pretty(&self, width: usize) -> String {
         let mut w = Vec::new();
-        self.to_doc().render(width, &mut w).unwrap();
+        lower_increments(self)
+            .to_doc()
+            .render(width, &mut w)
+            .unwrap();
         String::from_utf8(w).unwrap()
     }
 }
 
 fn get_precedence(binop: &BinOp) -> u8 {
     match *binop {
-        BinOp::Add(_, _) | BinOp::Sub(_, _) => 1,
-        BinOp::Mul(_, _) | BinOp::Div(_, _) => 2,
+        BinOp::Or(_, _) => 1,
+        BinOp::And(_, _) => 2,
         BinOp::Eq(_, _)
         | BinOp::NotEq(_, _)
         | BinOp::Greater(_, _)
         | BinOp::GreaterEq(_, _)
         | BinOp::Less(_, _)
         | BinOp::LessEq(_, _) => 3,
-        BinOp::And(_, _) | BinOp::Or(_, _) => 4,
-        BinOp::Pow(_, _) => 5,
+        BinOp::Add(_, _) | BinOp::Sub(_, _) => 4,
+        BinOp::Mul(_, _) | BinOp::Div(_, _) => 5,
+        BinOp::Pow(_, _) => RECEIVER,
+    }
+}
+
+fn parens(doc: RcDoc<'_, ()>, needed: bool) -> RcDoc<'_, ()> {
+    if needed {
+        RcDoc::text("(").append(doc).append(RcDoc::text(")"))
+    } else {
+        doc
     }
 }
 
 impl Expr {
-    pub fn to_doc(&self, precedence: u8) -> RcDoc<()> {
+    pub fn to_doc(&self, precedence: u8) -> RcDoc<'_, ()> {
         match *self {
-            Expr::Number(n) => RcDoc::as_string(format!("{}f32", n)),
-            Expr::BinaryOp(ref op) => op.to_doc(precedence),
-            Expr::Negate(ref e) => RcDoc::text("-").append(e.to_doc(precedence)),
-            Expr::Function { ref name, ref args }
-                if *name == FunctionName::Union
-                    || *name == FunctionName::RoundMin
-                    || *name == FunctionName::Intersect
-                    || *name == FunctionName::RoundMax =>
-            {
-                let mut doc = name.to_doc().append(RcDoc::text("(vec!["));
-                for (i, arg) in args.iter().enumerate() {
-                    doc = doc.append(arg.to_doc(0));
-                    if i < args.len() - 1 {
-                        doc = doc.append(RcDoc::text(", "));
-                    }
-                }
-                doc.append(RcDoc::text("])"))
+            Expr::Number(n) => {
+                let text = match CONSTS.iter().find(|(c, _)| *c == n.abs()) {
+                    Some((_, name)) if n < 0.0 => format!("-std::f32::consts::{}", name),
+                    Some((_, name)) => format!("std::f32::consts::{}", name),
+                    None => format!("{}f32", n),
+                };
+                parens(
+                    RcDoc::as_string(text),
+                    n.is_sign_negative() && precedence > NEGATE,
+                )
             }
+            Expr::BinaryOp(ref op) => op.to_doc(precedence),
+            Expr::Negate(ref e) => parens(
+                RcDoc::text("-").append(e.to_doc(NEGATE)),
+                precedence > NEGATE,
+            ),
+            Expr::Function { ref name, ref args } if name.takes_list() => name
+                .to_doc()
+                .append(RcDoc::text("(vec!["))
+                .append(list(args.iter().map(|arg| arg.to_doc(0))))
+                .append(RcDoc::text("])")),
             Expr::Function { ref name, ref args } => {
-                let mut doc = name.to_doc().append(RcDoc::text("("));
-                for (i, arg) in args.iter().enumerate() {
-                    doc = doc.append(arg.to_doc(0));
-                    if i < args.len() - 1 {
-                        doc = doc.append(RcDoc::text(", "));
+                let mut docs: Vec<RcDoc<()>> = args.iter().map(|arg| arg.to_doc(0)).collect();
+                // Fill in the arguments the interpreter defaults.
+                match *name {
+                    FunctionName::Rot0 => docs.push(RcDoc::text("a0")),
+                    FunctionName::Rot1 => docs.push(RcDoc::text("a1")),
+                    FunctionName::Hash => {
+                        while docs.len() < 3 {
+                            docs.push(RcDoc::text("0f32"));
+                        }
+                    }
+                    FunctionName::SmoothAbs | FunctionName::PolySmoothAbs if docs.len() == 1 => {
+                        docs.push(RcDoc::text("0.5f32"))
                     }
+                    _ => {}
                 }
-                if *name == FunctionName::Rot0 {
-                    doc = doc.append(RcDoc::text(", a0"));
-                } else if *name == FunctionName::Rot1 {
-                    doc = doc.append(RcDoc::text(", a1"));
+                // `min(a, b, c)` becomes `min(min(a, b), c)`.
+                if *name == FunctionName::Min || *name == FunctionName::Max {
+                    return docs
+                        .into_iter()
+                        .reduce(|a, b| {
+                            name.to_doc()
+                                .append(RcDoc::text("("))
+                                .append(list([a, b].into_iter()))
+                                .append(RcDoc::text(")"))
+                        })
+                        .unwrap();
                 }
-                doc.append(RcDoc::text(")"))
+                name.to_doc()
+                    .append(RcDoc::text("("))
+                    .append(list(docs.into_iter()))
+                    .append(RcDoc::text(")"))
             }
-            Expr::Variable(ref s) => RcDoc::text(s),
-            Expr::TernaryOp(ref cond, ref if_true, ref if_false) => RcDoc::text("if ")
-                .append(cond.to_doc(precedence))
-                .append(RcDoc::text(" { "))
-                .append(if_true.to_doc(precedence))
-                .append(RcDoc::text(" } else { "))
-                .append(if_false.to_doc(precedence))
-                .append(RcDoc::text(" }")),
+            Expr::Variable(ref s) => RcDoc::text(ident(s)),
+            Expr::TernaryOp(ref cond, ref if_true, ref if_false) => parens(
+                RcDoc::text("if ")
+                    .append(cond.to_doc(0))
+                    .append(RcDoc::text(" { "))
+                    .append(if_true.to_doc(0))
+                    .append(RcDoc::text(" } else { "))
+                    .append(if_false.to_doc(0))
+                    .append(RcDoc::text(" }")),
+                precedence > 0,
+            ),
             Expr::Assign(ref assign) => match assign {
-                AssignExpr::Inc(ref s) => RcDoc::text("let ")
-                    .append(RcDoc::as_string(s))
-                    .append(RcDoc::text(" = "))
-                    .append(RcDoc::as_string(s))
-                    .append(RcDoc::text(" + 1;")),
-                AssignExpr::Dec(ref s) => RcDoc::text("let ")
-                    .append(RcDoc::as_string(s))
-                    .append(RcDoc::text(" = "))
-                    .append(RcDoc::as_string(s))
-                    .append(RcDoc::text(" - 1;")),
+                AssignExpr::Inc(s) | AssignExpr::Dec(s) => {
+                    panic!("`{}` must be lowered with `lower_increments` first", s)
+                }
             },
         }
     }
 }
 
 impl BinOp {
-    pub fn to_doc(&self, precedence: u8) -> RcDoc<()> {
-        let op_prec = get_precedence(self);
-        let (left, right) = if precedence > op_prec {
-            (RcDoc::text("("), RcDoc::text(")"))
-        } else {
-            (RcDoc::nil(), RcDoc::nil())
-        };
-        match *self {
-            BinOp::Add(ref lhs, ref rhs) => {
-                let lhs = lhs.to_doc(op_prec);
-                let rhs = rhs.to_doc(op_prec);
-                left.append(lhs)
-                    .append(RcDoc::text(" + "))
-                    .append(rhs)
-                    .append(right)
-            }
-            BinOp::Sub(ref lhs, ref rhs) => {
-                let lhs = lhs.to_doc(op_prec);
-                let rhs = rhs.to_doc(op_prec);
-                left.append(lhs)
-                    .append(RcDoc::text(" - "))
-                    .append(rhs)
-                    .append(right)
-            }
-            BinOp::Mul(ref lhs, ref rhs) => {
-                let lhs = lhs.to_doc(op_prec);
-                let rhs = rhs.to_doc(op_prec);
-                left.append(lhs)
-                    .append(RcDoc::text(" * "))
-                    .append(rhs)
-                    .append(right)
-            }
-            BinOp::Div(ref lhs, ref rhs) => {
-                let lhs = lhs.to_doc(op_prec);
-                let rhs = rhs.to_doc(op_prec);
-                left.append(lhs)
-                    .append(RcDoc::text(" / "))
-                    .append(rhs)
-                    .append(right)
-            }
-            BinOp::Eq(ref lhs, ref rhs) => {
-                let lhs = lhs.to_doc(op_prec);
-                let rhs = rhs.to_doc(op_prec);
-                left.append(lhs)
-                    .append(RcDoc::text(" == "))
-                    .append(rhs)
-                    .append(right)
-            }
-            BinOp::NotEq(ref lhs, ref rhs) => {
-                let lhs = lhs.to_doc(op_prec);
-                let rhs = rhs.to_doc(op_prec);
-                left.append(lhs)
-                    .append(RcDoc::text(" != "))
-                    .append(rhs)
-                    .append(right)
-            }
-            BinOp::Greater(ref lhs, ref rhs) => {
-                let lhs = lhs.to_doc(op_prec);
-                let rhs = rhs.to_doc(op_prec);
-                left.append(lhs)
-                    .append(RcDoc::text(" > "))
-                    .append(rhs)
-                    .append(right)
-            }
-            BinOp::GreaterEq(ref lhs, ref rhs) => {
-                let lhs = lhs.to_doc(op_prec);
-                let rhs = rhs.to_doc(op_prec);
-                left.append(lhs)
-                    .append(RcDoc::text(" >= "))
-                    .append(rhs)
-                    .append(right)
-            }
-            BinOp::Less(ref lhs, ref rhs) => {
-                let lhs = lhs.to_doc(op_prec);
-                let rhs = rhs.to_doc(op_prec);
-                left.append(lhs)
-                    .append(RcDoc::text(" < "))
-                    .append(rhs)
-                    .append(right)
-            }
-            BinOp::LessEq(ref lhs, ref rhs) => {
-                let lhs = lhs.to_doc(op_prec);
-                let rhs = rhs.to_doc(op_prec);
-                left.append(lhs)
-                    .append(RcDoc::text(" <= "))
-                    .append(rhs)
-                    .append(right)
-            }
-            BinOp::And(ref lhs, ref rhs) => {
-                let lhs = lhs.to_doc(op_prec);
-                let rhs = rhs.to_doc(op_prec);
-                left.append(lhs)
-                    .append(RcDoc::text(" && "))
-                    .append(rhs)
-                    .append(right)
+    pub fn to_doc(&self, precedence: u8) -> RcDoc<'_, ()> {
+        let (lhs, rhs) = operands(self);
+        let op = match *self {
+            BinOp::Add(_, _) => " + ",
+            BinOp::Sub(_, _) => " - ",
+            BinOp::Mul(_, _) => " * ",
+            BinOp::Div(_, _) => " / ",
+            BinOp::Eq(_, _) => " == ",
+            BinOp::NotEq(_, _) => " != ",
+            BinOp::Greater(_, _) => " > ",
+            BinOp::GreaterEq(_, _) => " >= ",
+            BinOp::Less(_, _) => " < ",
+            BinOp::LessEq(_, _) => " <= ",
+            BinOp::And(_, _) => " && ",
+            BinOp::Or(_, _) => " || ",
+            BinOp::Pow(_, _) => {
+                return lhs
+                    .to_doc(RECEIVER)
+                    .append(RcDoc::text(".powf("))
+                    .append(rhs.to_doc(0))
+                    .append(RcDoc::text(")"))
             }
-            BinOp::Or(ref lhs, ref rhs) => {
-                let lhs = lhs.to_doc(op_prec);
-                let rhs = rhs.to_doc(op_prec);
-                left.append(lhs)
-                    .append(RcDoc::text(" || "))
-                    .append(rhs)
-                    .append(right)
-            }
-            BinOp::Pow(ref lhs, ref rhs) => lhs
-                .to_doc(precedence)
-                .append(RcDoc::text(".powf(").append(rhs.to_doc(precedence)))
-                .append(RcDoc::text(")")),
-        }
+        };
+        let op_prec = get_precedence(self);
+        // Operators associate to the left, and comparisons don't chain.
+        let lhs_prec = if op_prec == 3 { op_prec + 1 } else { op_prec };
+        parens(
+            lhs.to_doc(lhs_prec)
+                .append(RcDoc::text(op))
+                .append(rhs.to_doc(op_prec + 1)),
+            precedence > op_prec,
+        )
     }
 }
 
 impl FunctionName {
-    pub fn to_doc(&self) -> RcDoc<()> {
+    pub fn to_doc(&self) -> RcDoc<'_, ()> {
+        RcDoc::text(self.rust_name())
+    }
+
+    // Whether the generated code passes the arguments as one `Vec`, for the
+    // functions that take any number of them.
+    pub fn takes_list(&self) -> bool {
+        use FunctionName::*;
+        matches!(
+            self,
+            Union
+                | Intersect
+                | RoundMin
+                | RoundMax
+                | PolyMin
+                | PolyMax
+                | CubicMin
+                | CubicMax
+                | ExpMin
+                | ExpMax
+                | RootMin
+                | RootMax
+                | CircularMin
+                | CircularMax
+                | ChamferMin
+                | ChamferMax
+                | StairsMin
+                | StairsMax
+                | ColumnsMin
+                | ColumnsMax
+                | Groove
+                | Tongue
+                | Pipe
+                | Kifs
+                | KifsTrap
+                | Closest
+        )
+    }
+
+    // The name of the function or macro (ending in `!`) the generated code calls.
+    pub fn rust_name(&self) -> &'static str {
         match *self {
-            FunctionName::Sin => RcDoc::text("sin"),
-            FunctionName::Cos => RcDoc::text("cos"),
-            FunctionName::Acos => RcDoc::text("acos"),
-            FunctionName::Asin => RcDoc::text("asin"),
-            FunctionName::Tan => RcDoc::text("tan"),
-            FunctionName::Atan => RcDoc::text("atan"),
-            FunctionName::Atan2 => RcDoc::text("atan2"),
-            FunctionName::Sinh => RcDoc::text("sinh"),
-            FunctionName::Cosh => RcDoc::text("cosh"),
-            FunctionName::Tanh => RcDoc::text("tanh"),
-            FunctionName::Asinh => RcDoc::text("asinh"),
-            FunctionName::Acosh => RcDoc::text("acosh"),
-            FunctionName::Atanh => RcDoc::text("atanh"),
-            FunctionName::Exp => RcDoc::text("exp"),
-            FunctionName::Exp2 => RcDoc::text("exp2"),
-            FunctionName::Log => RcDoc::text("log"),
-            FunctionName::Log2 => RcDoc::text("log2"),
-            FunctionName::Pow => RcDoc::text("pow"),
-            FunctionName::Sqrt => RcDoc::text("sqrt"),
-            FunctionName::Abs => RcDoc::text("abs"),
-            FunctionName::Sign => RcDoc::text("signum"),
-            FunctionName::Floor => RcDoc::text("floor"),
-            FunctionName::Ceil => RcDoc::text("ceil"),
-            FunctionName::Trunc => RcDoc::text("trunc"),
-            FunctionName::Fract => RcDoc::text("fract"),
-            FunctionName::Mod => RcDoc::text("modulo"),
-            FunctionName::Min => RcDoc::text("min"),
-            FunctionName::Max => RcDoc::text("max"),
-            FunctionName::Clamp => RcDoc::text("clamp"),
-            FunctionName::Mix => RcDoc::text("mix"),
-            FunctionName::Smoothstep => RcDoc::text("smoothstep"),
-            FunctionName::Length => RcDoc::text("length!"),
-            FunctionName::Distance => RcDoc::text("distance!"),
-            FunctionName::Dot => RcDoc::text("dot!"),
-            FunctionName::Union => RcDoc::text("union"),
-            FunctionName::Intersect => RcDoc::text("intersect"),
-            FunctionName::Cross => RcDoc::text("cross"),
-            FunctionName::Normalize => RcDoc::text("normalize!"),
-            FunctionName::RoundMin => RcDoc::text("round_min"),
-            FunctionName::RoundMax => RcDoc::text("round_max"),
-            FunctionName::SmoothAbs => RcDoc::text("smooth_abs"),
-            FunctionName::PolySmoothAbs => RcDoc::text("poly_smooth_abs"),
-            FunctionName::SmoothClamp => RcDoc::text("smooth_clamp"),
-            FunctionName::PolySmoothClamp => RcDoc::text("poly_smooth_clamp"),
-            FunctionName::ValueNoise => RcDoc::text("value_noise!"),
-            FunctionName::Torus => RcDoc::text("torus"),
-            FunctionName::Box2 => RcDoc::text("box2!"),
-            FunctionName::Box3 => RcDoc::text("box3!"),
-            FunctionName::Rot0 => RcDoc::text("rot0"),
-            FunctionName::Rot1 => RcDoc::text("rot0"),
-            FunctionName::Rot => RcDoc::text("rot"),
-            FunctionName::Triangle => RcDoc::text("triangle"),
-            FunctionName::Corner => RcDoc::text("corner"),
-            FunctionName::FakeSine => RcDoc::text("fake_sine"),
-            FunctionName::Hash => RcDoc::text("hash"),
-            FunctionName::AddMul => RcDoc::text("add_mul!"),
-            FunctionName::Round => RcDoc::text("round"),
+            FunctionName::Sin => "sin",
+            FunctionName::Cos => "cos",
+            FunctionName::Acos => "acos",
+            FunctionName::Asin => "asin",
+            FunctionName::Tan => "tan",
+            FunctionName::Atan => "atan",
+            FunctionName::Atan2 => "atan2",
+            FunctionName::Sinh => "sinh",
+            FunctionName::Cosh => "cosh",
+            FunctionName::Tanh => "tanh",
+            FunctionName::Asinh => "asinh",
+            FunctionName::Acosh => "acosh",
+            FunctionName::Atanh => "atanh",
+            FunctionName::Exp => "exp",
+            FunctionName::Exp2 => "exp2",
+            FunctionName::Log => "log",
+            FunctionName::Log2 => "log2",
+            FunctionName::Pow => "pow",
+            FunctionName::Sqrt => "sqrt",
+            FunctionName::Abs => "abs",
+            FunctionName::Sign => "sign",
+            FunctionName::Floor => "floor",
+            FunctionName::Ceil => "ceil",
+            FunctionName::Trunc => "trunc",
+            FunctionName::Fract => "fract",
+            FunctionName::Mod => "modulo",
+            FunctionName::Min => "min",
+            FunctionName::Max => "max",
+            FunctionName::Clamp => "clamp",
+            FunctionName::Mix => "mix",
+            FunctionName::Smoothstep => "smoothstep",
+            FunctionName::Length => "length!",
+            FunctionName::Distance => "distance!",
+            FunctionName::Dot => "dot!",
+            FunctionName::Union => "union",
+            FunctionName::Intersect => "intersect",
+            FunctionName::Cross => "cross",
+            FunctionName::Normalize => "normalize!",
+            FunctionName::RoundMin => "round_min",
+            FunctionName::RoundMax => "round_max",
+            FunctionName::SmoothAbs => "smooth_abs",
+            FunctionName::PolySmoothAbs => "poly_smooth_abs",
+            FunctionName::SmoothClamp => "smooth_clamp",
+            FunctionName::PolySmoothClamp => "poly_smooth_clamp",
+            FunctionName::ValueNoise => "value_noise!",
+            FunctionName::Torus => "torus",
+            FunctionName::Box2 => "box2!",
+            FunctionName::Box3 => "box3!",
+            FunctionName::Cone => "cone",
+            FunctionName::CappedCone => "capped_cone",
+            FunctionName::RoundCone => "round_cone",
+            FunctionName::Ellipsoid => "ellipsoid",
+            FunctionName::HexPrism => "hex_prism",
+            FunctionName::TriPrism => "tri_prism",
+            FunctionName::Octahedron => "octahedron",
+            FunctionName::Pyramid => "pyramid",
+            FunctionName::Link => "link",
+            FunctionName::SolidAngle => "solid_angle",
+            FunctionName::CappedTorus => "capped_torus",
+            FunctionName::UdTriangle => "ud_triangle",
+            FunctionName::UdQuad => "ud_quad",
+            FunctionName::Hexagon => "hexagon",
+            FunctionName::Segment => "segment",
+            FunctionName::Triangle2 => "triangle2",
+            FunctionName::Extrude => "extrude",
+            FunctionName::Twist => "twist",
+            FunctionName::Bend => "bend",
+            FunctionName::Elongate => "elongate",
+            FunctionName::Onion => "onion",
+            FunctionName::Revolve => "revolve",
+            FunctionName::Repeat => "repeat",
+            FunctionName::Polar => "polar",
+            FunctionName::PolyMin => "poly_union",
+            FunctionName::PolyMax => "poly_intersect",
+            FunctionName::CubicMin => "cubic_union",
+            FunctionName::CubicMax => "cubic_intersect",
+            FunctionName::ExpMin => "exp_union",
+            FunctionName::ExpMax => "exp_intersect",
+            FunctionName::RootMin => "root_union",
+            FunctionName::RootMax => "root_intersect",
+            FunctionName::CircularMin => "circular_union",
+            FunctionName::CircularMax => "circular_intersect",
+            FunctionName::ChamferMin => "chamfer_union",
+            FunctionName::ChamferMax => "chamfer_intersect",
+            FunctionName::StairsMin => "stairs_union",
+            FunctionName::StairsMax => "stairs_intersect",
+            FunctionName::ColumnsMin => "columns_union",
+            FunctionName::ColumnsMax => "columns_intersect",
+            FunctionName::Groove => "groove",
+            FunctionName::Tongue => "tongue",
+            FunctionName::Pipe => "pipe",
+            FunctionName::Translate => "translate",
+            FunctionName::Rotate => "rotate",
+            FunctionName::Scale => "scale",
+            FunctionName::Mandelbulb => "mandelbulb",
+            FunctionName::MandelbulbTrap => "mandelbulb_trap",
+            FunctionName::Mandelbox => "mandelbox",
+            FunctionName::MandelboxTrap => "mandelbox_trap",
+            FunctionName::Menger => "menger",
+            FunctionName::MengerTrap => "menger_trap",
+            FunctionName::Sierpinski => "sierpinski",
+            FunctionName::SierpinskiTrap => "sierpinski_trap",
+            FunctionName::Kifs => "kifs",
+            FunctionName::KifsTrap => "kifs_trap",
+            FunctionName::SeededValue => "seeded_value",
+            FunctionName::Perlin => "perlin",
+            FunctionName::Simplex => "simplex",
+            FunctionName::Worley => "worley",
+            FunctionName::Cellular => "cellular",
+            FunctionName::Ridged => "ridged",
+            FunctionName::Billow => "billow",
+            FunctionName::Warp => "warp",
+            FunctionName::PerlinGradient => "perlin_gradient",
+            FunctionName::SimplexGradient => "simplex_gradient",
+            FunctionName::Bound => "bound!",
+            FunctionName::Closest => "closest",
+            FunctionName::Rot0 => "rot0",
+            FunctionName::Rot1 => "rot1",
+            FunctionName::Rot => "rot",
+            FunctionName::Triangle => "triangle",
+            FunctionName::Corner => "corner",
+            FunctionName::FakeSine => "fake_sine",
+            FunctionName::Hash => "hash",
+            FunctionName::AddMul => "add_mul!",
+            FunctionName::Round => "round",
         }
     }
 }
 
 #[cfg(test)]
-
 mod tests {
     use super::*;
     use crate::pratt::parse;
-    use crate::sdf::examples;
-
-    #[test]
-    fn show() {
-        let examples = examples();
-        let (mut input, _) = examples.get("ghost").unwrap();
-        let ast = parse(&mut input);
-        print!("{}", generate_code(&ast, 0.1, 0.2));
-    }
 
     #[test]
     fn assign_variable() {
@@ -343,23 +711,24 @@ mod tests {
 
     #[test]
     fn sequence() {
-        let mut stmts = Vec::new();
-        stmts.push(Statement::Assign {
-            var: "x".to_string(),
-            rhs: Box::new(Expr::Number(1.0)),
-        });
-        stmts.push(Statement::Assign {
-            var: "s".to_string(),
-            rhs: Box::new(Expr::Number(2.3)),
-        });
+        let stmts = vec![
+            Statement::Assign {
+                var: "x".to_string(),
+                rhs: Box::new(Expr::Number(1.0)),
+            },
+            Statement::Assign {
+                var: "s".to_string(),
+                rhs: Box::new(Expr::Number(2.3)),
+            },
+        ];
         let ast = Statement::Sequence(stmts);
-        assert_eq!(ast.to_pretty(80), "x = 1f32;\nlet s = 2.3f32;");
+        assert_eq!(ast.to_pretty(80), "let x = 1f32;\nlet s = 2.3f32;");
 
         let mut i = "x += y / 2; s = U(x,y,z)";
         let ast = parse(&mut i);
         assert_eq!(
             ast.to_pretty(80),
-            "x = x + y / 2f32;\nlet s = union(x, y, z);"
+            "let x = x + y / 2f32;\nlet s = union(vec![x, y, z]);"
         );
     }
 
@@ -403,6 +772,9 @@ mod tests {
             }),
         };
         assert_eq!(ast.to_pretty(80), "let t = atan2(1f32, 2f32);");
+
+        let mut i = "min(x, y, z)";
+        assert_eq!(parse(&mut i).to_pretty(80), "min(min(x, y), z)");
     }
 
     #[test]
@@ -439,6 +811,49 @@ mod tests {
     #[test]
     fn assign_expr() {
         let ast = Statement::Return(Box::new(Expr::Assign(AssignExpr::Inc("s".to_string()))));
-        assert_eq!(ast.to_pretty(80), "let s = s + 1;");
+        assert_eq!(ast.to_pretty(80), "let s_1 = s + 1f32;\ns_1");
+
+        // Only incremented when that side is taken.
+        let mut i = "i=0,c=x,d=c>0?i++:i,L(x,y,z)-d";
+        let code = generate_code(&parse(&mut i), "inc", 0.1, 0.2);
+        assert!(code.contains("let cond_1 = c > 0f32;"));
+        assert!(code.contains("let i_2 = if cond_1 { i + 1f32 } else { i };"));
+        assert!(code.contains("let d = if cond_1 { i_2 } else { i_2 };"));
+    }
+
+    #[test]
+    fn precedence() {
+        let mut i = "a = -(x + y) * 2; b = (x - y) - (z - x); c = -x ** 2; d = (x + y) ** -2; e = x < y == y < z";
+        let ast = parse(&mut i);
+        assert_eq!(
+            ast.to_pretty(80),
+            "let a = -(x + y) * 2f32;\n\
+             let b = x - y - (z - x);\n\
+             let c = (-x).powf(2f32);\n\
+             let d = (x + y).powf(-2f32);\n\
+             let e = (x < y) == (y < z);"
+        );
+    }
+
+    #[test]
+    fn module() {
+        let mut i = "[x,y]=r1(x,y), h=ri(x), L(x,y,z)-h";
+        let ast = parse(&mut i);
+        let code = generate_module(&ast, "ball", 0.1, 0.2);
+        assert!(code.contains("pub fn ball(p: Vec3) -> f32 {"));
+        assert!(code.contains("rot1(x, y, a1)"));
+        assert!(code.contains("hash(x, 0f32, 0f32)"));
+        assert!(code.contains("use arrow::functions::{hash, rot1};"));
+        assert!(code.contains("sdf: ball"));
+    }
+
+    #[test]
+    fn unassigned() {
+        let generate = |mut i: &'static str| {
+            std::panic::catch_unwind(move || generate_code(&parse(&mut i), "f", 0.0, 0.0))
+        };
+        assert!(generate("a=x,U(a,g)").is_err());
+        assert!(generate("c,L(x,y,z)-1").is_err());
+        assert!(generate("g=1,U(x,g)").is_ok());
     }
 }
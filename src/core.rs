@@ -1,7 +1,7 @@
-use ::noise::{Fbm, NoiseFn, Perlin};
-use glam::{Affine3A, Vec3};
-use noise::MultiFractal;
-// use serde::de;
+use crate::blend;
+use crate::noise::{Basis, Fbm};
+use glam::{Affine3A, Vec2, Vec3};
+use serde::{Deserialize, Serialize};
 
 pub const I: Affine3A = Affine3A::IDENTITY;
 pub const LUM: f32 = 0.3;
@@ -20,17 +20,111 @@ pub fn modulo(a: f32, b: f32) -> f32 {
     ((a % b) + b) % b
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LightKind {
+    #[default]
+    Point,
+    Directional,
+    Spot,
+    Sphere,
+    Environment,
+}
+
+// Point, spot and sphere lights are at `position`, directional lights shine
+// from its direction. Spot lights point at `target` with a cone of `angle`
+// degrees that fades out over another `falloff` degrees. Sphere lights have a
+// `radius` and environment lights an equirectangular `image`, both cast
+// `samples` shadow rays. Lights with a position are dimmed by `attenuation`
+// times the square of their distance.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Light {
+    pub kind: LightKind,
     pub position: Vec3,
     pub intensity: f32,
+    pub attenuation: f32,
+    pub target: Vec3,
+    pub angle: f32,
+    pub falloff: f32,
+    pub radius: f32,
+    pub samples: u32,
+    pub image: Option<String>,
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Light::new(ZERO3, 1.0)
+    }
 }
 
 impl Light {
-    pub fn new(position: Vec3, intensity: f32) -> Self {
+    // A point light.
+    pub const fn new(position: Vec3, intensity: f32) -> Self {
         Self {
+            kind: LightKind::Point,
             position,
             intensity,
+            attenuation: 0.0,
+            target: ZERO3,
+            angle: 30.0,
+            falloff: 5.0,
+            radius: 1.0,
+            samples: 16,
+            image: None,
+        }
+    }
+
+    pub fn directional(from: Vec3, intensity: f32) -> Self {
+        Self {
+            kind: LightKind::Directional,
+            ..Light::new(from, intensity)
+        }
+    }
+
+    pub fn spot(position: Vec3, target: Vec3, angle: f32, intensity: f32) -> Self {
+        Self {
+            kind: LightKind::Spot,
+            target,
+            angle,
+            ..Light::new(position, intensity)
+        }
+    }
+
+    pub fn sphere(position: Vec3, radius: f32, intensity: f32) -> Self {
+        Self {
+            kind: LightKind::Sphere,
+            radius,
+            ..Light::new(position, intensity)
+        }
+    }
+
+    pub fn environment(image: &str, intensity: f32) -> Self {
+        Self {
+            kind: LightKind::Environment,
+            image: Some(image.to_string()),
+            ..Light::new(ZERO3, intensity)
+        }
+    }
+
+    // How much of its light reaches `p`, going by distance alone.
+    pub fn attenuate(&self, p: Vec3) -> f32 {
+        match self.kind {
+            LightKind::Directional | LightKind::Environment => 1.0,
+            _ => 1.0 / (1.0 + self.attenuation * self.position.distance_squared(p)),
+        }
+    }
+
+    // How much of a spot light's cone `p` is in, 1 for other lights.
+    pub fn cone(&self, p: Vec3) -> f32 {
+        if self.kind != LightKind::Spot {
+            return 1.0;
         }
+        let axis = (self.target - self.position).normalize();
+        let cos = (p - self.position).normalize().dot(axis);
+        let inner = self.angle.to_radians().cos();
+        let outer = (self.angle + self.falloff).to_radians().cos();
+        ((cos - outer) / (inner - outer).max(1e-6)).clamp(0.0, 1.0)
     }
 }
 
@@ -39,17 +133,60 @@ pub type Lum = f32;
 
 pub type Sdf = Box<dyn Fn(Vec3) -> f32 + Sync>;
 
+// The signed distance to a shape in the plane.
+pub type Sdf2 = Box<dyn Fn(Vec2) -> f32 + Sync>;
+
+// An affine map placing a shape, with its inverse, which takes points to the
+// shape's own space, worked out once rather than at every sample. Distances
+// in the shape's space are multiplied by `scale`, the smallest of the map's
+// scales along its axes, which is exact for uniform scales and a bound for
+// the others.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    inverse: Affine3A,
+    scale: f32,
+}
+
+impl Transform {
+    pub fn new(transform: Affine3A) -> Self {
+        let m = transform.matrix3;
+        Transform {
+            inverse: transform.inverse(),
+            scale: m
+                .x_axis
+                .length()
+                .min(m.y_axis.length())
+                .min(m.z_axis.length()),
+        }
+    }
+
+    // `p` in the shape's space.
+    pub fn point(&self, p: Vec3) -> Vec3 {
+        self.inverse.transform_point3(p)
+    }
+
+    // A distance in the shape's space as a distance in the world.
+    pub fn distance(&self, d: f32) -> f32 {
+        d * self.scale
+    }
+
+    pub fn apply(self, sdf: Sdf) -> Sdf {
+        Box::new(move |p| self.distance(sdf(self.point(p))))
+    }
+}
+
+impl From<Affine3A> for Transform {
+    fn from(transform: Affine3A) -> Self {
+        Transform::new(transform)
+    }
+}
+
 pub fn union(sdf1: Sdf, sdf2: Sdf) -> Sdf {
     Box::new(move |p| sdf1(p).min(sdf2(p)))
 }
 
 pub fn smooth_union(sdf1: Sdf, sdf2: Sdf, k: f32) -> Sdf {
-    Box::new(move |p| {
-        let d1 = sdf1(p);
-        let d2 = sdf2(p);
-        let h = (k - (d1 - d2).abs()).max(0.0);
-        d1.min(d2) - h * h * 0.25 / k
-    })
+    Box::new(move |p| blend::poly(sdf1(p), sdf2(p), k))
 }
 
 pub fn intersect(sdf1: Sdf, sdf2: Sdf) -> Sdf {
@@ -57,24 +194,29 @@ pub fn intersect(sdf1: Sdf, sdf2: Sdf) -> Sdf {
 }
 
 pub fn smooth_intersection(sdf1: Sdf, sdf2: Sdf, k: f32) -> Sdf {
-    Box::new(move |p| {
-        let d1 = sdf1(p);
-        let d2 = sdf2(p);
-        let h = (k - (d1 - d2).abs()).max(0.0);
-        d1.max(d2) + h * h * 0.25 / k
-    })
+    Box::new(move |p| blend::max(|a, b| blend::poly(a, b, k), sdf1(p), sdf2(p)))
 }
 
 pub fn difference(sdf1: Sdf, sdf2: Sdf) -> Sdf {
     Box::new(move |p| sdf1(p).max(-sdf2(p)))
 }
 
+// Unlike `difference` this is `sdf2` without `sdf1`.
 pub fn smooth_difference(sdf1: Sdf, sdf2: Sdf, k: f32) -> Sdf {
+    Box::new(move |p| blend::max(|a, b| blend::poly(a, b, k), sdf2(p), -sdf1(p)))
+}
+
+// Joins the shapes with any of the unions in `blend`, e.g.
+// `blend(sdfs, |a, b| blend::chamfer(a, b, 0.5))`.
+pub fn blend<F>(sdfs: Vec<Sdf>, f: F) -> Sdf
+where
+    F: Fn(f32, f32) -> f32 + Sync + Send + 'static,
+{
     Box::new(move |p| {
-        let d1 = sdf1(p);
-        let d2 = sdf2(p);
-        let h = (k - (-d1 - d2).abs()).max(0.0);
-        d2.max(-d1) + h * h * 0.25 / k
+        sdfs.iter()
+            .map(|sdf| sdf(p))
+            .reduce(&f)
+            .expect("a blend needs a shape")
     })
 }
 
@@ -113,6 +255,24 @@ pub fn round(sdf: Sdf, radius: f32) -> Sdf {
     Box::new(move |p| sdf(p) - radius)
 }
 
+pub fn transform(sdf: Sdf, transform: Affine3A) -> Sdf {
+    Transform::new(transform).apply(sdf)
+}
+
+pub fn translate(sdf: Sdf, offset: Vec3) -> Sdf {
+    Box::new(move |p| sdf(p - offset))
+}
+
+// Turns the shape `angle` radians around `axis`, counterclockwise looking
+// down the axis.
+pub fn rotate(sdf: Sdf, axis: Vec3, angle: f32) -> Sdf {
+    transform(sdf, Affine3A::from_axis_angle(axis.normalize(), angle))
+}
+
+pub fn scale(sdf: Sdf, s: f32) -> Sdf {
+    Box::new(move |p| sdf(p / s) * s)
+}
+
 pub fn unions(sdfs: Vec<Sdf>) -> Sdf {
     sdfs.into_iter().reduce(|acc, sdf| union(acc, sdf)).unwrap()
 }
@@ -135,122 +295,183 @@ pub fn repeat_z(sdf: Sdf, space: f32) -> Sdf {
     Box::new(move |p| sdf(v3(p.x, p.y, p.z - space * (p.z / space).round())))
 }
 
+// Deforming space stretches distances, so a deformed distance can overshoot
+// and the marcher step through the surface. When the deformation's gradient
+// is at most `l` the distance divided by `l` is a bound again.
+pub fn lipschitz(sdf: Sdf, l: f32) -> Sdf {
+    Box::new(move |p| sdf(p) / l)
+}
+
+// Turns the xz plane by `k` radians per unit of y. Within `r` of the y axis
+// the gradient grows to sqrt(1 + (k * r)^2).
+pub fn twist(sdf: Sdf, k: f32) -> Sdf {
+    Box::new(move |p| {
+        let (s, c) = (k * p.y).sin_cos();
+        sdf(v3(c * p.x - s * p.z, p.y, s * p.x + c * p.z))
+    })
+}
+
+// Bends the xy plane by turning it `k` radians per unit of x, cheap because
+// it is just a twist around z. Within `r` of the z axis the gradient grows
+// to sqrt(1 + (k * r)^2).
+pub fn bend(sdf: Sdf, k: f32) -> Sdf {
+    Box::new(move |p| {
+        let (s, c) = (k * p.x).sin_cos();
+        sdf(v3(c * p.x - s * p.y, s * p.x + c * p.y, p.z))
+    })
+}
+
+// Pulls the shape apart by `h` each way along each axis, filling the gap
+// with the shape's cross sections. Exact outside, a bound inside.
+pub fn elongate(sdf: Sdf, h: Vec3) -> Sdf {
+    Box::new(move |p| sdf(p - p.clamp(-h, h)))
+}
+
+// A shell `thickness` thick around the surface, exact if `sdf` is.
+pub fn onion(sdf: Sdf, thickness: f32) -> Sdf {
+    Box::new(move |p| sdf(p).abs() - thickness)
+}
+
+// The shape in the xy plane pulled `height` each way along z, exact if
+// `shape` is.
+pub fn extrude(shape: Sdf2, height: f32) -> Sdf {
+    Box::new(move |p| crate::sdf::extrusion(shape(Vec2::new(p.x, p.y)), p.z, height))
+}
+
+// The shape in the xy plane spun around the y axis with its own y axis
+// `offset` from it, exact if `shape` is.
+pub fn revolve(shape: Sdf2, offset: f32) -> Sdf {
+    Box::new(move |p| shape(Vec2::new(Vec2::new(p.x, p.z).length() - offset, p.y)))
+}
+
+// Copies of the shape `space` apart, `limit` of them each way along each
+// axis. Like the `repeat_` functions this only looks at the nearest copy, so
+// it is exact only if the shape stays within its cell.
+pub fn repeat_limited(sdf: Sdf, space: f32, limit: Vec3) -> Sdf {
+    Box::new(move |p| sdf(p - space * (p / space).round().clamp(-limit, limit)))
+}
+
+// `n` copies of the shape around the y axis, the first one on the x axis.
+// Exact only if the shape stays within its wedge of 1/n of a turn.
+pub fn repeat_polar(sdf: Sdf, n: f32) -> Sdf {
+    Box::new(move |p| {
+        let [x, z] = crate::functions::polar(p.x, p.z, n);
+        sdf(v3(x, p.y, z))
+    })
+}
+
 pub fn mix(a: f32, b: f32, t: f32) -> f32 {
     a * (1.0 - t) + b * t
 }
 
+// `octaves` of Perlin noise at `frequency`, between about -`amplitude` and
+// `amplitude`.
 pub struct Noise {
-    octaves: u32,
     frequency: f32,
     amplitude: f32,
-    nf: Fbm<Perlin>,
+    fbm: Fbm,
 }
 
 impl Noise {
-    pub fn new(octaves: u32, frequency: f32, amplitude: f32) -> Self {
-        let nf = Fbm::<Perlin>::new(0);
+    pub fn new(octaves: u32, frequency: f32, amplitude: f32, seed: u32) -> Self {
         Self {
-            octaves,
             frequency,
             amplitude,
-            nf,
+            fbm: Fbm::new(Basis::Perlin, seed, octaves),
         }
     }
 
     pub fn get(&self, p: Vec3) -> f32 {
-        let mut value = 0.0;
-        let mut amplitude = self.amplitude as f64;
-        let mut frequency = self.frequency as f64;
-        for _ in 0..self.octaves {
-            value += amplitude
-                * self.nf.get([
-                    p.x as f64 * frequency,
-                    p.y as f64 * frequency,
-                    p.z as f64 * frequency,
-                ]);
-            amplitude *= 0.5;
-            frequency *= 2.0;
-        }
-        0.5 * value as f32
-    }
-}
-
-pub fn hash(p: Vec3) -> f32 // replace this by something better
-{
-    let mut p = (p * 0.3183099 + 0.1).fract();
-    p *= 17.0;
-    (p.x * p.y * p.z * (p.x + p.y + p.z)).fract()
-}
-
-// Return a value between -1 and 1.
-pub fn noise(x: Vec3) -> f32 {
-    let i: Vec3 = x.floor();
-    let f: Vec3 = x.fract();
-    let f = f * f * (3.0 - 2.0 * f);
-
-    mix(
-        mix(
-            mix(
-                hash(i + v3(0.0, 0.0, 0.0)),
-                hash(i + v3(1.0, 0.0, 0.0)),
-                f.x,
-            ),
-            mix(
-                hash(i + v3(0.0, 1.0, 0.0)),
-                hash(i + v3(1.0, 1.0, 0.0)),
-                f.x,
-            ),
-            f.y,
-        ),
-        mix(
-            mix(
-                hash(i + v3(0.0, 0.0, 1.0)),
-                hash(i + v3(1.0, 0.0, 1.0)),
-                f.x,
-            ),
-            mix(
-                hash(i + v3(0.0, 1.0, 1.0)),
-                hash(i + v3(1.0, 1.0, 1.0)),
-                f.x,
-            ),
-            f.y,
-        ),
-        f.z,
-    ) * 2.0
-        - 1.0
-}
-
-pub fn fbm_perlin(x: f32, y: f32, z: f32, scale: f32, offset: f32, octaves: u32) -> f32 {
-    let mut p = v3(x, y, z) * scale;
-    let voff = v3(offset, offset, offset);
-    p = p * scale + voff;
-    let nf = Fbm::<Perlin>::new(0).set_octaves(octaves as usize);
-    nf.get([p.x as f64, p.y as f64, p.z as f64]) as f32
-}
-
-// The range of the noise depends on the number of octaves.
-pub fn fbm_value(x: f32, y: f32, z: f32, scale: f32, offset: f32, octaves: u32) -> f32 {
-    let mut p = v3(x, y, z) * scale;
-    let voff = v3(offset, offset, offset);
-    let mut a = 1.0;
-    let mut sum = 0.0;
-    for _ in 1..=octaves {
-        a *= 0.5;
-        sum += a * noise(p + voff);
-        p *= 2.03;
+        self.amplitude * self.fbm.get(p * self.frequency)
     }
-    sum
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_noise() {
+        let noise = Noise::new(4, 0.5, 2.0, 1);
         for i in 0..100 {
             let w = i as f32;
-            let n = fbm_value(w / 2.375, w / 11.8, w / 20.73, 0.03, 1.0, 4);
-            println!("Noise: {}", n);
+            let n = noise.get(v3(w / 2.375, w / 11.8, w / 20.73));
+            assert!(n.abs() <= 2.0);
         }
+        let p = v3(0.3, 1.7, 2.2);
+        assert_ne!(noise.get(p), Noise::new(4, 0.5, 2.0, 2).get(p));
+    }
+
+    #[test]
+    fn domains() {
+        let ball = || -> Sdf { Box::new(|p: Vec3| p.length() - 1.0) };
+        let p = v3(3.0, 0.0, 0.5);
+        assert_eq!(twist(ball(), 2.0)(p), ball()(p));
+        assert_eq!(
+            bend(ball(), 2.0)(v3(0.0, 3.0, 0.5)),
+            ball()(v3(0.0, 3.0, 0.5))
+        );
+        assert_eq!(
+            elongate(ball(), v3(2.0, 0.0, 0.0))(p),
+            0.5f32.hypot(1.0) - 1.0
+        );
+        assert_eq!(onion(ball(), 0.1)(ZERO3), 0.9);
+        let disk: Sdf2 = Box::new(|p: Vec2| p.length() - 1.0);
+        assert_eq!(extrude(disk, 1.0)(v3(0.0, 0.0, 3.0)), 2.0);
+        let disk: Sdf2 = Box::new(|p: Vec2| p.length() - 1.0);
+        assert_eq!(revolve(disk, 3.0)(v3(0.0, 0.0, 3.0)), -1.0);
+        // Two copies each way, then nothing.
+        let row = repeat_limited(ball(), 4.0, v3(2.0, 0.0, 0.0));
+        assert_eq!(row(v3(8.0, 0.0, 0.0)), -1.0);
+        assert_eq!(row(v3(12.0, 0.0, 0.0)), 3.0);
+        let ring = repeat_polar(
+            Box::new(|p: Vec3| (p - v3(3.0, 0.0, 0.0)).length() - 1.0),
+            4.0,
+        );
+        assert!((ring(v3(0.0, 0.0, -3.0)) + 1.0).abs() < 1e-6);
+        assert!((ring(v3(0.0, 0.0, 0.0)) - 2.0).abs() < 1e-6);
+        assert_eq!(lipschitz(ball(), 2.0)(v3(3.0, 0.0, 0.0)), 1.0);
+    }
+
+    #[test]
+    fn transforms() {
+        use std::f32::consts::FRAC_PI_2;
+        let ball = || -> Sdf { Box::new(|p: Vec3| p.length() - 1.0) };
+        let big = transform(ball(), Affine3A::from_scale(v(2.0)));
+        assert_eq!(big(v3(5.0, 0.0, 0.0)), 3.0);
+        // Stretched along x the distance is a bound.
+        let long = transform(ball(), Affine3A::from_scale(v3(3.0, 1.0, 1.0)));
+        assert!(long(v3(0.0, 3.0, 0.0)) <= 2.0 && long(v3(5.0, 0.0, 0.0)) <= 2.0);
+        assert_eq!(scale(ball(), 2.0)(v3(5.0, 0.0, 0.0)), 3.0);
+        assert_eq!(
+            translate(ball(), v3(0.0, 2.0, 0.0))(v3(0.0, 2.0, 0.0)),
+            -1.0
+        );
+        let moved = || translate(ball(), v3(3.0, 0.0, 0.0));
+        let turned = rotate(moved(), Vec3::Z, FRAC_PI_2);
+        assert!((turned(v3(0.0, 3.0, 0.0)) + 1.0).abs() < 1e-6);
+        let [x, y, z] = crate::functions::rotate(0.0, 3.0, 0.0, 0.0, 0.0, 1.0, FRAC_PI_2);
+        assert!((moved()(v3(x, y, z)) + 1.0).abs() < 1e-6);
+        let t = Transform::new(Affine3A::from_translation(v3(1.0, 2.0, 3.0)));
+        assert_eq!(t.point(v3(1.0, 2.0, 3.0)), ZERO3);
+        assert_eq!(t.distance(2.0), 2.0);
+    }
+
+    #[test]
+    fn lights() {
+        let spot = Light::spot(v3(0.0, 10.0, 0.0), ZERO3, 20.0, 1.0);
+        assert_eq!(spot.cone(v3(0.0, -5.0, 0.0)), 1.0);
+        assert_eq!(spot.cone(v3(10.0, 0.0, 0.0)), 0.0);
+        let edge = v3(10.0 * 22.5f32.to_radians().tan(), 0.0, 0.0);
+        assert!(spot.cone(edge) > 0.0 && spot.cone(edge) < 1.0);
+        assert_eq!(Light::new(ZERO3, 1.0).attenuate(v3(0.0, 0.0, 100.0)), 1.0);
+        let dim = Light {
+            attenuation: 0.01,
+            ..Light::new(ZERO3, 1.0)
+        };
+        assert_eq!(dim.attenuate(v3(0.0, 0.0, 10.0)), 0.5);
+        let light: Light =
+            toml::from_str("kind = \"sphere\"\nposition = [0, 5, 0]\nradius = 2").unwrap();
+        assert_eq!(light, Light::sphere(v3(0.0, 5.0, 0.0), 2.0, 1.0));
     }
 }
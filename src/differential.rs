@@ -0,0 +1,195 @@
+// Checks that compiled versions of a program compute the same distances as
+// the interpreter, e.g. the output of `codegen` or the hand written scenes in
+// `sdfs`. For functions from `codegen::generate_traced_code` a divergence is
+// traced back to the first statement whose value differs.
+use crate::ast::Statement;
+use crate::eval::{eval, make_sdf, Environment, Value};
+use crate::optimize::{defines, lower_increments};
+use glam::Vec3;
+use std::fmt;
+
+pub const TOLERANCE: f32 = 1e-4;
+
+pub type Traced = fn(Vec3, &mut dyn FnMut(&'static str, Value)) -> f32;
+
+#[derive(Debug, Clone)]
+pub struct Divergence {
+    pub point: Vec3,
+    pub expected: f32,
+    pub actual: f32,
+    pub origin: Option<Origin>,
+}
+
+// The first assignment where the two disagree, `statement` is its Rust code.
+#[derive(Debug, Clone)]
+pub struct Origin {
+    pub statement: String,
+    pub var: String,
+    pub expected: Value,
+    pub actual: Value,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "at {}: interpreter {}, compiled {}",
+            self.point, self.expected, self.actual
+        )?;
+        if let Some(origin) = &self.origin {
+            write!(
+                f,
+                "\n  starting at `{}` where {} is {:?} instead of {:?}",
+                origin.statement, origin.var, origin.actual, origin.expected
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// `n` points spread evenly over the cube of side `2 * radius` around `center`.
+// They come from the additive recurrence with the generalized golden ratio,
+// so they are the same on every run.
+pub fn sample_points(center: Vec3, radius: f32, n: usize) -> Vec<Vec3> {
+    let phi = 1.220_744_1_f64;
+    let alpha = [1.0 / phi, 1.0 / (phi * phi), 1.0 / (phi * phi * phi)];
+    (0..n)
+        .map(|i| {
+            let u = alpha.map(|a| (0.5 + a * i as f64).fract() as f32);
+            center + radius * (2.0 * Vec3::from(u) - 1.0)
+        })
+        .collect()
+}
+
+pub fn agree(a: f32, b: f32) -> bool {
+    a == b
+        || (a.is_nan() && b.is_nan())
+        || (a - b).abs() <= TOLERANCE * a.abs().max(b.abs()).max(1.0)
+}
+
+fn agree_values(a: Value, b: Value) -> bool {
+    match (a, b) {
+        (Value::ScalarVal(a), Value::ScalarVal(b)) => agree(a, b),
+        (Value::BoolVal(a), Value::BoolVal(b)) => a == b,
+        (Value::Vec2Val(a), Value::Vec2Val(b)) => agree(a.x, b.x) && agree(a.y, b.y),
+        (Value::Vec3Val(a), Value::Vec3Val(b)) => {
+            agree(a.x, b.x) && agree(a.y, b.y) && agree(a.z, b.z)
+        }
+        _ => false,
+    }
+}
+
+// The value of every variable right after it is assigned, in the order the
+// assignments happen, with the index of the assigning statement in the
+// program from `lower_increments`.
+pub fn trace(ast: &Statement, a0: f32, a1: f32, p: Vec3) -> Vec<(usize, String, Value)> {
+    let Statement::Sequence(stmts) = lower_increments(ast) else {
+        unreachable!("lowered programs are sequences")
+    };
+    let mut env = Environment::new();
+    env.insert("a0".to_string(), Value::ScalarVal(a0));
+    env.insert("a1".to_string(), Value::ScalarVal(a1));
+    let mut out = Vec::new();
+    for (i, stmt) in stmts.iter().enumerate() {
+        eval(&mut env, stmt, p);
+        for var in defines(stmt) {
+            out.push((i, var.clone(), env[var]));
+        }
+    }
+    out
+}
+
+// The first of the points where `f` disagrees with the interpreter.
+pub fn compare<F>(ast: &Statement, a0: f32, a1: f32, f: F, points: &[Vec3]) -> Option<Divergence>
+where
+    F: Fn(Vec3) -> f32,
+{
+    points.iter().find_map(|&p| {
+        let expected = make_sdf(ast, a0, a1, p);
+        let actual = f(p);
+        (!agree(expected, actual)).then_some(Divergence {
+            point: p,
+            expected,
+            actual,
+            origin: None,
+        })
+    })
+}
+
+// Like `compare`, and also finds where the divergence starts.
+pub fn compare_traced(
+    ast: &Statement,
+    a0: f32,
+    a1: f32,
+    f: Traced,
+    points: &[Vec3],
+) -> Option<Divergence> {
+    let mut divergence = compare(ast, a0, a1, |p| f(p, &mut |_, _| {}), points)?;
+    let expected = trace(ast, a0, a1, divergence.point);
+    let mut actual = Vec::new();
+    f(divergence.point, &mut |var, value| {
+        actual.push((var, value))
+    });
+    let Statement::Sequence(stmts) = lower_increments(ast) else {
+        unreachable!("lowered programs are sequences")
+    };
+    // If every variable agrees it's the value of the last statement that
+    // doesn't.
+    let origin = expected
+        .into_iter()
+        .zip(actual)
+        .find(|((_, _, e), (_, a))| !agree_values(*e, *a))
+        .map(|((i, var, expected), (_, actual))| Origin {
+            statement: stmts[i].to_pretty(100),
+            var,
+            expected,
+            actual,
+        })
+        .unwrap_or_else(|| Origin {
+            statement: stmts.last().map_or(String::new(), |s| s.to_pretty(100)),
+            var: "the value".to_string(),
+            expected: Value::ScalarVal(divergence.expected),
+            actual: Value::ScalarVal(divergence.actual),
+        });
+    divergence.origin = Some(origin);
+    Some(divergence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::v3;
+    use crate::pratt::parse;
+
+    // A hand written `[x,y]=r1(x,y),d=x-1` that rotates by a0 instead.
+    fn wrong(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+        let [x, y] = crate::functions::rot0(p.x, p.y, 0.1);
+        trace("x", x.into());
+        trace("y", y.into());
+        let d = x - 1.0;
+        trace("d", d.into());
+        d
+    }
+
+    #[test]
+    fn sample() {
+        let points = sample_points(v3(1.0, 2.0, 3.0), 10.0, 1000);
+        assert!(points
+            .iter()
+            .all(|p| (*p - v3(1.0, 2.0, 3.0)).abs().max_element() <= 10.0));
+        let mean = points.iter().sum::<Vec3>() / 1000.0;
+        assert!((mean - v3(1.0, 2.0, 3.0)).length() < 0.5);
+    }
+
+    #[test]
+    fn finds_origin() {
+        let mut i = "[x,y]=r1(x,y),d=x-1";
+        let ast = parse(&mut i);
+        let points = sample_points(Vec3::ZERO, 5.0, 100);
+        let d = compare_traced(&ast, 0.1, 0.2, wrong, &points).unwrap();
+        let origin = d.origin.unwrap();
+        assert_eq!(origin.var, "x");
+        assert_eq!(origin.statement, "let [x, y] = rot1(x, y, a1);");
+        assert!(compare_traced(&ast, 0.1, 0.1, wrong, &points).is_none());
+    }
+}
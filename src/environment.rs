@@ -0,0 +1,103 @@
+// The image around a scene that an environment light shines from, mapped
+// onto the sphere of directions by longitude and latitude. HDR and EXR
+// images keep their range, other images are sRGB and become linear 0 to 1.
+use crate::core::Lum;
+use crate::tonemap::linear;
+use glam::Vec3;
+use image::DynamicImage;
+use std::f32::consts::PI;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Environment {
+    width: u32,
+    height: u32,
+    pixels: Vec<Lum>,
+}
+
+impl Environment {
+    pub fn new(width: u32, height: u32, pixels: Vec<Lum>) -> Self {
+        assert_eq!(pixels.len(), (width * height) as usize);
+        Environment {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> image::ImageResult<Self> {
+        let image = image::open(path)?;
+        // Only float images are linear, the rest are sRGB.
+        let float = matches!(
+            image,
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_)
+        );
+        let image = image.to_luma32f();
+        let (width, height) = image.dimensions();
+        let mut pixels = image.into_raw();
+        if !float {
+            pixels.iter_mut().for_each(|l| *l = linear(*l));
+        }
+        Ok(Environment::new(width, height, pixels))
+    }
+
+    // The light coming from direction `d`, +y is the top of the image and +z
+    // its middle.
+    pub fn lookup(&self, d: Vec3) -> Lum {
+        let d = d.normalize();
+        let u = 0.5 + d.x.atan2(d.z) / (2.0 * PI);
+        let v = d.y.clamp(-1.0, 1.0).acos() / PI;
+        let x = ((u * self.width as f32) as u32).min(self.width - 1);
+        let y = ((v * self.height as f32) as u32).min(self.height - 1);
+        self.pixels[(y * self.width + x) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    #[test]
+    fn lookup() {
+        // A sky over black ground, brighter towards +x.
+        let image = GrayImage::from_fn(8, 4, |x, y| {
+            Luma([if y < 2 { 100 + 10 * x as u8 } else { 0 }])
+        });
+        let path = std::env::temp_dir().join("arrow_environment.png");
+        image.save(&path).unwrap();
+        let environment = Environment::load(&path).unwrap();
+        assert_eq!(environment.lookup(Vec3::NEG_Y), 0.0);
+        assert!(environment.lookup(Vec3::Y) >= linear(100.0 / 255.0));
+        assert!(
+            environment.lookup(Vec3::new(1.0, 0.1, 0.0))
+                > environment.lookup(Vec3::new(-1.0, 0.1, 0.0))
+        );
+        assert_eq!(
+            environment.lookup(Vec3::new(0.0, 0.1, 1.0)),
+            linear(140.0 / 255.0)
+        );
+    }
+
+    #[test]
+    fn lights_a_ball() {
+        // Light from above and darkness below.
+        let sky = Environment::new(2, 2, vec![1.0, 1.0, 0.0, 0.0]);
+        let ball: crate::core::Sdf = Box::new(|p| p.length() - 5.0);
+        let lights = [crate::core::Light::environment("sky.png", 1.0)];
+        let mut view = crate::march::View::new(
+            &ball,
+            Vec3::new(0.0, 0.0, -20.0),
+            Vec3::ZERO,
+            &lights,
+            0.5,
+            40,
+            40,
+        );
+        assert_eq!(view.pixel(20.0, 20.0, 1), 0.0);
+        view.environment = Some(sky);
+        let (top, bottom) = (view.pixel(20.0, 17.0, 1), view.pixel(20.0, 23.0, 1));
+        assert!(top > bottom + 0.3, "{} {}", top, bottom);
+        assert_eq!(view.pixel(0.0, 0.0, 1), 0.5);
+    }
+}
@@ -1,5 +1,10 @@
 use crate::ast::*;
-use crate::core::{fbm_value, hash, modulo, v3, I, ZERO3};
+use crate::blend;
+use crate::bound::MARGIN;
+use crate::core::{modulo, v3, I, ZERO3};
+use crate::functions;
+use crate::noise::{self, fbm_value};
+use crate::optimize::optimize;
 use crate::sdf::{sd_box, sd_torus};
 use core::panic;
 use glam::{Mat2, Vec2, Vec3};
@@ -7,10 +12,16 @@ use std::collections::HashMap;
 use std::f32::consts::TAU;
 
 pub fn make_sdf(ast: &Statement, a0: f32, a1: f32, p: Vec3) -> f32 {
-    let mut env = HashMap::new();
-    env.insert("a0".to_string(), Value::ScalarVal(a0));
-    env.insert("a1".to_string(), Value::ScalarVal(a1));
-    eval(&mut env, ast, p);
+    make_sdf_with(ast, [("a0", a0), ("a1", a1)], p)
+}
+
+// Like `make_sdf` with any number of named parameters, e.g. the `params` of a
+// scene file.
+pub fn make_sdf_with<'a, P>(ast: &Statement, params: P, p: Vec3) -> f32
+where
+    P: IntoIterator<Item = (&'a str, f32)>,
+{
+    let env = run(ast, params, p);
     let v = env.get("#").unwrap();
     match v {
         Value::ScalarVal(s) => *s,
@@ -18,6 +29,45 @@ pub fn make_sdf(ast: &Statement, a0: f32, a1: f32, p: Vec3) -> f32 {
     }
 }
 
+// The program as a distance field with the parameters `params`, optimized
+// once here rather than run as written at every point.
+pub fn interpret(ast: &Statement, params: &[(&str, f32)]) -> impl Fn(Vec3) -> f32 + Send + Sync {
+    let ast = optimize(ast);
+    let params: Vec<(String, f32)> = params.iter().map(|&(k, v)| (k.to_string(), v)).collect();
+    move |p| make_sdf_with(&ast, params.iter().map(|(k, v)| (k.as_str(), *v)), p)
+}
+
+// The scalar variables `names`, with booleans as 0 or 1, after running the
+// program at `p`, so a program can say more about a point than how far it is
+// from the surface, e.g. the `col` of the surface there.
+pub fn outputs_with<'a, P>(ast: &Statement, params: P, p: Vec3, names: &[&str]) -> Vec<Option<f32>>
+where
+    P: IntoIterator<Item = (&'a str, f32)>,
+{
+    let env = run(ast, params, p);
+    names
+        .iter()
+        .map(|name| match env.get(*name) {
+            Some(Value::ScalarVal(s)) => Some(*s),
+            Some(Value::BoolVal(b)) => Some(*b as u8 as f32),
+            Some(_) => panic!("{} is not a scalar", name),
+            None => None,
+        })
+        .collect()
+}
+
+fn run<'a, P>(ast: &Statement, params: P, p: Vec3) -> Environment
+where
+    P: IntoIterator<Item = (&'a str, f32)>,
+{
+    let mut env = HashMap::new();
+    for (name, value) in params {
+        env.insert(name.to_string(), Value::ScalarVal(value));
+    }
+    eval(&mut env, ast, p);
+    env
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum Value {
     ScalarVal(f32),
@@ -48,8 +98,38 @@ impl PartialOrd for Value {
     }
 }
 
+impl From<f32> for Value {
+    fn from(v: f32) -> Self {
+        Value::ScalarVal(v)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Value::BoolVal(v)
+    }
+}
+
+impl From<Vec2> for Value {
+    fn from(v: Vec2) -> Self {
+        Value::Vec2Val(v)
+    }
+}
+
+impl From<Vec3> for Value {
+    fn from(v: Vec3) -> Self {
+        Value::Vec3Val(v)
+    }
+}
+
 pub type Environment = HashMap<String, Value>;
 
+// Evaluate an expression that references no variables, e.g. for constant folding.
+pub fn eval_constant(expr: &Expr) -> Value {
+    let mut env = Environment::new();
+    eval_expr(&mut env, Box::new(expr.clone()))
+}
+
 pub fn eval(env: &mut Environment, ast: &Statement, v: Vec3) {
     use Value::*;
     if !env.contains_key("x") {
@@ -89,7 +169,7 @@ pub fn eval(env: &mut Environment, ast: &Statement, v: Vec3) {
                 .map(|r| eval_expr(env, Box::new(r.clone())))
                 .collect();
             for var in vars.iter().zip(values.iter()) {
-                env.insert(var.0.clone(), var.1.clone());
+                env.insert(var.0.clone(), *var.1);
             }
         }
         Statement::Sequence(stmts) => {
@@ -104,15 +184,18 @@ pub fn eval(env: &mut Environment, ast: &Statement, v: Vec3) {
     }
 }
 
+#[allow(clippy::boxed_local)]
 fn eval_expr(env: &mut Environment, ast: Box<Expr>) -> Value {
     use Value::*;
     match *ast {
         Expr::Negate(expr) => {
             let r = eval_expr(env, expr);
-            match r {
+            let r = match r {
                 ScalarVal(r) => ScalarVal(-r),
                 _ => panic!("negate expects scalar values"),
-            }
+            };
+            env.insert("#".to_string(), r);
+            r
         }
         Expr::Number(value) => {
             let r = ScalarVal(value);
@@ -132,7 +215,7 @@ fn eval_expr(env: &mut Environment, ast: Box<Expr>) -> Value {
         Expr::Variable(name) => {
             let value = *env
                 .get(&name)
-                .expect(format!("variable not found: {}", name).as_str());
+                .unwrap_or_else(|| panic!("variable not found: {}", name));
             env.insert("#".to_string(), value);
             value
         }
@@ -487,22 +570,24 @@ fn eval_function(env: &mut Environment, name: FunctionName, args: Vec<Expr>) ->
                 _ => panic!("mod expects scalar values"),
             }
         }
-        Min => {
-            let x = eval_expr(env, Box::new(args[0].clone()));
-            let y = eval_expr(env, Box::new(args[1].clone()));
-            match (x, y) {
-                (ScalarVal(x), ScalarVal(y)) => ScalarVal(x.min(y)),
+        Min => args
+            .into_iter()
+            .map(|arg| match eval_expr(env, Box::new(arg)) {
+                ScalarVal(v) => v,
                 _ => panic!("min expects scalar values"),
-            }
-        }
-        Max => {
-            let x = eval_expr(env, Box::new(args[0].clone()));
-            let y = eval_expr(env, Box::new(args[1].clone()));
-            match (x, y) {
-                (ScalarVal(x), ScalarVal(y)) => ScalarVal(x.max(y)),
+            })
+            .reduce(f32::min)
+            .map(ScalarVal)
+            .unwrap_or_else(|| panic!("min expects at least one value")),
+        Max => args
+            .into_iter()
+            .map(|arg| match eval_expr(env, Box::new(arg)) {
+                ScalarVal(v) => v,
                 _ => panic!("max expects scalar values"),
-            }
-        }
+            })
+            .reduce(f32::max)
+            .map(ScalarVal)
+            .unwrap_or_else(|| panic!("max expects at least one value")),
         Clamp => {
             let x = eval_expr(env, Box::new(args[0].clone()));
             let a = eval_expr(env, Box::new(args[1].clone()));
@@ -670,7 +755,7 @@ fn eval_function(env: &mut Environment, name: FunctionName, args: Vec<Expr>) ->
                 })
                 .collect();
             let r = ds.pop().unwrap();
-            let d = ds.into_iter().reduce(|a, b| smooth_min(a, b, r));
+            let d = ds.into_iter().reduce(|a, b| blend::round(a, b, r));
             ScalarVal(d.unwrap_or(r))
         }
         RoundMax => {
@@ -685,7 +770,7 @@ fn eval_function(env: &mut Environment, name: FunctionName, args: Vec<Expr>) ->
                 })
                 .collect();
             let r = ds.pop().unwrap();
-            let d = ds.into_iter().reduce(|a, b| smooth_max(a, b, r));
+            let d = ds.into_iter().reduce(|a, b| blend::round_max(a, b, r));
             ScalarVal(d.unwrap_or(r))
         }
         Intersect => {
@@ -840,6 +925,270 @@ fn eval_function(env: &mut Environment, name: FunctionName, args: Vec<Expr>) ->
                 _ => panic!("box3 expects scalar values"),
             }
         }
+        Cone => {
+            let a = scalars(env, &args, "cone");
+            ScalarVal(functions::cone(a[0], a[1], a[2], a[3], a[4]))
+        }
+        CappedCone => {
+            let a = scalars(env, &args, "capped cone");
+            ScalarVal(functions::capped_cone(a[0], a[1], a[2], a[3], a[4], a[5]))
+        }
+        RoundCone => {
+            let a = scalars(env, &args, "round cone");
+            ScalarVal(functions::round_cone(a[0], a[1], a[2], a[3], a[4], a[5]))
+        }
+        Ellipsoid => {
+            let a = scalars(env, &args, "ellipsoid");
+            ScalarVal(functions::ellipsoid(a[0], a[1], a[2], a[3], a[4], a[5]))
+        }
+        HexPrism => {
+            let a = scalars(env, &args, "hex prism");
+            ScalarVal(functions::hex_prism(a[0], a[1], a[2], a[3], a[4]))
+        }
+        TriPrism => {
+            let a = scalars(env, &args, "tri prism");
+            ScalarVal(functions::tri_prism(a[0], a[1], a[2], a[3], a[4]))
+        }
+        Octahedron => {
+            let a = scalars(env, &args, "octahedron");
+            ScalarVal(functions::octahedron(a[0], a[1], a[2], a[3]))
+        }
+        Pyramid => {
+            let a = scalars(env, &args, "pyramid");
+            ScalarVal(functions::pyramid(a[0], a[1], a[2], a[3]))
+        }
+        Link => {
+            let a = scalars(env, &args, "link");
+            ScalarVal(functions::link(a[0], a[1], a[2], a[3], a[4], a[5]))
+        }
+        SolidAngle => {
+            let a = scalars(env, &args, "solid angle");
+            ScalarVal(functions::solid_angle(a[0], a[1], a[2], a[3], a[4]))
+        }
+        CappedTorus => {
+            let a = scalars(env, &args, "capped torus");
+            ScalarVal(functions::capped_torus(a[0], a[1], a[2], a[3], a[4], a[5]))
+        }
+        UdTriangle => {
+            let a = scalars(env, &args, "triangle");
+            ScalarVal(functions::ud_triangle(
+                a[0], a[1], a[2], a[3], a[4], a[5], a[6], a[7], a[8], a[9], a[10], a[11],
+            ))
+        }
+        UdQuad => {
+            let a = scalars(env, &args, "quad");
+            ScalarVal(functions::ud_quad(
+                a[0], a[1], a[2], a[3], a[4], a[5], a[6], a[7], a[8], a[9], a[10], a[11], a[12],
+                a[13], a[14],
+            ))
+        }
+        Hexagon => {
+            let a = scalars(env, &args, "hexagon");
+            ScalarVal(functions::hexagon(a[0], a[1], a[2]))
+        }
+        Segment => {
+            let a = scalars(env, &args, "segment");
+            ScalarVal(functions::segment(a[0], a[1], a[2], a[3], a[4], a[5]))
+        }
+        Triangle2 => {
+            let a = scalars(env, &args, "triangle");
+            ScalarVal(functions::triangle2(
+                a[0], a[1], a[2], a[3], a[4], a[5], a[6], a[7],
+            ))
+        }
+        Extrude => {
+            let a = scalars(env, &args, "extrude");
+            ScalarVal(functions::extrude(a[0], a[1], a[2]))
+        }
+        Twist => {
+            let a = scalars(env, &args, "twist");
+            Vec3Val(Vec3::from(functions::twist(a[0], a[1], a[2], a[3])))
+        }
+        Bend => {
+            let a = scalars(env, &args, "bend");
+            Vec3Val(Vec3::from(functions::bend(a[0], a[1], a[2], a[3])))
+        }
+        Elongate => {
+            let a = scalars(env, &args, "elongate");
+            Vec3Val(Vec3::from(functions::elongate(
+                a[0], a[1], a[2], a[3], a[4], a[5],
+            )))
+        }
+        Onion => {
+            let a = scalars(env, &args, "onion");
+            ScalarVal(functions::onion(a[0], a[1]))
+        }
+        Revolve => {
+            let a = scalars(env, &args, "revolve");
+            Vec2Val(Vec2::from(functions::revolve(a[0], a[1], a[2], a[3])))
+        }
+        Repeat => {
+            let a = scalars(env, &args, "repeat");
+            ScalarVal(functions::repeat(a[0], a[1], a[2]))
+        }
+        Polar => {
+            let a = scalars(env, &args, "polar");
+            Vec2Val(Vec2::from(functions::polar(a[0], a[1], a[2])))
+        }
+        PolyMin => ScalarVal(functions::poly_union(scalars(env, &args, "poly union"))),
+        PolyMax => ScalarVal(functions::poly_intersect(scalars(
+            env,
+            &args,
+            "poly intersect",
+        ))),
+        CubicMin => ScalarVal(functions::cubic_union(scalars(env, &args, "cubic union"))),
+        CubicMax => ScalarVal(functions::cubic_intersect(scalars(
+            env,
+            &args,
+            "cubic intersect",
+        ))),
+        ExpMin => ScalarVal(functions::exp_union(scalars(env, &args, "exp union"))),
+        ExpMax => ScalarVal(functions::exp_intersect(scalars(
+            env,
+            &args,
+            "exp intersect",
+        ))),
+        RootMin => ScalarVal(functions::root_union(scalars(env, &args, "root union"))),
+        RootMax => ScalarVal(functions::root_intersect(scalars(
+            env,
+            &args,
+            "root intersect",
+        ))),
+        CircularMin => ScalarVal(functions::circular_union(scalars(
+            env,
+            &args,
+            "circular union",
+        ))),
+        CircularMax => ScalarVal(functions::circular_intersect(scalars(
+            env,
+            &args,
+            "circular intersect",
+        ))),
+        ChamferMin => ScalarVal(functions::chamfer_union(scalars(
+            env,
+            &args,
+            "chamfer union",
+        ))),
+        ChamferMax => ScalarVal(functions::chamfer_intersect(scalars(
+            env,
+            &args,
+            "chamfer intersect",
+        ))),
+        StairsMin => ScalarVal(functions::stairs_union(scalars(env, &args, "stairs union"))),
+        StairsMax => ScalarVal(functions::stairs_intersect(scalars(
+            env,
+            &args,
+            "stairs intersect",
+        ))),
+        ColumnsMin => ScalarVal(functions::columns_union(scalars(
+            env,
+            &args,
+            "columns union",
+        ))),
+        ColumnsMax => ScalarVal(functions::columns_intersect(scalars(
+            env,
+            &args,
+            "columns intersect",
+        ))),
+        Groove => ScalarVal(functions::groove(scalars(env, &args, "groove"))),
+        Tongue => ScalarVal(functions::tongue(scalars(env, &args, "tongue"))),
+        Pipe => ScalarVal(functions::pipe(scalars(env, &args, "pipe"))),
+        Translate => {
+            let a = scalars(env, &args, "translate");
+            Vec3Val(Vec3::from(functions::translate(
+                a[0], a[1], a[2], a[3], a[4], a[5],
+            )))
+        }
+        Rotate => {
+            let a = scalars(env, &args, "rotate");
+            Vec3Val(Vec3::from(functions::rotate(
+                a[0], a[1], a[2], a[3], a[4], a[5], a[6],
+            )))
+        }
+        Scale => {
+            let a = scalars(env, &args, "scale");
+            Vec3Val(Vec3::from(functions::scale(a[0], a[1], a[2], a[3])))
+        }
+        Mandelbulb => {
+            let a = scalars(env, &args, "mandelbulb");
+            ScalarVal(functions::mandelbulb(a[0], a[1], a[2], a[3], a[4]))
+        }
+        MandelbulbTrap => {
+            let a = scalars(env, &args, "mandelbulb trap");
+            ScalarVal(functions::mandelbulb_trap(a[0], a[1], a[2], a[3], a[4]))
+        }
+        Mandelbox => {
+            let a = scalars(env, &args, "mandelbox");
+            ScalarVal(functions::mandelbox(a[0], a[1], a[2], a[3], a[4]))
+        }
+        MandelboxTrap => {
+            let a = scalars(env, &args, "mandelbox trap");
+            ScalarVal(functions::mandelbox_trap(a[0], a[1], a[2], a[3], a[4]))
+        }
+        Menger => {
+            let a = scalars(env, &args, "menger");
+            ScalarVal(functions::menger(a[0], a[1], a[2], a[3]))
+        }
+        MengerTrap => {
+            let a = scalars(env, &args, "menger trap");
+            ScalarVal(functions::menger_trap(a[0], a[1], a[2], a[3]))
+        }
+        Sierpinski => {
+            let a = scalars(env, &args, "sierpinski");
+            ScalarVal(functions::sierpinski(a[0], a[1], a[2], a[3]))
+        }
+        SierpinskiTrap => {
+            let a = scalars(env, &args, "sierpinski trap");
+            ScalarVal(functions::sierpinski_trap(a[0], a[1], a[2], a[3]))
+        }
+        Kifs => ScalarVal(functions::kifs(scalars(env, &args, "kifs"))),
+        KifsTrap => ScalarVal(functions::kifs_trap(scalars(env, &args, "kifs trap"))),
+        SeededValue => {
+            let a = scalars(env, &args, "seeded value");
+            ScalarVal(functions::seeded_value(a[0], a[1], a[2], a[3], a[4], a[5]))
+        }
+        Perlin => {
+            let a = scalars(env, &args, "perlin");
+            ScalarVal(functions::perlin(a[0], a[1], a[2], a[3], a[4], a[5]))
+        }
+        Simplex => {
+            let a = scalars(env, &args, "simplex");
+            ScalarVal(functions::simplex(a[0], a[1], a[2], a[3], a[4], a[5]))
+        }
+        Worley => {
+            let a = scalars(env, &args, "worley");
+            ScalarVal(functions::worley(a[0], a[1], a[2], a[3], a[4], a[5]))
+        }
+        Cellular => {
+            let a = scalars(env, &args, "cellular");
+            Vec2Val(Vec2::from(functions::cellular(
+                a[0], a[1], a[2], a[3], a[4],
+            )))
+        }
+        Ridged => {
+            let a = scalars(env, &args, "ridged");
+            ScalarVal(functions::ridged(a[0], a[1], a[2], a[3], a[4], a[5]))
+        }
+        Billow => {
+            let a = scalars(env, &args, "billow");
+            ScalarVal(functions::billow(a[0], a[1], a[2], a[3], a[4], a[5]))
+        }
+        Warp => {
+            let a = scalars(env, &args, "warp");
+            ScalarVal(functions::warp(a[0], a[1], a[2], a[3], a[4], a[5], a[6]))
+        }
+        PerlinGradient => {
+            let a = scalars(env, &args, "perlin gradient");
+            Vec3Val(Vec3::from(functions::perlin_gradient(
+                a[0], a[1], a[2], a[3], a[4], a[5],
+            )))
+        }
+        SimplexGradient => {
+            let a = scalars(env, &args, "simplex gradient");
+            Vec3Val(Vec3::from(functions::simplex_gradient(
+                a[0], a[1], a[2], a[3], a[4], a[5],
+            )))
+        }
         Rot0 => {
             let x = eval_expr(env, Box::new(args[0].clone()));
             let y = eval_expr(env, Box::new(args[1].clone()));
@@ -949,28 +1298,17 @@ fn eval_function(env: &mut Environment, name: FunctionName, args: Vec<Expr>) ->
                 _ => panic!("smoothclamp expects scalar values"),
             }
         }
+        // Lazy, so `e` is only evaluated near the bound.
+        Bound => match eval_expr(env, Box::new(args[0].clone())) {
+            ScalarVal(b) if b > MARGIN => ScalarVal(b),
+            ScalarVal(_) => eval_expr(env, Box::new(args[1].clone())),
+            _ => panic!("bound expects scalar values"),
+        },
+        Closest => Vec2Val(Vec2::from(functions::closest(scalars(env, &args, "iU")))),
         ValueNoise => {
-            let x = eval_expr(env, Box::new(args[0].clone()));
-            let y = eval_expr(env, Box::new(args[1].clone()));
-            let z = eval_expr(env, Box::new(args[2].clone()));
-            let scale = eval_expr(env, Box::new(args[3].clone()));
-            let offset = eval_expr(env, Box::new(args[4].clone()));
-            let octaves = if args.len() > 5 {
-                eval_expr(env, Box::new(args[5].clone()))
-            } else {
-                ScalarVal(1.0)
-            };
-            match (x, y, z, scale, offset, octaves) {
-                (
-                    ScalarVal(x),
-                    ScalarVal(y),
-                    ScalarVal(z),
-                    ScalarVal(scale),
-                    ScalarVal(offset),
-                    ScalarVal(octaves),
-                ) => ScalarVal(fbm_value(x, y, z, scale, offset, octaves as u32)),
-                _ => panic!("noise expects scalar values"),
-            }
+            let a = scalars(env, &args, "noise");
+            let octaves = a.get(5).map_or(1, |&o| o as u32);
+            ScalarVal(fbm_value(a[0], a[1], a[2], a[3], a[4], octaves))
         }
         Hash => {
             let x = eval_expr(env, Box::new(args[0].clone()));
@@ -985,31 +1323,25 @@ fn eval_function(env: &mut Environment, name: FunctionName, args: Vec<Expr>) ->
                 ScalarVal(0.0)
             };
             match (x, y, z) {
-                (ScalarVal(x), ScalarVal(y), ScalarVal(z)) => ScalarVal(hash(v3(x, y, z))),
+                (ScalarVal(x), ScalarVal(y), ScalarVal(z)) => ScalarVal(noise::hash(v3(x, y, z))),
                 _ => panic!("hash expects scalar values"),
             }
         }
     }
 }
 
-fn smooth_abs(x: f32, p: f32) -> f32 {
-    (x * x + p).sqrt()
+// The values of `args`, which must all be scalars.
+fn scalars(env: &mut Environment, args: &[Expr], name: &str) -> Vec<f32> {
+    args.iter()
+        .map(|arg| match eval_expr(env, Box::new(arg.clone())) {
+            Value::ScalarVal(x) => x,
+            _ => panic!("{} expects scalar values", name),
+        })
+        .collect()
 }
 
-fn smooth_min(a: f32, b: f32, r: f32) -> f32 {
-    if a < r && b < r {
-        r - Vec2::new(r - a, r - b).length()
-    } else {
-        a.min(b)
-    }
-}
-
-fn smooth_max(a: f32, b: f32, r: f32) -> f32 {
-    if -a < r && -b < r {
-        r - Vec2::new(r + a, r + b).length()
-    } else {
-        a.max(b)
-    }
+fn smooth_abs(x: f32, p: f32) -> f32 {
+    (x * x + p).sqrt()
 }
 
 fn poly_smooth_abs(x: f32, m: f32) -> f32 {
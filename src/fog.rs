@@ -0,0 +1,200 @@
+// Fog between the camera and what it sees, and between bounces. Its
+// `density` thins out exponentially with height above `height` at the rate
+// `falloff`, none meaning the same everywhere, and it glows with
+// `luminance`. Smooth fog is worked out exactly. With `noise` the density
+// varies by up to that fraction, like `core::Noise` at `scale` and `seed`,
+// and with `god_rays` the fog also scatters light from the lights it can
+// see. Both of those are ray marched in `steps` steps.
+use crate::core::{Light, LightKind, Lum, Noise, Sdf};
+use crate::march::intersect;
+use crate::sampling::random;
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+// How far volumetric fog is marched when a ray hits nothing.
+const FAR: f32 = 75.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Fog {
+    pub density: f32,
+    pub falloff: f32,
+    pub height: f32,
+    pub luminance: f32,
+    pub noise: f32,
+    pub scale: f32,
+    pub seed: u32,
+    pub god_rays: f32,
+    pub steps: u32,
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Fog {
+            density: 0.02,
+            falloff: 0.0,
+            height: 0.0,
+            luminance: 0.75,
+            noise: 0.0,
+            scale: 0.1,
+            seed: 0,
+            god_rays: 0.0,
+            steps: 48,
+        }
+    }
+}
+
+// A `Fog` ready to render, with its noise.
+pub struct Atmosphere {
+    pub fog: Fog,
+    noise: Noise,
+}
+
+impl Atmosphere {
+    pub fn new(fog: Fog) -> Self {
+        Atmosphere {
+            fog,
+            noise: Noise::new(4, fog.scale, 1.0, fog.seed),
+        }
+    }
+
+    fn volumetric(&self) -> bool {
+        self.fog.noise > 0.0 || self.fog.god_rays > 0.0
+    }
+
+    fn density(&self, p: Vec3) -> f32 {
+        let fog = &self.fog;
+        let mut density = fog.density * (-fog.falloff * (p.y - fog.height)).exp();
+        if fog.noise > 0.0 {
+            density *= (1.0 + fog.noise * 2.0 * self.noise.get(p)).max(0.0);
+        }
+        density
+    }
+
+    // The density integrated along the ray from `ro` for `t`, which can be
+    // infinite.
+    fn optical_depth(&self, ro: Vec3, rd: Vec3, t: f32) -> f32 {
+        let fog = &self.fog;
+        let base = fog.density * (-fog.falloff * (ro.y - fog.height)).exp();
+        let k = fog.falloff * rd.y;
+        if k.abs() < 1e-5 {
+            base * t
+        } else {
+            base * (1.0 - (-k * t).exp()) / k
+        }
+    }
+
+    // How much light scattered towards the camera at `p` comes from the
+    // lights, those it can see.
+    fn in_scattering(&self, sdf: &Sdf, p: Vec3, lights: &[Light]) -> Lum {
+        let mut lum = 0.0;
+        for light in lights {
+            let (l, d) = match light.kind {
+                LightKind::Environment => continue,
+                LightKind::Directional => (light.position.normalize(), f32::INFINITY),
+                _ => {
+                    let to_light = light.position - p;
+                    (to_light.normalize(), to_light.length())
+                }
+            };
+            if intersect(sdf, p, l).is_some_and(|t| t < d) {
+                continue;
+            }
+            lum += light.intensity * light.cone(p) * light.attenuate(p);
+        }
+        lum
+    }
+
+    fn smooth(&self, ro: Vec3, rd: Vec3, t: f32, col: Lum) -> Lum {
+        let transmittance = (-self.optical_depth(ro, rd, t)).exp();
+        col * transmittance + self.fog.luminance * (1.0 - transmittance)
+    }
+
+    // What is seen along the ray from `ro` through fog when `col` is `t`
+    // away, infinitely far for rays that hit nothing.
+    pub fn apply(&self, sdf: &Sdf, lights: &[Light], ro: Vec3, rd: Vec3, t: f32, col: Lum) -> Lum {
+        if self.fog.density <= 0.0 {
+            return col;
+        }
+        if !self.volumetric() {
+            return self.smooth(ro, rd, t, col);
+        }
+        let far = t.min(FAR);
+        let steps = self.fog.steps.max(1);
+        let ds = far / steps as f32;
+        // Starting each ray a little further in hides the steps.
+        let jitter = random(rd.x, rd.y, steps);
+        let (mut lum, mut transmittance) = (0.0, 1.0);
+        for i in 0..steps {
+            let p = ro + rd * (i as f32 + jitter) * ds;
+            let density = self.density(p);
+            if density <= 0.0 {
+                continue;
+            }
+            let mut scattered = self.fog.luminance;
+            if self.fog.god_rays > 0.0 {
+                scattered += self.fog.god_rays * self.in_scattering(sdf, p, lights);
+            }
+            let absorbed = 1.0 - (-density * ds).exp();
+            lum += transmittance * absorbed * scattered;
+            transmittance *= 1.0 - absorbed;
+        }
+        // Past FAR the fog is smooth.
+        let behind = if t > far {
+            self.smooth(ro + rd * far, rd, t - far, col)
+        } else {
+            col
+        };
+        lum + transmittance * behind
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::v3;
+
+    #[test]
+    fn smooth() {
+        let sdf: Sdf = Box::new(|p| p.y + 1000.0);
+        let atmosphere = Atmosphere::new(Fog::default());
+        let apply = |t| atmosphere.apply(&sdf, &[], Vec3::ZERO, Vec3::Z, t, 0.0);
+        assert_eq!(apply(0.0), 0.0);
+        let expected = 0.75 * (1.0 - (-0.02f32 * 10.0).exp());
+        assert!((apply(10.0) - expected).abs() < 1e-6);
+        assert_eq!(apply(f32::INFINITY), 0.75);
+        // Height fog gets thinner going up.
+        let height = Atmosphere::new(Fog {
+            falloff: 0.5,
+            ..Default::default()
+        });
+        let up = v3(0.0, 1.0, 1.0).normalize();
+        assert!(height.apply(&sdf, &[], Vec3::ZERO, up, f32::INFINITY, 0.0) < 0.75);
+        // Marching the same smooth fog agrees with the exact answer.
+        let marched = Atmosphere::new(Fog {
+            falloff: 0.5,
+            noise: 1e-9,
+            steps: 400,
+            ..Default::default()
+        });
+        let exact = height.apply(&sdf, &[], Vec3::ZERO, up, 20.0, 0.5);
+        let approx = marched.apply(&sdf, &[], Vec3::ZERO, up, 20.0, 0.5);
+        assert!((exact - approx).abs() < 0.01, "{} {}", exact, approx);
+    }
+
+    #[test]
+    fn god_rays() {
+        // A wall below y = 0 with a light behind it, rays over it see more
+        // light than ones in its shadow.
+        let sdf: Sdf = Box::new(|p| p.z.abs().max(p.y) - 0.1);
+        let lights = [Light::new(v3(0.0, 0.0, 20.0), 1.0)];
+        let atmosphere = Atmosphere::new(Fog {
+            luminance: 0.0,
+            god_rays: 1.0,
+            ..Default::default()
+        });
+        let lit = atmosphere.apply(&sdf, &lights, v3(-5.0, 5.0, -10.0), Vec3::X, 10.0, 0.0);
+        let shadowed = atmosphere.apply(&sdf, &lights, v3(-5.0, -5.0, -10.0), Vec3::X, 10.0, 0.0);
+        assert!(lit > 0.1 && shadowed < lit / 2.0, "{} {}", lit, shadowed);
+    }
+}
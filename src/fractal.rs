@@ -0,0 +1,288 @@
+// Fractals as distance estimators, which are bounds on the distance rather
+// than the distance itself, good enough to march with. Each also returns an
+// orbit trap for colouring, the smallest |z| the point's orbit reaches, which
+// can be used for `col`, e.g. `col=mbulbt(x,y,z,8,8)`.
+use crate::core::{Sdf, Transform};
+use crate::sdf::placed;
+use glam::{Vec3, Vec3Swizzles};
+
+// The Mandelbulb of `power` in the ball of radius about 1.2 around the
+// origin.
+pub fn mandelbulb(p: Vec3, power: f32, iterations: u32) -> (f32, f32) {
+    let mut z = p;
+    let mut dr = 1.0;
+    let mut trap = f32::MAX;
+    for _ in 0..iterations {
+        let r = z.length();
+        trap = trap.min(r);
+        if r > 2.0 {
+            break;
+        }
+        let theta = if r > 0.0 { (z.z / r).acos() } else { 0.0 } * power;
+        let phi = z.y.atan2(z.x) * power;
+        dr = r.powf(power - 1.0) * power * dr + 1.0;
+        let (st, ct) = theta.sin_cos();
+        let (sp, cp) = phi.sin_cos();
+        z = r.powf(power) * Vec3::new(st * cp, st * sp, ct) + p;
+    }
+    let r = z.length().max(1e-6);
+    (0.5 * r.ln() * r / dr, trap)
+}
+
+// The Mandelbox of `scale`, classically -1.5 or 2. Above 1 it is about
+// 4 * (scale + 1) / (scale - 1) across.
+pub fn mandelbox(p: Vec3, scale: f32, iterations: u32) -> (f32, f32) {
+    let mut z = p;
+    let mut dr = 1.0;
+    let mut trap = f32::MAX;
+    for _ in 0..iterations {
+        z = z.clamp(Vec3::splat(-1.0), Vec3::ONE) * 2.0 - z;
+        let r2 = z.length_squared();
+        if r2 < 0.25 {
+            z *= 4.0;
+            dr *= 4.0;
+        } else if r2 < 1.0 {
+            z /= r2;
+            dr /= r2;
+        }
+        z = scale * z + p;
+        dr = dr * scale.abs() + 1.0;
+        trap = trap.min(z.length());
+    }
+    (z.length() / dr.abs(), trap)
+}
+
+// The Menger sponge in the cube from -1 to 1, exact at every level.
+pub fn menger(p: Vec3, iterations: u32) -> (f32, f32) {
+    let q = p.abs() - Vec3::ONE;
+    let mut d = q.max_element().min(0.0) + q.max(Vec3::ZERO).length();
+    let mut s = 1.0;
+    let mut trap = f32::MAX;
+    for _ in 0..iterations {
+        let a = (p * s).rem_euclid(Vec3::splat(2.0)) - Vec3::ONE;
+        s *= 3.0;
+        let r = (Vec3::ONE - 3.0 * a.abs()).abs();
+        let da = r.x.max(r.y);
+        let db = r.y.max(r.z);
+        let dc = r.z.max(r.x);
+        d = d.max((da.min(db).min(dc) - 1.0) / s);
+        trap = trap.min(a.length());
+    }
+    (d, trap)
+}
+
+// The tetrahedron with its vertices at (1, 1, 1), (-1, -1, 1), (1, -1, -1)
+// and (-1, 1, -1).
+fn tetrahedron(p: Vec3) -> f32 {
+    ((p.x + p.y).abs() - p.z).max((p.x - p.y).abs() + p.z) - 1.0
+}
+
+// Sierpinski's tetrahedron in the tetrahedron above, a `tetrahedron` after
+// the last fold.
+pub fn sierpinski(p: Vec3, iterations: u32) -> (f32, f32) {
+    let mut z = p;
+    let mut trap = f32::MAX;
+    for _ in 0..iterations {
+        if z.x + z.y < 0.0 {
+            let xy = -z.yx();
+            (z.x, z.y) = (xy.x, xy.y);
+        }
+        if z.x + z.z < 0.0 {
+            let xz = -z.zx();
+            (z.x, z.z) = (xz.x, xz.y);
+        }
+        if z.y + z.z < 0.0 {
+            let yz = -z.zy();
+            (z.y, z.z) = (yz.x, yz.y);
+        }
+        z = z * 2.0 - Vec3::ONE;
+        trap = trap.min(z.length());
+    }
+    (
+        tetrahedron(z) / 3f32.sqrt() * 2f32.powi(-(iterations as i32)),
+        trap,
+    )
+}
+
+// A kaleidoscopic IFS: at each step the point is reflected to the positive
+// side of each of the `planes` through the origin, given by their normals,
+// then scaled by `scale` away from `offset`. The copies of the unit ball
+// this makes are the fractal.
+pub fn kifs(p: Vec3, planes: &[Vec3], scale: f32, offset: Vec3, iterations: u32) -> (f32, f32) {
+    let mut z = p;
+    let mut trap = f32::MAX;
+    for _ in 0..iterations {
+        for n in planes {
+            let n = n.normalize();
+            z -= 2.0 * z.dot(n).min(0.0) * n;
+        }
+        z = z * scale - offset * (scale - 1.0);
+        trap = trap.min(z.length());
+    }
+    ((z.length() - 1.0) * scale.powi(-(iterations as i32)), trap)
+}
+
+pub fn sd_mandelbulb(
+    power: f32,
+    iterations: u32,
+    center: Vec3,
+    transform: impl Into<Transform>,
+) -> Sdf {
+    placed(center, transform, move |p| {
+        mandelbulb(p, power, iterations).0
+    })
+}
+
+pub fn sd_mandelbox(
+    scale: f32,
+    iterations: u32,
+    center: Vec3,
+    transform: impl Into<Transform>,
+) -> Sdf {
+    placed(center, transform, move |p| {
+        mandelbox(p, scale, iterations).0
+    })
+}
+
+pub fn sd_menger(iterations: u32, center: Vec3, transform: impl Into<Transform>) -> Sdf {
+    placed(center, transform, move |p| menger(p, iterations).0)
+}
+
+pub fn sd_sierpinski(iterations: u32, center: Vec3, transform: impl Into<Transform>) -> Sdf {
+    placed(center, transform, move |p| sierpinski(p, iterations).0)
+}
+
+pub fn sd_kifs(
+    planes: Vec<Vec3>,
+    scale: f32,
+    offset: Vec3,
+    iterations: u32,
+    center: Vec3,
+    transform: impl Into<Transform>,
+) -> Sdf {
+    placed(center, transform, move |p| {
+        kifs(p, &planes, scale, offset, iterations).0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::v3;
+    use glam::Affine3A;
+
+    const ZERO: Vec3 = Vec3::ZERO;
+
+    #[test]
+    fn fractals() {
+        // The sponge starts as the cube and loses its middle.
+        assert_eq!(menger(v3(3.0, 0.0, 0.0), 0).0, 2.0);
+        assert!(menger(v3(0.0, 0.0, 0.9), 0).0 < 0.0);
+        assert!(menger(v3(0.0, 0.0, 0.9), 1).0 > 0.0);
+        assert!(menger(v3(0.99, 0.99, 0.99), 4).0 < 0.0);
+        // So does Sierpinski's tetrahedron, keeping its corners.
+        let tip = v3(0.999, 0.999, 0.999);
+        assert!(sierpinski(ZERO, 0).0 < 0.0);
+        assert!(sierpinski(ZERO, 1).0 > 0.0);
+        assert!(sierpinski(tip, 6).0 < 0.0);
+        // Folding by the same planes a KIFS is the same fractal made of balls,
+        // the one nearest the corner centered where six steps take it to the
+        // origin.
+        let planes = [v3(1.0, 1.0, 0.0), v3(1.0, 0.0, 1.0), v3(0.0, 1.0, 1.0)];
+        let kifs = |p: Vec3| kifs(p, &planes, 2.0, Vec3::ONE, 6).0;
+        assert!(kifs(Vec3::splat(1.0 - 1.0 / 64.0)) < 0.0 && kifs(ZERO) > 0.0);
+        for p in [v3(4.0, 0.0, 0.0), v3(0.0, 3.0, 2.0)] {
+            let bulb = mandelbulb(p, 8.0, 8);
+            assert!(bulb.0 > 0.0 && bulb.0 < p.length() && bulb.1 <= p.length());
+            assert!(kifs(p) > 0.0 && kifs(p) < p.length());
+        }
+        assert!(mandelbulb(v3(0.1, 0.0, 0.0), 8.0, 8).0 < 0.0);
+        assert!(mandelbox(ZERO, 2.0, 10).0 < 0.1);
+        assert!(mandelbox(v3(20.0, 0.0, 0.0), 2.0, 10).0 > 1.0);
+    }
+
+    // Marching needs the estimates to be no steeper than a distance.
+    fn lipschitz(f: impl Fn(Vec3) -> f32) {
+        for i in 0..200 {
+            let t = i as f32 * 0.37;
+            let p = v3(t.sin(), (t * 1.3).cos(), (t * 0.7).sin()) * 1.5;
+            let q = p + v3(0.011, -0.007, 0.013);
+            assert!((f(p) - f(q)).abs() <= p.distance(q) * 1.001, "{}", p);
+        }
+    }
+
+    #[test]
+    fn menger_sponge() {
+        // The corners stay at every level, and the tunnels through the
+        // middle third are a third wide.
+        let corner = v3(2.0, 2.0, 2.0);
+        for n in 0..4 {
+            assert!((menger(corner, n).0 - 3f32.sqrt()).abs() < 1e-6);
+        }
+        for n in 1..4 {
+            assert!((menger(v3(0.0, 0.0, 0.9), n).0 - 1.0 / 3.0).abs() < 1e-6);
+            // The face has a hole in it, so it's a bound outside.
+            let face = menger(v3(1.5, 0.0, 0.0), n).0;
+            assert!(face > 0.0 && face <= 0.5f32.hypot(1.0 / 3.0));
+        }
+        assert!((menger(ZERO, 1).1 - 3f32.sqrt()).abs() < 1e-6);
+        lipschitz(|p| menger(p, 3).0);
+        let placed = sd_menger(3, v3(5.0, 0.0, 0.0), Affine3A::IDENTITY);
+        assert!((placed(v3(7.0, 2.0, 2.0)) - 3f32.sqrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sierpinski_tetrahedron() {
+        // Level 0 is the tetrahedron, which is 1/sqrt(3) deep at the center.
+        assert!((sierpinski(ZERO, 0).0 + 1.0 / 3f32.sqrt()).abs() < 1e-6);
+        // Beyond a vertex it's a bound on the distance to it.
+        for n in 0..4 {
+            let d = sierpinski(v3(2.0, 2.0, 2.0), n).0;
+            assert!(d > 0.0 && d <= 3f32.sqrt());
+        }
+        // The first fold sends the center to the corner (-1, -1, -1).
+        assert!((sierpinski(ZERO, 1).1 - 3f32.sqrt()).abs() < 1e-6);
+        lipschitz(|p| sierpinski(p, 5).0);
+    }
+
+    #[test]
+    fn mandelbulb_bounds() {
+        for p in [v3(0.5, 0.0, 0.0), v3(0.0, 0.5, 0.0), v3(-0.5, 0.0, 0.0)] {
+            let (d, trap) = mandelbulb(p, 8.0, 8);
+            assert!(d < 0.0 && trap <= p.length());
+        }
+        for p in [v3(1.2, 0.0, 0.0), v3(0.0, 0.0, 1.2)] {
+            assert!(mandelbulb(p, 8.0, 8).0 > 0.0);
+        }
+        // Escaping at once, the trap is where the orbit starts, and the
+        // bulb reaches (0.5, 0, 0).
+        let (d, trap) = mandelbulb(v3(3.0, 0.0, 0.0), 8.0, 8);
+        assert_eq!(trap, 3.0);
+        assert!(d > 0.0 && d < 2.5);
+    }
+
+    #[test]
+    fn mandelbox_bounds() {
+        // Scale 2 is 12 across, and the origin is fixed.
+        assert!(mandelbox(v3(6.0, 0.0, 0.0), 2.0, 10).0 < 1e-3);
+        for x in [7.0, 10.0, 20.0] {
+            let d = mandelbox(v3(x, 0.0, 0.0), 2.0, 10).0;
+            assert!(d > 0.0 && d <= x - 6.0, "{}", x);
+        }
+        assert_eq!(mandelbox(ZERO, 2.0, 10), (0.0, 0.0));
+        assert!(mandelbox(v3(3.0, 0.0, 0.0), -1.5, 10).0 > 0.0);
+    }
+
+    #[test]
+    fn kifs_balls() {
+        let planes = [v3(1.0, 1.0, 0.0), v3(1.0, 0.0, 1.0), v3(0.0, 1.0, 1.0)];
+        // No steps is the unit ball.
+        assert_eq!(
+            kifs(v3(3.0, 0.0, 0.0), &planes, 2.0, Vec3::ONE, 0),
+            (2.0, f32::MAX)
+        );
+        // The first step takes the origin to -offset.
+        assert!((kifs(ZERO, &planes, 2.0, Vec3::ONE, 1).1 - 3f32.sqrt()).abs() < 1e-6);
+        lipschitz(|p| kifs(p, &planes, 2.0, Vec3::ONE, 6).0);
+    }
+}
@@ -1,9 +1,12 @@
-use glam::{Mat2, Vec2, Vec3};
+use glam::{Mat2, Quat, Vec2, Vec3};
 use std::f32::consts::TAU;
 
 use crate::{
+    blend,
     core::{v3, I, ZERO3},
-    sdf::sd_torus,
+    fractal,
+    noise::{self, Basis, Fbm},
+    sdf::{self, sd_torus},
 };
 
 pub fn sin(x: f32) -> f32 {
@@ -189,6 +192,18 @@ pub fn union(xs: Vec<f32>) -> f32 {
         .unwrap()
 }
 
+// The DSL's `iU(id0, d0, id1, d1, ...)`, the union of the distances `d0`,
+// `d1` and so on, and the `id` that goes with the closest, e.g.
+// `[d,id]=iU(1,L(x,y,z)-1,2,y+1),d`. The first of equally close ones wins.
+pub fn closest(xs: Vec<f32>) -> [f32; 2] {
+    let (id, d) = xs
+        .chunks(2)
+        .map(|pair| (pair[0], pair[1]))
+        .reduce(|a, b| if b.1 < a.1 { b } else { a })
+        .expect("iU needs an id and a distance");
+    [d, id]
+}
+
 pub fn round_min(xs: Vec<f32>) -> f32 {
     let mut xs = xs;
     let r = xs.pop().unwrap();
@@ -211,9 +226,15 @@ pub fn intersect(xs: Vec<f32>) -> f32 {
 
 #[macro_export]
 macro_rules! add_mul {
+    ($x:expr, $y:expr, $a:expr, $b:expr) => {
+        Vec2::new($x + $a, $y + $b)
+    };
     ($x:expr, $y:expr, $a:expr, $b:expr, $t:expr) => {
         Vec2::new($x + $a * $t, $y + $b * $t)
     };
+    ($x:expr, $y:expr, $z:expr, $a:expr, $b:expr, $c:expr) => {
+        Vec3::new($x + $a, $y + $b, $z + $c)
+    };
     ($x:expr, $y:expr, $z:expr, $a:expr, $b:expr, $c:expr, $t:expr) => {
         Vec3::new($x + $a * $t, $y + $b * $t, $z + $c * $t)
     };
@@ -224,19 +245,106 @@ pub fn smooth_abs(x: f32, p: f32) -> f32 {
 }
 
 pub fn smooth_min(a: f32, b: f32, r: f32) -> f32 {
-    if a < r && b < r {
-        r - Vec2::new(r - a, r - b).length()
-    } else {
-        a.min(b)
-    }
+    blend::round(a, b, r)
 }
 
 pub fn smooth_max(a: f32, b: f32, r: f32) -> f32 {
-    if -a < r && -b < r {
-        r - Vec2::new(r + a, r + b).length()
-    } else {
-        a.max(b)
-    }
+    blend::round_max(a, b, r)
+}
+
+// The blends of `blend` over all their arguments but the parameters at the
+// end, like `round_min`.
+
+pub fn poly_union(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 1, |a, b, p| blend::poly(a, b, p[0]))
+}
+
+pub fn poly_intersect(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 1, |a, b, p| {
+        blend::max(|a, b| blend::poly(a, b, p[0]), a, b)
+    })
+}
+
+pub fn cubic_union(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 1, |a, b, p| blend::cubic(a, b, p[0]))
+}
+
+pub fn cubic_intersect(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 1, |a, b, p| {
+        blend::max(|a, b| blend::cubic(a, b, p[0]), a, b)
+    })
+}
+
+pub fn exp_union(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 1, |a, b, p| blend::exponential(a, b, p[0]))
+}
+
+pub fn exp_intersect(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 1, |a, b, p| {
+        blend::max(|a, b| blend::exponential(a, b, p[0]), a, b)
+    })
+}
+
+pub fn root_union(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 1, |a, b, p| blend::root(a, b, p[0]))
+}
+
+pub fn root_intersect(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 1, |a, b, p| {
+        blend::max(|a, b| blend::root(a, b, p[0]), a, b)
+    })
+}
+
+pub fn circular_union(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 1, |a, b, p| blend::circular(a, b, p[0]))
+}
+
+pub fn circular_intersect(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 1, |a, b, p| {
+        blend::max(|a, b| blend::circular(a, b, p[0]), a, b)
+    })
+}
+
+pub fn chamfer_union(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 1, |a, b, p| blend::chamfer(a, b, p[0]))
+}
+
+pub fn chamfer_intersect(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 1, |a, b, p| {
+        blend::max(|a, b| blend::chamfer(a, b, p[0]), a, b)
+    })
+}
+
+pub fn stairs_union(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 2, |a, b, p| blend::stairs(a, b, p[0], p[1]))
+}
+
+pub fn stairs_intersect(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 2, |a, b, p| {
+        blend::max(|a, b| blend::stairs(a, b, p[0], p[1]), a, b)
+    })
+}
+
+pub fn columns_union(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 2, |a, b, p| blend::columns(a, b, p[0], p[1]))
+}
+
+pub fn columns_intersect(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 2, |a, b, p| {
+        blend::max(|a, b| blend::columns(a, b, p[0], p[1]), a, b)
+    })
+}
+
+pub fn groove(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 2, |a, b, p| blend::groove(a, b, p[0], p[1]))
+}
+
+pub fn tongue(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 2, |a, b, p| blend::tongue(a, b, p[0], p[1]))
+}
+
+pub fn pipe(xs: Vec<f32>) -> f32 {
+    blend::fold(&xs, 1, |a, b, p| blend::pipe(a, b, p[0]))
 }
 
 pub fn poly_smooth_abs(x: f32, m: f32) -> f32 {
@@ -260,6 +368,309 @@ pub fn torus(x: f32, y: f32, z: f32, r1: f32, r2: f32) -> f32 {
     sdf(p)
 }
 
+pub fn cone(x: f32, y: f32, z: f32, angle: f32, height: f32) -> f32 {
+    sdf::cone(v3(x, y, z), angle, height)
+}
+
+pub fn capped_cone(x: f32, y: f32, z: f32, height: f32, r1: f32, r2: f32) -> f32 {
+    sdf::capped_cone(v3(x, y, z), height, r1, r2)
+}
+
+pub fn round_cone(x: f32, y: f32, z: f32, r1: f32, r2: f32, height: f32) -> f32 {
+    sdf::round_cone(v3(x, y, z), r1, r2, height)
+}
+
+pub fn ellipsoid(x: f32, y: f32, z: f32, a: f32, b: f32, c: f32) -> f32 {
+    sdf::ellipsoid(v3(x, y, z), v3(a, b, c))
+}
+
+pub fn hex_prism(x: f32, y: f32, z: f32, radius: f32, height: f32) -> f32 {
+    sdf::hex_prism(v3(x, y, z), radius, height)
+}
+
+pub fn tri_prism(x: f32, y: f32, z: f32, radius: f32, height: f32) -> f32 {
+    sdf::tri_prism(v3(x, y, z), radius, height)
+}
+
+pub fn octahedron(x: f32, y: f32, z: f32, size: f32) -> f32 {
+    sdf::octahedron(v3(x, y, z), size)
+}
+
+pub fn pyramid(x: f32, y: f32, z: f32, height: f32) -> f32 {
+    sdf::pyramid(v3(x, y, z), height)
+}
+
+pub fn link(x: f32, y: f32, z: f32, length: f32, r1: f32, r2: f32) -> f32 {
+    sdf::link(v3(x, y, z), length, r1, r2)
+}
+
+pub fn solid_angle(x: f32, y: f32, z: f32, angle: f32, radius: f32) -> f32 {
+    sdf::solid_angle(v3(x, y, z), angle, radius)
+}
+
+pub fn capped_torus(x: f32, y: f32, z: f32, angle: f32, r1: f32, r2: f32) -> f32 {
+    sdf::capped_torus(v3(x, y, z), angle, r1, r2)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn ud_triangle(
+    x: f32,
+    y: f32,
+    z: f32,
+    ax: f32,
+    ay: f32,
+    az: f32,
+    bx: f32,
+    by: f32,
+    bz: f32,
+    cx: f32,
+    cy: f32,
+    cz: f32,
+) -> f32 {
+    sdf::unsigned_triangle(v3(x, y, z), v3(ax, ay, az), v3(bx, by, bz), v3(cx, cy, cz))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn ud_quad(
+    x: f32,
+    y: f32,
+    z: f32,
+    ax: f32,
+    ay: f32,
+    az: f32,
+    bx: f32,
+    by: f32,
+    bz: f32,
+    cx: f32,
+    cy: f32,
+    cz: f32,
+    dx: f32,
+    dy: f32,
+    dz: f32,
+) -> f32 {
+    sdf::unsigned_quad(
+        v3(x, y, z),
+        v3(ax, ay, az),
+        v3(bx, by, bz),
+        v3(cx, cy, cz),
+        v3(dx, dy, dz),
+    )
+}
+
+pub fn hexagon(x: f32, y: f32, radius: f32) -> f32 {
+    sdf::hexagon(Vec2::new(x, y), radius)
+}
+
+pub fn segment(x: f32, y: f32, ax: f32, ay: f32, bx: f32, by: f32) -> f32 {
+    sdf::segment(Vec2::new(x, y), Vec2::new(ax, ay), Vec2::new(bx, by))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn triangle2(x: f32, y: f32, ax: f32, ay: f32, bx: f32, by: f32, cx: f32, cy: f32) -> f32 {
+    sdf::triangle(
+        Vec2::new(x, y),
+        Vec2::new(ax, ay),
+        Vec2::new(bx, by),
+        Vec2::new(cx, cy),
+    )
+}
+
+// `d` is the distance to a shape in the xy plane, e.g. `ext(hex2(x,y,1),z,2)`.
+pub fn extrude(d: f32, z: f32, height: f32) -> f32 {
+    sdf::extrusion(d, z, height)
+}
+
+// The coordinates in the space of a shape moved by `dx, dy, dz`.
+pub fn translate(x: f32, y: f32, z: f32, dx: f32, dy: f32, dz: f32) -> [f32; 3] {
+    [x - dx, y - dy, z - dz]
+}
+
+// The coordinates in the space of a shape turned `angle` radians around the
+// axis `ax, ay, az`, like `core::rotate`.
+pub fn rotate(x: f32, y: f32, z: f32, ax: f32, ay: f32, az: f32, angle: f32) -> [f32; 3] {
+    (Quat::from_axis_angle(v3(ax, ay, az).normalize(), -angle) * v3(x, y, z)).to_array()
+}
+
+// The coordinates in the space of a shape scaled by `s`. Its distance has to
+// be scaled back, e.g. `[x,y,z]=scale(x,y,z,2),bx3(x,y,z,1)*2`, which the
+// parser's `scale(2,bx3(x,y,z,1))` does by itself.
+pub fn scale(x: f32, y: f32, z: f32, s: f32) -> [f32; 3] {
+    [x / s, y / s, z / s]
+}
+
+// The domain deformations of `core`, which has the notes on how much they
+// stretch distances.
+
+pub fn twist(x: f32, y: f32, z: f32, k: f32) -> [f32; 3] {
+    let (s, c) = (k * y).sin_cos();
+    [c * x - s * z, y, s * x + c * z]
+}
+
+pub fn bend(x: f32, y: f32, z: f32, k: f32) -> [f32; 3] {
+    let (s, c) = (k * x).sin_cos();
+    [c * x - s * y, s * x + c * y, z]
+}
+
+pub fn elongate(x: f32, y: f32, z: f32, a: f32, b: f32, c: f32) -> [f32; 3] {
+    [x - x.clamp(-a, a), y - y.clamp(-b, b), z - z.clamp(-c, c)]
+}
+
+pub fn onion(d: f32, thickness: f32) -> f32 {
+    d.abs() - thickness
+}
+
+// The coordinates in the plane of a solid of revolution around y,
+// e.g. `[u,v]=rev(x,y,z,3),hex2(u,v,1)`.
+pub fn revolve(x: f32, y: f32, z: f32, offset: f32) -> [f32; 2] {
+    [Vec2::new(x, z).length() - offset, y]
+}
+
+// `x` in the nearest of the cells `space` apart, `limit` of them each way.
+pub fn repeat(x: f32, space: f32, limit: f32) -> f32 {
+    x - space * (x / space).round().clamp(-limit, limit)
+}
+
+// `x` and `y` turned into the wedge of 1/n of a turn around the x axis.
+pub fn polar(x: f32, y: f32, n: f32) -> [f32; 2] {
+    let sector = TAU / n;
+    let a = y.atan2(x);
+    let a = a - sector * (a / sector).round();
+    let r = Vec2::new(x, y).length();
+    [r * a.cos(), r * a.sin()]
+}
+
+// The fractals of `fractal`, with a `_trap` function for each that returns
+// its orbit trap instead of the distance.
+
+pub fn mandelbulb(x: f32, y: f32, z: f32, power: f32, iterations: f32) -> f32 {
+    fractal::mandelbulb(v3(x, y, z), power, iterations as u32).0
+}
+
+pub fn mandelbulb_trap(x: f32, y: f32, z: f32, power: f32, iterations: f32) -> f32 {
+    fractal::mandelbulb(v3(x, y, z), power, iterations as u32).1
+}
+
+pub fn mandelbox(x: f32, y: f32, z: f32, scale: f32, iterations: f32) -> f32 {
+    fractal::mandelbox(v3(x, y, z), scale, iterations as u32).0
+}
+
+pub fn mandelbox_trap(x: f32, y: f32, z: f32, scale: f32, iterations: f32) -> f32 {
+    fractal::mandelbox(v3(x, y, z), scale, iterations as u32).1
+}
+
+pub fn menger(x: f32, y: f32, z: f32, iterations: f32) -> f32 {
+    fractal::menger(v3(x, y, z), iterations as u32).0
+}
+
+pub fn menger_trap(x: f32, y: f32, z: f32, iterations: f32) -> f32 {
+    fractal::menger(v3(x, y, z), iterations as u32).1
+}
+
+pub fn sierpinski(x: f32, y: f32, z: f32, iterations: f32) -> f32 {
+    fractal::sierpinski(v3(x, y, z), iterations as u32).0
+}
+
+pub fn sierpinski_trap(x: f32, y: f32, z: f32, iterations: f32) -> f32 {
+    fractal::sierpinski(v3(x, y, z), iterations as u32).1
+}
+
+// `kifs(x, y, z, scale, ox, oy, oz, iterations, nx, ny, nz, ...)` with the
+// normals of any number of fold planes at the end.
+fn kifs_args(xs: &[f32]) -> (f32, f32) {
+    let planes: Vec<Vec3> = xs[8..]
+        .chunks_exact(3)
+        .map(|n| v3(n[0], n[1], n[2]))
+        .collect();
+    fractal::kifs(
+        v3(xs[0], xs[1], xs[2]),
+        &planes,
+        xs[3],
+        v3(xs[4], xs[5], xs[6]),
+        xs[7] as u32,
+    )
+}
+
+pub fn kifs(xs: Vec<f32>) -> f32 {
+    kifs_args(&xs).0
+}
+
+pub fn kifs_trap(xs: Vec<f32>) -> f32 {
+    kifs_args(&xs).1
+}
+
+// The DSL's noises, seeded by `seed`, at `scale` times the point.
+fn fbm(basis: Basis, x: f32, y: f32, z: f32, scale: f32, seed: f32, octaves: f32) -> (Fbm, Vec3) {
+    (
+        Fbm::new(basis, seed as u32, octaves as u32),
+        v3(x, y, z) * scale,
+    )
+}
+
+pub fn seeded_value(x: f32, y: f32, z: f32, scale: f32, seed: f32, octaves: f32) -> f32 {
+    let (fbm, p) = fbm(Basis::Value, x, y, z, scale, seed, octaves);
+    fbm.get(p)
+}
+
+pub fn perlin(x: f32, y: f32, z: f32, scale: f32, seed: f32, octaves: f32) -> f32 {
+    let (fbm, p) = fbm(Basis::Perlin, x, y, z, scale, seed, octaves);
+    fbm.get(p)
+}
+
+pub fn simplex(x: f32, y: f32, z: f32, scale: f32, seed: f32, octaves: f32) -> f32 {
+    let (fbm, p) = fbm(Basis::Simplex, x, y, z, scale, seed, octaves);
+    fbm.get(p)
+}
+
+pub fn worley(x: f32, y: f32, z: f32, scale: f32, seed: f32, octaves: f32) -> f32 {
+    let (fbm, p) = fbm(Basis::Worley, x, y, z, scale, seed, octaves);
+    fbm.get(p)
+}
+
+// The distances to the nearest and second nearest cell points.
+pub fn cellular(x: f32, y: f32, z: f32, scale: f32, seed: f32) -> [f32; 2] {
+    noise::worley(v3(x, y, z) * scale, seed as u32).into()
+}
+
+pub fn ridged(x: f32, y: f32, z: f32, scale: f32, seed: f32, octaves: f32) -> f32 {
+    let (fbm, p) = fbm(Basis::Perlin, x, y, z, scale, seed, octaves);
+    fbm.ridged(p)
+}
+
+pub fn billow(x: f32, y: f32, z: f32, scale: f32, seed: f32, octaves: f32) -> f32 {
+    let (fbm, p) = fbm(Basis::Perlin, x, y, z, scale, seed, octaves);
+    fbm.billow(p)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn warp(x: f32, y: f32, z: f32, scale: f32, seed: f32, octaves: f32, strength: f32) -> f32 {
+    let (fbm, p) = fbm(Basis::Perlin, x, y, z, scale, seed, octaves);
+    fbm.warp(p, strength)
+}
+
+// The gradients are with respect to `x`, `y` and `z`.
+pub fn perlin_gradient(x: f32, y: f32, z: f32, scale: f32, seed: f32, octaves: f32) -> [f32; 3] {
+    let (fbm, p) = fbm(Basis::Perlin, x, y, z, scale, seed, octaves);
+    (fbm.gradient(p).1 * scale).into()
+}
+
+pub fn simplex_gradient(x: f32, y: f32, z: f32, scale: f32, seed: f32, octaves: f32) -> [f32; 3] {
+    let (fbm, p) = fbm(Basis::Simplex, x, y, z, scale, seed, octaves);
+    (fbm.gradient(p).1 * scale).into()
+}
+
+// The DSL's `bound(b, e)`, which only evaluates `e` near its bound `b`.
+#[macro_export]
+macro_rules! bound {
+    ($b:expr, $e:expr) => {{
+        let b = $b;
+        if b > MARGIN {
+            b
+        } else {
+            $e
+        }
+    }};
+}
+
 #[macro_export]
 macro_rules! value_noise {
     ($x:expr, $y:expr, $z:expr, $s:expr, $i:expr, $o:expr) => {
@@ -273,15 +684,15 @@ macro_rules! value_noise {
 #[macro_export]
 macro_rules! perlin_noise {
     ($x:expr, $y:expr, $z:expr, $s:expr, $i:expr, $o:expr) => {
-        fbm_perlin($x, $y, $z, $s, $i, $o as u32)
+        fbm_perlin($x, $y, $z, $s, $i, $o as u32, 0)
     };
     ($x:expr, $y:expr, $z:expr, $s:expr, $i:expr) => {
-        fbm_perlin($x, $y, $z, $s, $i, 1u32)
+        fbm_perlin($x, $y, $z, $s, $i, 1u32, 0)
     };
 }
 
 pub fn hash(x: f32, y: f32, z: f32) -> f32 {
-    crate::core::hash(v3(x, y, z))
+    noise::hash(v3(x, y, z))
 }
 
 #[macro_export]
@@ -330,6 +741,10 @@ pub fn rot0(x: f32, y: f32, a: f32) -> [f32; 2] {
     [result.x, result.y]
 }
 
+pub fn rot1(x: f32, y: f32, a: f32) -> [f32; 2] {
+    rot0(x, y, a)
+}
+
 pub fn rot(x: f32, y: f32, c: f32, s: f32) -> [f32; 2] {
     [c * x + s * y, c * y - s * x]
 }
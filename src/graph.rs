@@ -0,0 +1,379 @@
+// A scene as named objects instead of one program. Each object has its own DSL
+// program, moved by `translate`, turned `angle` radians around `axis` and
+// scaled by `scale` from its own space, like the DSL functions of the same
+// names. `material` names one of the scene's materials, which it's made of
+// unless the program sets `mat`, and objects that aren't `visible` are left
+// out of the picture but keep their ids.
+//
+// `program` joins the objects with `iU`, setting `id` to the index of the
+// closest one, for the material, `View::pick` and `render_ids`. A scene's own
+// `source`, if it has one, joins them with the id -1, which is no object. The
+// variables of object `i`, including its `x`, `y` and `z`, are renamed `oix`
+// and so on, so the objects don't see each other's; the parameters aren't.
+// Each keeps its own `col` and `mat` too, and `SceneFile::materials` uses
+// the closest one's.
+use crate::ast::{AssignExpr, BinOp, Expr, FunctionName, Statement};
+use crate::core::ZERO3;
+use crate::pratt::parse;
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Object {
+    pub name: String,
+    pub source: String,
+    pub translate: Vec3,
+    pub axis: Vec3,
+    pub angle: f32,
+    pub scale: f32,
+    pub material: Option<String>,
+    pub visible: bool,
+}
+
+impl Default for Object {
+    fn default() -> Self {
+        Object {
+            name: String::new(),
+            source: String::new(),
+            translate: ZERO3,
+            axis: Vec3::Y,
+            angle: 0.0,
+            scale: 1.0,
+            material: None,
+            visible: true,
+        }
+    }
+}
+
+// The object closest to a point, if the program tags them.
+pub type Ids = Box<dyn Fn(Vec3) -> Option<u32> + Sync>;
+
+impl Object {
+    pub fn new(name: &str, source: &str) -> Self {
+        Object {
+            name: name.to_string(),
+            source: source.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn body(&self) -> Vec<Statement> {
+        match parse(&mut self.source.as_str()) {
+            Statement::Sequence(stmts) => stmts,
+            stmt => vec![stmt],
+        }
+    }
+
+    fn ends_in_distance(&self) -> bool {
+        matches!(
+            self.body().last(),
+            Some(Statement::Return(_) | Statement::Assign { .. })
+        )
+    }
+
+    // The object's program in the point `x`, `y`, `z` of the scene, with its
+    // variables starting with `prefix` and its distance in `{prefix}d`.
+    fn statements(&self, prefix: &str) -> Vec<Statement> {
+        let var = |name: &str| format!("{}{}", prefix, name);
+        let point = || ["x", "y", "z"].map(var).to_vec();
+        let call = |name: FunctionName, params: &[f32]| {
+            let mut args: Vec<Expr> = point().into_iter().map(Expr::Variable).collect();
+            args.extend(params.iter().map(|&a| Expr::Number(a)));
+            Statement::AssignToArray {
+                vars: point(),
+                rhs: Box::new(Expr::Function { name, args }),
+            }
+        };
+        let mut stmts = vec![Statement::AssignFromArray {
+            vars: point(),
+            rhs: ["x", "y", "z"]
+                .map(|v| Expr::Variable(v.to_string()))
+                .to_vec(),
+        }];
+        if self.translate != ZERO3 {
+            stmts.push(call(FunctionName::Translate, &self.translate.to_array()));
+        }
+        if self.angle != 0.0 {
+            let [ax, ay, az] = self.axis.to_array();
+            stmts.push(call(FunctionName::Rotate, &[ax, ay, az, self.angle]));
+        }
+        if self.scale != 1.0 {
+            stmts.push(call(FunctionName::Scale, &[self.scale]));
+        }
+        let body = self.body();
+        let mut locals: HashSet<String> = ["x", "y", "z"].map(String::from).into();
+        body.iter().for_each(|stmt| assigned(stmt, &mut locals));
+        let rename = |name: &String| {
+            if locals.contains(name) {
+                var(name)
+            } else {
+                name.clone()
+            }
+        };
+        stmts.extend(body.iter().map(|stmt| rename_statement(stmt, &rename)));
+        let d = match stmts.last() {
+            Some(Statement::Return(e)) => {
+                let d = (**e).clone();
+                stmts.pop();
+                d
+            }
+            Some(Statement::Assign { var, .. }) => Expr::Variable(var.clone()),
+            _ => panic!("{}: an object's source must end in its distance", self.name),
+        };
+        let d = if self.scale != 1.0 {
+            Expr::BinaryOp(BinOp::Mul(Box::new(d), Box::new(Expr::Number(self.scale))))
+        } else {
+            d
+        };
+        stmts.push(Statement::Assign {
+            var: var("d"),
+            rhs: Box::new(d),
+        });
+        stmts
+    }
+}
+
+// One program for the visible objects, and `source` unless it's empty,
+// setting `id` and returning the distance to the closest.
+pub fn program(source: &str, objects: &[Object]) -> Statement {
+    let mut stmts = Vec::new();
+    let mut args = Vec::new();
+    let source = Object::new("source", source);
+    for id in parts(&source.source, objects) {
+        let object = if id < 0.0 {
+            &source
+        } else {
+            &objects[id as usize]
+        };
+        stmts.extend(object.statements(&local(id, "")));
+        args.push(Expr::Number(id));
+        args.push(Expr::Variable(local(id, "d")));
+    }
+    assert!(!args.is_empty(), "a scene needs a visible object");
+    stmts.push(Statement::AssignToArray {
+        vars: vec!["d".to_string(), "id".to_string()],
+        rhs: Box::new(Expr::Function {
+            name: FunctionName::Closest,
+            args,
+        }),
+    });
+    stmts.push(Statement::Return(Box::new(Expr::Variable("d".to_string()))));
+    Statement::Sequence(stmts)
+}
+
+// Why `program` can't join `source` and the objects, if it can't, so scene
+// files can say so instead of panicking.
+pub fn check(source: &str, objects: &[Object]) -> Result<(), String> {
+    if parts(source, objects).is_empty() {
+        return Err("a scene needs a visible object".to_string());
+    }
+    let source = Object::new("source", source);
+    let own = (!source.source.trim().is_empty()).then_some(&source);
+    match own
+        .into_iter()
+        .chain(objects)
+        .find(|o| !o.ends_in_distance())
+    {
+        Some(object) => Err(format!(
+            "{}: an object's source must end in its distance",
+            object.name
+        )),
+        None => Ok(()),
+    }
+}
+
+// The ids of what `program` joins, -1 for `source` unless it's empty and
+// then the visible objects.
+pub fn parts(source: &str, objects: &[Object]) -> Vec<f32> {
+    let visible = objects
+        .iter()
+        .enumerate()
+        .filter(|(_, object)| object.visible)
+        .map(|(i, _)| i as f32);
+    (!source.trim().is_empty())
+        .then_some(-1.0)
+        .into_iter()
+        .chain(visible)
+        .collect()
+}
+
+// What `program` renames the variable `var` of the object `id` to, -1 being
+// the scene's own source.
+pub fn local(id: f32, var: &str) -> String {
+    if id < 0.0 {
+        format!("s{}", var)
+    } else {
+        format!("o{}{}", id as u32, var)
+    }
+}
+
+// The object an `id` the program sets is, if it is one.
+pub fn object(id: Option<f32>) -> Option<u32> {
+    id.filter(|&id| id >= 0.0).map(|id| id as u32)
+}
+
+// Whether the program assigns `var` anywhere, whichever way it goes.
+pub fn assigns(ast: &Statement, var: &str) -> bool {
+    let mut vars = HashSet::new();
+    assigned(ast, &mut vars);
+    vars.contains(var)
+}
+
+fn assigned(stmt: &Statement, vars: &mut HashSet<String>) {
+    match stmt {
+        Statement::Assign { var, .. } => {
+            vars.insert(var.clone());
+        }
+        Statement::AssignToArray { vars: vs, .. } | Statement::AssignFromArray { vars: vs, .. } => {
+            vars.extend(vs.iter().cloned());
+        }
+        Statement::Sequence(stmts) => stmts.iter().for_each(|s| assigned(s, vars)),
+        Statement::Return(_) | Statement::Empty => {}
+    }
+}
+
+fn rename_statement(stmt: &Statement, f: &impl Fn(&String) -> String) -> Statement {
+    match stmt {
+        Statement::Assign { var, rhs } => Statement::Assign {
+            var: f(var),
+            rhs: Box::new(rename(rhs, f)),
+        },
+        Statement::AssignToArray { vars, rhs } => Statement::AssignToArray {
+            vars: vars.iter().map(f).collect(),
+            rhs: Box::new(rename(rhs, f)),
+        },
+        Statement::AssignFromArray { vars, rhs } => Statement::AssignFromArray {
+            vars: vars.iter().map(f).collect(),
+            rhs: rhs.iter().map(|e| rename(e, f)).collect(),
+        },
+        Statement::Sequence(stmts) => {
+            Statement::Sequence(stmts.iter().map(|s| rename_statement(s, f)).collect())
+        }
+        Statement::Return(e) => Statement::Return(Box::new(rename(e, f))),
+        Statement::Empty => Statement::Empty,
+    }
+}
+
+fn rename(e: &Expr, f: &impl Fn(&String) -> String) -> Expr {
+    let r = |e: &Expr| Box::new(rename(e, f));
+    match e {
+        Expr::Number(x) => Expr::Number(*x),
+        Expr::Variable(name) => Expr::Variable(f(name)),
+        Expr::Negate(a) => Expr::Negate(r(a)),
+        Expr::Function { name, args } => Expr::Function {
+            name: name.clone(),
+            args: args.iter().map(|a| rename(a, f)).collect(),
+        },
+        Expr::TernaryOp(c, t, e) => Expr::TernaryOp(r(c), r(t), r(e)),
+        Expr::Assign(AssignExpr::Inc(var)) => Expr::Assign(AssignExpr::Inc(f(var))),
+        Expr::Assign(AssignExpr::Dec(var)) => Expr::Assign(AssignExpr::Dec(f(var))),
+        Expr::BinaryOp(op) => {
+            use BinOp::*;
+            Expr::BinaryOp(match op {
+                Add(a, b) => Add(r(a), r(b)),
+                Sub(a, b) => Sub(r(a), r(b)),
+                Mul(a, b) => Mul(r(a), r(b)),
+                Div(a, b) => Div(r(a), r(b)),
+                Eq(a, b) => Eq(r(a), r(b)),
+                NotEq(a, b) => NotEq(r(a), r(b)),
+                Greater(a, b) => Greater(r(a), r(b)),
+                GreaterEq(a, b) => GreaterEq(r(a), r(b)),
+                Less(a, b) => Less(r(a), r(b)),
+                LessEq(a, b) => LessEq(r(a), r(b)),
+                And(a, b) => And(r(a), r(b)),
+                Or(a, b) => Or(r(a), r(b)),
+                Pow(a, b) => Pow(r(a), r(b)),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::v3;
+    use crate::eval::{make_sdf_with, outputs_with};
+
+    #[test]
+    fn objects() {
+        let ball = Object {
+            translate: v3(-5.0, 0.0, 0.0),
+            ..Object::new("ball", "r=2,L(x,y,z)-r")
+        };
+        let cube = Object {
+            translate: v3(5.0, 0.0, 0.0),
+            axis: Vec3::Z,
+            angle: std::f32::consts::FRAC_PI_4,
+            scale: 2.0,
+            ..Object::new("cube", "bx3(x,y,z,1)")
+        };
+        let hidden = Object {
+            visible: false,
+            ..Object::new("hidden", "L(x,y,z)-1")
+        };
+        let ast = program("y+10", &[ball, hidden, cube]);
+        let at = |p| {
+            let d = make_sdf_with(&ast, [], p);
+            (d, object(outputs_with(&ast, [], p, &["id"])[0]))
+        };
+        assert_eq!(at(v3(-5.0, 3.0, 0.0)), (1.0, Some(0)));
+        // Turned onto its edge and twice the size.
+        let (d, id) = at(v3(5.0, 2.0f32.sqrt() * 2.0 + 1.0, 0.0));
+        assert!((d - 1.0).abs() < 1e-5);
+        assert_eq!(id, Some(2));
+        assert_eq!(at(v3(0.0, -9.0, 0.0)), (1.0, None));
+        // The hidden one isn't there, and `r` is the ball's.
+        assert_eq!(at(v3(-1.0, 0.0, 0.0)).1, Some(0));
+        assert_eq!(
+            make_sdf_with(
+                &program("r", &[Object::new("a", "r=1,r")]),
+                [("r", 3.0)],
+                ZERO3
+            ),
+            1.0
+        );
+    }
+
+    #[test]
+    fn clashes() {
+        // Both have an `r` and a `d`, and the first moves its `x`.
+        let a = Object::new("a", "r=1,x-=3,d=L(x,y,z)-r,d");
+        let b = Object::new("b", "r=2,d=L(x,y,z)-r,x+d");
+        let ast = program("", &[a, b]);
+        let at = |p| {
+            let d = make_sdf_with(&ast, [], p);
+            (d, object(outputs_with(&ast, [], p, &["id"])[0]))
+        };
+        assert_eq!(at(v3(3.0, 0.0, 0.0)), (-1.0, Some(0)));
+        assert_eq!(at(ZERO3), (-2.0, Some(1)));
+        assert_eq!(at(v3(0.0, 5.0, 0.0)), (3.0, Some(1)));
+    }
+
+    #[test]
+    fn closest() {
+        let ast = parse(&mut "[d,id]=iU(3,L(x,y,z)-1,5,L(x-4,y,z)-1),d");
+        let at = |p| {
+            let d = make_sdf_with(&ast, [], p);
+            (d, outputs_with(&ast, [], p, &["id"])[0])
+        };
+        assert_eq!(at(v3(-2.0, 0.0, 0.0)), (1.0, Some(3.0)));
+        assert_eq!(at(v3(4.0, 2.0, 0.0)), (1.0, Some(5.0)));
+        assert_eq!(object(Some(-1.0)), None);
+    }
+
+    #[test]
+    fn checks() {
+        let hidden = [Object {
+            visible: false,
+            ..Object::new("hidden", "L(x,y,z)-1")
+        }];
+        assert!(check("", &hidden).is_err());
+        assert!(check("y+1", &hidden).is_ok());
+        let unfinished = [Object::new("unfinished", "[a,b]=[x,y]")];
+        assert!(check("", &unfinished).is_err());
+        assert!(check("[a,b]=[x,y]", &hidden).is_err());
+        assert!(check("", &[Object::new("ball", "r=2,L(x,y,z)-r")]).is_ok());
+    }
+}
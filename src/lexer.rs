@@ -79,21 +79,21 @@ impl winnow::stream::ContainsToken<Token> for Token {
 impl winnow::stream::ContainsToken<Token> for &'_ [Token] {
     #[inline]
     fn contains_token(&self, token: Token) -> bool {
-        self.iter().any(|t| *t == token)
+        self.contains(&token)
     }
 }
 
 impl<const LEN: usize> winnow::stream::ContainsToken<Token> for &'_ [Token; LEN] {
     #[inline]
     fn contains_token(&self, token: Token) -> bool {
-        self.iter().any(|t| *t == token)
+        self.contains(&token)
     }
 }
 
 impl<const LEN: usize> winnow::stream::ContainsToken<Token> for [Token; LEN] {
     #[inline]
     fn contains_token(&self, token: Token) -> bool {
-        self.iter().any(|t| *t == token)
+        self.contains(&token)
     }
 }
 
@@ -113,6 +113,7 @@ impl Lexer {
         Lexer { tokens }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Token {
         self.tokens.pop().unwrap_or(Token::Eof)
     }
@@ -175,6 +176,11 @@ fn identifier(i: &mut &str) -> PResult<Token> {
         Some(c) => format!("{}{}", c1, c),
         None => c1.to_string(),
     };
+    // A function's name that isn't called, as in `g=1` or `U(a,g)`, is a
+    // variable.
+    if opt(peek((multispace0, '('))).parse_next(i)?.is_none() {
+        return Ok(Variable(s));
+    }
     match s.as_str() {
         "sin" => Ok(Function(Sin)),
         "cos" => Ok(Function(Cos)),
@@ -212,6 +218,74 @@ fn identifier(i: &mut &str) -> PResult<Token> {
         "don" => Ok(Function(Torus)),
         "bx2" => Ok(Function(Box2)),
         "bx3" => Ok(Function(Box3)),
+        "cone" => Ok(Function(Cone)),
+        "ccone" => Ok(Function(CappedCone)),
+        "rcone" => Ok(Function(RoundCone)),
+        "ell" => Ok(Function(Ellipsoid)),
+        "hex3" => Ok(Function(HexPrism)),
+        "tri3" => Ok(Function(TriPrism)),
+        "oct" => Ok(Function(Octahedron)),
+        "pyr" => Ok(Function(Pyramid)),
+        "link" => Ok(Function(Link)),
+        "sang" => Ok(Function(SolidAngle)),
+        "ctor" => Ok(Function(CappedTorus)),
+        "utri" => Ok(Function(UdTriangle)),
+        "uquad" => Ok(Function(UdQuad)),
+        "hex2" => Ok(Function(Hexagon)),
+        "seg2" => Ok(Function(Segment)),
+        "tri2" => Ok(Function(Triangle2)),
+        "ext" => Ok(Function(Extrude)),
+        "tw" => Ok(Function(Twist)),
+        "bend" => Ok(Function(Bend)),
+        "el" => Ok(Function(Elongate)),
+        "onion" => Ok(Function(Onion)),
+        "rev" => Ok(Function(Revolve)),
+        "rep" => Ok(Function(Repeat)),
+        "pol" => Ok(Function(Polar)),
+        "pU" => Ok(Function(PolyMin)),
+        "pG" => Ok(Function(PolyMax)),
+        "cU" => Ok(Function(CubicMin)),
+        "cG" => Ok(Function(CubicMax)),
+        "eU" => Ok(Function(ExpMin)),
+        "eG" => Ok(Function(ExpMax)),
+        "qU" => Ok(Function(RootMin)),
+        "qG" => Ok(Function(RootMax)),
+        "oU" => Ok(Function(CircularMin)),
+        "oG" => Ok(Function(CircularMax)),
+        "chU" => Ok(Function(ChamferMin)),
+        "chG" => Ok(Function(ChamferMax)),
+        "stU" => Ok(Function(StairsMin)),
+        "stG" => Ok(Function(StairsMax)),
+        "coU" => Ok(Function(ColumnsMin)),
+        "coG" => Ok(Function(ColumnsMax)),
+        "grv" => Ok(Function(Groove)),
+        "tng" => Ok(Function(Tongue)),
+        "pipe" => Ok(Function(Pipe)),
+        "translate" => Ok(Function(Translate)),
+        "rotate" => Ok(Function(Rotate)),
+        "scale" => Ok(Function(Scale)),
+        "mbulb" => Ok(Function(Mandelbulb)),
+        "mbulbt" => Ok(Function(MandelbulbTrap)),
+        "mbox" => Ok(Function(Mandelbox)),
+        "mboxt" => Ok(Function(MandelboxTrap)),
+        "menger" => Ok(Function(Menger)),
+        "mengert" => Ok(Function(MengerTrap)),
+        "sierp" => Ok(Function(Sierpinski)),
+        "sierpt" => Ok(Function(SierpinskiTrap)),
+        "kifs" => Ok(Function(Kifs)),
+        "kifst" => Ok(Function(KifsTrap)),
+        "vnz" => Ok(Function(SeededValue)),
+        "pnz" => Ok(Function(Perlin)),
+        "snz" => Ok(Function(Simplex)),
+        "wnz" => Ok(Function(Worley)),
+        "cell" => Ok(Function(Cellular)),
+        "rnz" => Ok(Function(Ridged)),
+        "bnz" => Ok(Function(Billow)),
+        "wrp" => Ok(Function(Warp)),
+        "pgrad" => Ok(Function(PerlinGradient)),
+        "sgrad" => Ok(Function(SimplexGradient)),
+        "bound" => Ok(Function(Bound)),
+        "iU" => Ok(Function(Closest)),
         "r0" => Ok(Function(Rot0)),
         "r1" => Ok(Function(Rot1)),
         "TR" => Ok(Function(Triangle)),
@@ -244,17 +318,27 @@ fn identifier(i: &mut &str) -> PResult<Token> {
 }
 
 #[cfg(test)]
-
 mod tests {
     use super::*;
 
     #[test]
-    fn show() {
-        // let mut input = "s=1;@5{@xyz{$=B($*2)-8,}s*=.5,}(L(x,y,z)-8)*s";
-        let input = "ri(xi,yi,zi)>.4&&L(xi,yi,zi)>3?L(xm,ym,zm)-2:10";
-        // let mut input = "variable0 = 1 + 2.8 * 3 - 4 / 5 % 6 ** 7";
-        let i = expand(input);
-        let _ = dbg!(lex.parse_peek(&i));
+    fn test_variable_or_call() {
+        use Token::*;
+        let input = "U(a,g)+g (L)";
+        let expected = vec![
+            Function(FunctionName::Union),
+            LParen,
+            Variable("a".to_string()),
+            Comma,
+            Variable("g".to_string()),
+            RParen,
+            Operator(Op::Add),
+            Function(FunctionName::FakeSine),
+            LParen,
+            Variable("L".to_string()),
+            RParen,
+        ];
+        assert_eq!(lex.parse_peek(input), Ok(("", expected)));
     }
 
     #[test]
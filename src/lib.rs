@@ -1,11 +1,29 @@
 pub mod ast;
+pub mod blend;
+pub mod bound;
 pub mod codegen;
 pub mod core;
+pub mod differential;
+pub mod environment;
 pub mod eval;
 pub mod expand;
+pub mod fog;
+pub mod fractal;
 pub mod functions;
+pub mod graph;
 pub mod lexer;
 pub mod march;
+pub mod material;
+pub mod node;
+pub mod noise;
+pub mod optimize;
+pub mod path;
+pub mod post;
 pub mod pratt;
+pub mod sampling;
+pub mod scene;
 pub mod sdf;
 pub mod sdfs;
+pub mod tiles;
+pub mod tonemap;
+pub mod watch;
@@ -1,43 +1,111 @@
 use arrow::core::*;
-// use arrow::eval::make_sdf;
-use arrow::march::render;
-// use arrow::sdf::examples;
-use arrow::sdfs::*;
+use arrow::path::denoise;
+use arrow::post::Frame;
+use arrow::scene::{library, SceneFile, Settings};
+use arrow::tiles::{passes, Cancel, TiledImage};
+use arrow::watch::watch;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
-#[allow(unused_imports)]
-use arrow::sdf::sd_plane;
+const CHECKPOINT: Duration = Duration::from_secs(10);
 
-const S: u32 = 1;
-const M: u32 = 1;
-const WIDTH: u32 = M * 1024 / S;
-const HEIGHT: u32 = M * 768 / S;
-const AA: u32 = 3;
+// FNV-1a, which unlike `DefaultHasher` is the same in every Rust release, so
+// checkpoints stay valid across toolchains.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
 
+// Renders a scene from the library, or a scene file, e.g.
+// `cargo run --release -- asurf hatch.png` or `cargo run -- my_scene.toml`.
+// The hand written version in `sdfs` is used if there is one, run with
+// ARROW_INTERPRET=1 to use the interpreter anyway. Ctrl-C stops the render
+// after saving a checkpoint to resume from.
+//
+// `cargo run --release -- watch my_scene.arrow` renders the file every time
+// it changes, see `arrow::watch`.
 fn main() {
-    // use arrow::pratt::*;
-    let background = 0.75;
-    // let examples = examples();
-    // let (mut input, camera) = *examples.get("system").unwrap();
-    // let ast = parse(&mut input);
-    // dbg!(&ast);
-    // let sdf: Sdf = Box::new(move |p| make_sdf(&ast, 0.2, 0.4, p));
-    let Scene { sdf, camera } = scene("asurf");
-    let sdf: Sdf = Box::new(sdf);
-    println!("sdf: {}", sdf(camera));
-    let img_data = render(
-        &sdf,
-        // Camera position
-        camera,
-        // Look at
-        ZERO3,
-        &vec![
-            Light::new(v3(0.0, 0.0, -50.0), 1.0),
-            Light::new(v3(0.0, 10.0, 40.0), 1.0),
-        ],
-        background,
-        WIDTH,
-        HEIGHT,
-        AA, // Anti-aliasing
-    );
-    image::save_buffer("hatch.png", &img_data, WIDTH, HEIGHT, image::ColorType::L8).unwrap();
+    let mut args = std::env::args().skip(1);
+    let mut name = args.next().unwrap_or("asurf".to_string());
+    let watching = name == "watch";
+    if watching {
+        name = args.next().expect("watch needs a file");
+    }
+    let output = args.next().unwrap_or("hatch.png".to_string());
+    if watching {
+        watch(Path::new(&name), Path::new(&output));
+    }
+    let scene = if Path::new(&name).is_file() {
+        SceneFile::load(&name).unwrap_or_else(|e| panic!("{}", e))
+    } else {
+        library()[name.as_str()].clone()
+    };
+    let compiled = scene
+        .compiled()
+        .filter(|_| std::env::var("ARROW_INTERPRET").is_err());
+    let sdf: Sdf = match compiled {
+        Some(sdf) => Box::new(sdf),
+        None => scene.sdf(),
+    };
+    println!("sdf: {}", sdf(scene.camera.position));
+    let Settings {
+        width,
+        height,
+        aa,
+        tone,
+        ref post,
+        ..
+    } = scene.settings;
+    // PNG, or HDR or EXR to keep the linear values, see `arrow::tonemap`,
+    // after the filters of `arrow::post`.
+    let save = |pixels: &[Lum]| {
+        Frame::new(width, height, pixels.to_vec(), tone)
+            .post(post)
+            .save(&output)
+            .unwrap()
+    };
+    // The image is written after every pass and checkpointed every
+    // CHECKPOINT, a render of the same scene with the same sdf picks up from
+    // the checkpoint.
+    let checkpoint = format!("{}.checkpoint", output);
+    let sdf_kind = if compiled.is_some() {
+        "compiled"
+    } else {
+        "interpreted"
+    };
+    let key = fnv1a(format!("{}\n{}", sdf_kind, scene.to_toml()).as_bytes());
+    let mut image = TiledImage::load(&checkpoint)
+        .ok()
+        .filter(|image| image.key == key)
+        .unwrap_or_else(|| TiledImage::new(width, height, key));
+    let view = scene.view(&sdf);
+    let mut saved = Instant::now();
+    let cancel = Cancel::new();
+    let stop = cancel.clone();
+    ctrlc::set_handler(move || stop.cancel()).expect("can't handle Ctrl-C");
+    let rendered = image.render(&view, &passes(aa), &cancel, |p, image| {
+        eprint!(
+            "\rblocks of {} aa {}: {}/{} tiles ",
+            p.block, p.aa, p.done, p.tiles
+        );
+        if p.done == p.tiles {
+            save(&image.pixels);
+        }
+        if saved.elapsed() > CHECKPOINT {
+            image.save(&checkpoint).unwrap();
+            saved = Instant::now();
+        }
+    });
+    eprintln!();
+    if rendered.is_err() {
+        image.save(&checkpoint).unwrap();
+        eprintln!("stopped, run again to resume from {}", checkpoint);
+        return;
+    }
+    if view.path.is_some_and(|path| path.denoise) {
+        image.pixels = denoise(&view, &image.pixels);
+    }
+    save(&image.pixels);
+    let _ = std::fs::remove_file(&checkpoint);
 }
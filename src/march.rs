@@ -1,13 +1,21 @@
-use crate::core::{v3, Light, Lum, Sdf, LUM, SHINE};
+use crate::bound::Bound;
+use crate::core::{v3, Light, LightKind, Lum, Sdf, LUM, SHINE};
+use crate::environment::Environment;
+use crate::fog::Atmosphere;
+use crate::graph::Ids;
+use crate::material::{Bounces, Materials};
+use crate::path::{denoise, PathTracer};
+use crate::sampling::{random, Sampling};
+use crate::tonemap::ToneMap;
 use glam::{Mat3, Vec3};
 use rayon::prelude::*;
 use wassily::stipple::poisson_disk;
 
 const MAX_STEPS: u32 = 128; //512;
 const MAX_DIST: f32 = 75.0;
-const EPSILON: f32 = 0.001;
+pub(crate) const EPSILON: f32 = 0.001;
 
-fn reflect(i: Vec3, n: Vec3) -> Vec3 {
+pub(crate) fn reflect(i: Vec3, n: Vec3) -> Vec3 {
     i - n * 2.0 * i.dot(n)
 }
 
@@ -20,7 +28,7 @@ fn phong(light_dir: Vec3, normal: Vec3, rd: Vec3) -> Lum {
     LUM + diffuse + specular
 }
 
-fn normal(p: Vec3, sdf: &Sdf) -> Vec3 {
+pub(crate) fn normal(p: Vec3, sdf: &(impl Fn(Vec3) -> f32 + ?Sized)) -> Vec3 {
     let x = v3(EPSILON, 0.0, 0.0);
     let y = v3(0.0, EPSILON, 0.0);
     let z = v3(0.0, 0.0, EPSILON);
@@ -70,30 +78,462 @@ fn ambient_occlusion(sdf: &Sdf, p: Vec3, n: Vec3) -> f32 {
     (1.0 - 3.0 * occ).clamp(0.0, 1.0)
 }
 
-fn march(sdf: &Sdf, ro: Vec3, rd: Vec3, lights: &[Light], background: Lum) -> Lum {
-    let mut total_dist = 0.0;
+// How far along the ray it hits something, if it does.
+pub(crate) fn intersect(sdf: &(impl Fn(Vec3) -> f32 + ?Sized), ro: Vec3, rd: Vec3) -> Option<f32> {
+    intersect_between(sdf, ro, rd, 0.0, MAX_DIST)
+}
+
+// Like `intersect`, only looking from `near` to `far` along the ray.
+fn intersect_between(
+    sdf: &(impl Fn(Vec3) -> f32 + ?Sized),
+    ro: Vec3,
+    rd: Vec3,
+    near: f32,
+    far: f32,
+) -> Option<f32> {
+    let mut total_dist = near;
     for _ in 0..MAX_STEPS {
-        let p = ro + rd * total_dist;
-        let dist = sdf(p);
+        let dist = sdf(ro + rd * total_dist);
         if dist.abs() < EPSILON {
-            let n = normal(p, sdf);
-            let mut col = 0.0;
-            lights.iter().for_each(|light| {
-                col += light.intensity
-                    * phong((light.position - p).normalize(), n, rd)
-                    * softshadow(sdf, p, (light.position - p).normalize(), 0.2, 1.0, 4.0)
-                    * ambient_occlusion(sdf, p, n);
-            });
-            return col;
-        }
-        if total_dist > MAX_DIST {
+            return Some(total_dist);
+        }
+        if total_dist > far {
             break;
         }
         total_dist += dist;
     }
-    background
+    None
+}
+
+// The light reaching `p` on a surface with normal `n`, seen along `rd`,
+// straight from the lights.
+fn lighting(
+    sdf: &Sdf,
+    p: Vec3,
+    n: Vec3,
+    rd: Vec3,
+    lights: &[Light],
+    environment: Option<&Environment>,
+) -> Lum {
+    let mut col = 0.0;
+    lights.iter().for_each(|light| {
+        let l = (light.position - p).normalize();
+        col += match light.kind {
+            LightKind::Point => {
+                light.intensity
+                    * phong(l, n, rd)
+                    * softshadow(sdf, p, l, 0.2, 1.0, 4.0)
+                    * ambient_occlusion(sdf, p, n)
+                    * light.attenuate(p)
+            }
+            // Far away, so its shadows are long and sharp.
+            LightKind::Directional => {
+                let l = light.position.normalize();
+                light.intensity
+                    * phong(l, n, rd)
+                    * softshadow(sdf, p, l, 0.2, MAX_DIST, 16.0)
+                    * ambient_occlusion(sdf, p, n)
+            }
+            // Shadows reach all the way to the light.
+            LightKind::Spot => {
+                let d = light.position.distance(p);
+                light.intensity
+                    * phong(l, n, rd)
+                    * light.cone(p)
+                    * softshadow(sdf, p, l, 0.2, d, 8.0)
+                    * ambient_occlusion(sdf, p, n)
+                    * light.attenuate(p)
+            }
+            LightKind::Sphere => {
+                light.intensity
+                    * phong(l, n, rd)
+                    * penumbra(sdf, p, n, light)
+                    * ambient_occlusion(sdf, p, n)
+                    * light.attenuate(p)
+            }
+            LightKind::Environment => match environment {
+                Some(environment) => light.intensity * sky(sdf, p, n, light, environment),
+                None => 0.0,
+            },
+        };
+    });
+    col
+}
+
+// How much of a sphere light `p` sees, from shadow rays to points on it.
+fn penumbra(sdf: &Sdf, p: Vec3, n: Vec3, light: &Light) -> f32 {
+    let samples = light.samples.max(1);
+    let ro = p + n * OFFSET;
+    let visible = (0..samples)
+        .filter(|&i| {
+            let q = light.position + direction(p, i) * light.radius;
+            let d = q.distance(ro);
+            intersect(sdf, ro, (q - ro) / d).is_none_or(|t| t > d)
+        })
+        .count();
+    visible as f32 / samples as f32
+}
+
+// The average light from the environment over the directions around `n` that
+// aren't blocked, weighted by their cosines.
+fn sky(sdf: &Sdf, p: Vec3, n: Vec3, light: &Light, environment: &Environment) -> Lum {
+    let samples = light.samples.max(1);
+    let ro = p + n * OFFSET;
+    let sum: Lum = (0..samples)
+        .map(|i| {
+            let d = (n + direction(p, i)).normalize();
+            match intersect(sdf, ro, d) {
+                Some(_) => 0.0,
+                None => environment.lookup(d),
+            }
+        })
+        .sum();
+    sum / samples as f32
+}
+
+// The luminosity along the ray and how far it went before it hit something,
+// infinity if it didn't.
+fn march(sdf: &Sdf, ro: Vec3, rd: Vec3, lights: &[Light], background: Lum) -> (Lum, f32) {
+    let Some(total_dist) = intersect(sdf, ro, rd) else {
+        return (background, f32::INFINITY);
+    };
+    let p = ro + rd * total_dist;
+    let n = normal(p, sdf);
+    (lighting(sdf, p, n, rd, lights, None), total_dist)
+}
+
+// How far off a surface reflected and refracted rays start.
+const OFFSET: f32 = 10.0 * EPSILON;
+
+// The direction `rd` bends to going through a surface with normal `n` facing
+// it, where `eta` is the ratio of the indices of refraction, or nothing if
+// it is reflected instead.
+fn refract(rd: Vec3, n: Vec3, eta: f32) -> Option<Vec3> {
+    let cos = -n.dot(rd);
+    let k = 1.0 - eta * eta * (1.0 - cos * cos);
+    (k >= 0.0).then(|| rd * eta + n * (eta * cos - k.sqrt()))
+}
+
+// Schlick's approximation of how much of the light a dielectric reflects.
+fn fresnel(cos: f32, eta: f32) -> f32 {
+    let r0 = ((1.0 - eta) / (1.0 + eta)).powi(2);
+    r0 + (1.0 - r0) * (1.0 - cos.clamp(0.0, 1.0)).powi(5)
+}
+
+// A direction that only depends on `p` and `i`.
+fn direction(p: Vec3, i: u32) -> Vec3 {
+    let (x, y) = (p.x + p.z, p.y - p.z);
+    let z = 1.0 - 2.0 * random(x, y, 3 * i);
+    let phi = 2.0 * std::f32::consts::PI * random(x, y, 3 * i + 1);
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    v3(r * phi.cos(), r * phi.sin(), z)
+}
+
+// A point in the unit ball that only depends on `p` and `i`.
+fn ball(p: Vec3, i: u32) -> Vec3 {
+    let (x, y) = (p.x + p.z, p.y - p.z);
+    direction(p, i) * random(x, y, 3 * i + 2).cbrt()
+}
+
+// Whether two neighbouring rays hit different things.
+fn silhouette(a: f32, b: f32) -> bool {
+    a.is_finite() != b.is_finite() || (a - b).abs() > 0.05 * a.min(b)
+}
+
+// Everything needed to shade a pixel of a `width` by `height` image.
+pub struct View<'a> {
+    pub sdf: &'a Sdf,
+    pub camera_pos: Vec3,
+    pub lights: &'a [Light],
+    pub background: Lum,
+    pub width: u32,
+    pub height: u32,
+    pub sampling: Sampling,
+    pub materials: Materials,
+    pub bounces: Bounces,
+    // The image of the environment light, if there is one.
+    pub environment: Option<Environment>,
+    pub atmosphere: Option<Atmosphere>,
+    // Path traced instead of lit directly, if set.
+    pub path: Option<PathTracer>,
+    // A box the surface is inside, if it is known.
+    pub bound: Option<Bound>,
+    // Which object is where, if the scene tags them.
+    pub ids: Option<Ids>,
+    cam_mat: Mat3,
+}
+
+impl<'a> View<'a> {
+    pub fn new(
+        sdf: &'a Sdf,
+        camera_pos: Vec3,
+        look_at: Vec3,
+        lights: &'a [Light],
+        background: Lum,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        View {
+            sdf,
+            camera_pos,
+            lights,
+            background,
+            width,
+            height,
+            sampling: Sampling::default(),
+            materials: Materials::default(),
+            bounces: Bounces::default(),
+            environment: None,
+            atmosphere: None,
+            path: None,
+            bound: None,
+            ids: None,
+            cam_mat: camera(camera_pos, look_at),
+        }
+    }
+
+    // The origin and direction of the ray through the point (x, y) of the
+    // image.
+    pub fn camera_ray(&self, x: f32, y: f32) -> (Vec3, Vec3) {
+        let (width, height) = (self.width as f32, self.height as f32);
+        let uv = v3(
+            (2.0 * x - width) / height,
+            (2.0 * (height - y) - height) / height,
+            0.0,
+        );
+        (
+            self.camera_pos,
+            self.cam_mat * v3(uv.x, uv.y, 1.0).normalize(),
+        )
+    }
+
+    // `intersect` within the bound, if there is one, so rays that miss it
+    // miss everything and the others start where they go in.
+    pub(crate) fn hit(
+        &self,
+        field: &(impl Fn(Vec3) -> f32 + ?Sized),
+        ro: Vec3,
+        rd: Vec3,
+    ) -> Option<f32> {
+        match self.bound {
+            Some(bound) => {
+                let (near, far) = bound.ray(ro, rd)?;
+                intersect_between(field, ro, rd, near, far.min(MAX_DIST))
+            }
+            None => intersect(field, ro, rd),
+        }
+    }
+
+    // The object seen through the point (x, y) of the image, if there is one
+    // and it's tagged.
+    pub fn pick(&self, x: f32, y: f32) -> Option<u32> {
+        let ids = self.ids.as_ref()?;
+        let (ro, rd) = self.camera_ray(x, y);
+        let t = self.hit(self.sdf, ro, rd)?;
+        ids(ro + rd * t)
+    }
+
+    // A single ray through the point (x, y) of the image.
+    fn ray(&self, x: f32, y: f32) -> (Lum, f32) {
+        let (ro, rd) = self.camera_ray(x, y);
+        match &self.path {
+            Some(path) => {
+                let (lum, t) = path.trace(self, rd, x, y);
+                (self.fog(ro, rd, t, lum), t)
+            }
+            None => self.shade(ro, rd, 0, 1.0, false),
+        }
+    }
+
+    // `lum` seen through the fog from `t` away, if there is fog.
+    fn fog(&self, ro: Vec3, rd: Vec3, t: f32, lum: Lum) -> Lum {
+        match &self.atmosphere {
+            Some(atmosphere) => atmosphere.apply(self.sdf, self.lights, ro, rd, t, lum),
+            None => lum,
+        }
+    }
+
+    // The light along a ray and how far it went, following its reflections
+    // and refractions. Inside an object the field is negated so the ray
+    // finds where it leaves. `weight` is how much the ray adds to the pixel.
+    fn shade(&self, ro: Vec3, rd: Vec3, depth: u32, weight: f32, inside: bool) -> (Lum, f32) {
+        let sign = if inside { -1.0 } else { 1.0 };
+        let field = |p: Vec3| sign * (self.sdf)(p);
+        let Some(t) = self.hit(&field, ro, rd) else {
+            let escaped = self.escaped(rd, depth);
+            return (self.fog(ro, rd, f32::INFINITY, escaped), f32::INFINITY);
+        };
+        let p = ro + rd * t;
+        // Facing the ray, inside or out.
+        let n = normal(p, &field);
+        let (material, brightness) = self.materials.at(p);
+        let bounce = |rd: Vec3, w: f32, inside: bool| {
+            if depth >= self.bounces.max_depth || weight * w < self.bounces.min_weight {
+                return 0.0;
+            }
+            let ro = if inside == (sign < 0.0) {
+                p + n * OFFSET
+            } else {
+                p - n * OFFSET
+            };
+            w * self.shade(ro, rd, depth + 1, weight * w, inside).0
+        };
+        let mut col = 0.0;
+        if !inside {
+            col += material.opaque()
+                * material.diffuse
+                * brightness
+                * lighting(self.sdf, p, n, rd, self.lights, self.environment.as_ref());
+            if material.reflect > 0.0 {
+                col += self.glossy(p, n, rd, material.roughness, |r| {
+                    bounce(r, material.reflect, false)
+                });
+            }
+        }
+        // The light leaving a transparent object is refracted too.
+        if inside || material.transparency > 0.0 {
+            let share = if inside { 1.0 } else { material.transparency };
+            let eta = if inside {
+                material.ior
+            } else {
+                1.0 / material.ior
+            };
+            let mirror = reflect(rd, n);
+            col += match refract(rd, n, eta) {
+                Some(r) => {
+                    let f = fresnel(-n.dot(rd), eta);
+                    bounce(r, share * (1.0 - f), !inside) + bounce(mirror, share * f, inside)
+                }
+                None => bounce(mirror, share, inside),
+            };
+        }
+        // There's no fog inside things.
+        if inside {
+            (col, t)
+        } else {
+            (self.fog(ro, rd, t, col), t)
+        }
+    }
+
+    // What a ray that hits nothing sees: the background from the camera and
+    // the environment, if there is one, after a bounce.
+    pub fn escaped(&self, rd: Vec3, depth: u32) -> Lum {
+        let light = self
+            .lights
+            .iter()
+            .find(|light| light.kind == LightKind::Environment);
+        match (light, &self.environment) {
+            (Some(light), Some(environment)) if depth > 0 => {
+                light.intensity * environment.lookup(rd)
+            }
+            _ => self.background,
+        }
+    }
+
+    // The average of `trace` over rays spread around the mirror direction by
+    // `roughness`.
+    fn glossy(
+        &self,
+        p: Vec3,
+        n: Vec3,
+        rd: Vec3,
+        roughness: f32,
+        trace: impl Fn(Vec3) -> Lum,
+    ) -> Lum {
+        let mirror = reflect(rd, n);
+        if roughness <= 0.0 {
+            return trace(mirror);
+        }
+        let samples = self.bounces.glossy_samples.max(1);
+        let sum: Lum = (0..samples)
+            .map(|i| {
+                let r = (mirror + ball(p, i) * roughness).normalize();
+                // Kept on the outside of the surface.
+                trace(if r.dot(n) < 0.0 { reflect(r, n) } else { r })
+            })
+            .sum();
+        sum / samples as f32
+    }
+
+    // The luminosity of pixel (x, y) from `aa * aa` rays placed and weighted
+    // by `sampling`. The coordinates needn't be whole, e.g. the center of a
+    // block of pixels.
+    pub fn pixel(&self, x: f32, y: f32, anti_aliasing: u32) -> Lum {
+        let rays: Vec<(Lum, f32)> = self
+            .sampling
+            .samples(x, y, anti_aliasing)
+            .into_iter()
+            .map(|(dx, dy, w)| (self.ray(x + dx, y + dy).0, w))
+            .collect();
+        let weight: f32 = rays.iter().map(|(_, w)| w).sum();
+        let total: f32 = rays.iter().map(|(_, w)| w.abs()).sum();
+        // With only a few rays the negative lobes of the Mitchell filter can
+        // cancel most of the weight, or all of it, and dividing by what's left
+        // blows the pixel up or flips it. The plain mean is safer then.
+        if weight < 0.5 * total {
+            return rays.iter().map(|(col, _)| col).sum::<Lum>() / rays.len() as f32;
+        }
+        rays.iter().map(|(col, w)| w * col).sum::<Lum>() / weight
+    }
+
+    // The pixels of the `w` by `h` region with its top left corner at (x0,
+    // y0). With adaptive sampling every pixel first gets one ray, and the
+    // ones that differ from a neighbour get the rest.
+    pub fn region(&self, x0: u32, y0: u32, w: u32, h: u32, anti_aliasing: u32) -> Vec<Lum> {
+        let Some(threshold) = self.sampling.adaptive.filter(|_| anti_aliasing > 1) else {
+            return (0..w * h)
+                .into_par_iter()
+                .map(|i| self.pixel((x0 + i % w) as f32, (y0 + i / w) as f32, anti_aliasing))
+                .collect();
+        };
+        // The region and a border of one pixel around it.
+        let (bw, bh) = (w + 2, h + 2);
+        let first: Vec<(Lum, f32)> = (0..bw * bh)
+            .into_par_iter()
+            .map(|i| self.ray((x0 + i % bw) as f32 - 1.0, (y0 + i / bw) as f32 - 1.0))
+            .collect();
+        (0..w * h)
+            .into_par_iter()
+            .map(|i| {
+                let (x, y) = (i % w + 1, i / w + 1);
+                let (col, dist) = first[(y * bw + x) as usize];
+                let differs = (y - 1..=y + 1)
+                    .flat_map(|y| (x - 1..=x + 1).map(move |x| (y * bw + x) as usize))
+                    .map(|j| first[j])
+                    .any(|(c, d)| (c - col).abs() > threshold || silhouette(dist, d));
+                if differs {
+                    self.pixel((x0 + x - 1) as f32, (y0 + y - 1) as f32, anti_aliasing)
+                } else {
+                    col
+                }
+            })
+            .collect()
+    }
+}
+
+// The linear luminance of every pixel, denoised if it's path traced.
+pub fn render_linear(view: &View, anti_aliasing: u32) -> Vec<Lum> {
+    let lum = view.region(0, 0, view.width, view.height, anti_aliasing);
+    if view.path.is_some_and(|path| path.denoise) {
+        return denoise(view, &lum);
+    }
+    lum
 }
 
+// The object seen through the center of every pixel, an image of ids to go
+// with the picture for picking or masking them afterwards.
+pub fn render_ids(view: &View) -> Vec<Option<u32>> {
+    (0..view.width * view.height)
+        .into_par_iter()
+        .map(|i| view.pick((i % view.width) as f32, (i / view.width) as f32))
+        .collect()
+}
+
+pub fn render_view(view: &View, anti_aliasing: u32) -> Vec<u8> {
+    ToneMap::default().to_u8(&render_linear(view, anti_aliasing))
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn render(
     sdf: &Sdf,
     camera_pos: Vec3,
@@ -104,39 +544,12 @@ pub fn render(
     height: u32,
     anti_aliasing: u32,
 ) -> Vec<u8> {
-    let cam_mat = camera(camera_pos, look_at);
-    let mut img_data: Vec<u8> = Vec::with_capacity((width * height) as usize);
-    for y in 0..height {
-        let scanline: Vec<u8> = (0..width)
-            .into_par_iter()
-            .map(|x| {
-                let mut col = 0.0;
-                for m in 0..anti_aliasing {
-                    for n in 0..anti_aliasing {
-                        let ox = (m as f32) / (anti_aliasing as f32) - 0.5;
-                        let oy = (n as f32) / (anti_aliasing as f32) - 0.5;
-                        let uv = v3(
-                            (2.0 * ((x as f32) + ox) - width as f32) / (height as f32),
-                            (2.0 * ((height as f32 - y as f32) + oy) - height as f32)
-                                / (height as f32),
-                            0.0,
-                        );
-                        let rd = cam_mat * v3(uv.x, uv.y, 1.0).normalize();
-                        col += march(sdf, camera_pos, rd, lights, background);
-                    }
-                }
-                col /= (anti_aliasing * anti_aliasing) as f32;
-                (col * 255.0) as u8
-            })
-            .collect();
-        for col in scanline {
-            img_data.push(col);
-        }
-    }
-    img_data
+    let view = View::new(sdf, camera_pos, look_at, lights, background, width, height);
+    render_view(&view, anti_aliasing)
 }
 
 // XXX Fix this function to return image data
+#[allow(clippy::too_many_arguments)]
 pub fn render_stipple(
     sdf: &Sdf,
     camera_pos: Vec3,
@@ -156,8 +569,8 @@ pub fn render_stipple(
                 let ox = (m as f32) / (anti_aliasing as f32) - 0.5;
                 let oy = (n as f32) / (anti_aliasing as f32) - 0.5;
                 let uv = Vec3::new(
-                    (p.x as f32) / (height as f32),
-                    (height as f32 - p.y as f32) / (height as f32), // flip y
+                    p.x / (height as f32),
+                    (height as f32 - p.y) / (height as f32), // flip y
                     0.0,
                 );
                 let rd = cam_mat
@@ -167,7 +580,7 @@ pub fn render_stipple(
                         1.0,
                     )
                     .normalize();
-                col += march(sdf, camera_pos, rd, lights, background);
+                col += march(sdf, camera_pos, rd, lights, background).0;
             }
         }
         col /= (anti_aliasing * anti_aliasing) as f32;
@@ -175,3 +588,30 @@ pub fn render_stipple(
     });
     img_data.collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ZERO3;
+    use crate::sampling::Filter;
+
+    #[test]
+    fn mitchell_with_few_rays() {
+        let sdf: Sdf = Box::new(|p| p.length() - 5.0);
+        let lights = [Light::new(v3(0.0, 0.0, -50.0), 1.0)];
+        let mut view = View::new(&sdf, v3(0.0, 0.0, -20.0), ZERO3, &lights, 0.75, 70, 40);
+        view.sampling.filter = Filter::Mitchell;
+        for aa in [1, 2] {
+            // No pixel blows up or goes negative.
+            for lum in render_linear(&view, aa) {
+                assert!(
+                    (0.0..=1.0).contains(&lum),
+                    "{} with {}x{} rays",
+                    lum,
+                    aa,
+                    aa
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+// What a surface does with the light that reaches it. Of the light, `reflect`
+// is mirrored, blurred by `roughness`, `transparency` goes through the surface
+// bending by `ior` (apart from what the Fresnel term reflects), and the rest is
+// lit directly, scaled by `diffuse`. A scene's materials each apply where
+// their `region`, a DSL expression, is negative, or everywhere without one,
+// and the first that applies wins. A DSL program can also set the variables
+// `col`, how bright the surface is, and `mat`, which of the materials it's
+// made of, as functions of the point on it, e.g. `col=.5+.5*mod(floor(x),2)`
+// for stripes.
+use crate::core::{Lum, Sdf};
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Material {
+    // What objects call it, see `arrow::graph`.
+    pub name: Option<String>,
+    pub region: Option<String>,
+    pub diffuse: f32,
+    pub reflect: f32,
+    pub roughness: f32,
+    pub transparency: f32,
+    pub ior: f32,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            name: None,
+            region: None,
+            diffuse: 1.0,
+            reflect: 0.0,
+            roughness: 0.0,
+            transparency: 0.0,
+            ior: 1.5,
+        }
+    }
+}
+
+impl Material {
+    pub fn mirror(reflect: f32) -> Self {
+        Material {
+            reflect,
+            ..Default::default()
+        }
+    }
+
+    pub fn glass(ior: f32) -> Self {
+        Material {
+            transparency: 1.0,
+            ior,
+            ..Default::default()
+        }
+    }
+
+    // The fraction of light that is lit directly.
+    pub fn opaque(&self) -> f32 {
+        (1.0 - self.reflect - self.transparency).max(0.0)
+    }
+}
+
+// How far reflected and refracted rays are followed. A ray stops after
+// `max_depth` bounces or once it adds less than `min_weight` to the pixel.
+// Rough reflections average `glossy_samples` rays.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bounces {
+    pub max_depth: u32,
+    pub min_weight: f32,
+    pub glossy_samples: u32,
+}
+
+impl Default for Bounces {
+    fn default() -> Self {
+        Bounces {
+            max_depth: 4,
+            min_weight: 0.01,
+            glossy_samples: 4,
+        }
+    }
+}
+
+// The `col` and `mat` a program sets at a point, if it does.
+pub type Surface = Box<dyn Fn(Vec3) -> (Option<Lum>, Option<f32>) + Sync>;
+
+// The materials of a scene with their regions evaluated.
+#[derive(Default)]
+pub struct Materials {
+    materials: Vec<(Material, Option<Sdf>)>,
+    default: Material,
+    surface: Option<Surface>,
+}
+
+impl Materials {
+    pub fn new(materials: Vec<(Material, Option<Sdf>)>) -> Self {
+        Materials {
+            materials,
+            default: Material::default(),
+            surface: None,
+        }
+    }
+
+    pub fn with_surface(self, surface: Surface) -> Self {
+        Materials {
+            surface: Some(surface),
+            ..self
+        }
+    }
+
+    // The same material everywhere.
+    pub fn uniform(material: Material) -> Self {
+        Materials::new(vec![(material, None)])
+    }
+
+    // The material at `p` and how bright it is there.
+    pub fn at(&self, p: Vec3) -> (&Material, Lum) {
+        let (col, mat) = self
+            .surface
+            .as_ref()
+            .map_or((None, None), |surface| surface(p));
+        let material = match mat {
+            Some(mat) => self
+                .materials
+                .get(mat.max(0.0) as usize)
+                .map_or(&self.default, |(material, _)| material),
+            None => self
+                .materials
+                .iter()
+                .find(|(_, region)| region.as_ref().is_none_or(|region| region(p) < 0.0))
+                .map_or(&self.default, |(material, _)| material),
+        };
+        (material, col.unwrap_or(1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{v3, Light, ZERO3};
+    use crate::march::View;
+
+    #[test]
+    fn regions() {
+        let floor: Sdf = Box::new(|p| p.y + 1.0);
+        let materials = Materials::new(vec![
+            (Material::mirror(0.5), Some(floor)),
+            (Material::glass(1.5), None),
+        ]);
+        assert_eq!(materials.at(v3(0.0, -2.0, 0.0)).0.reflect, 0.5);
+        assert_eq!(materials.at(v3(0.0, 2.0, 0.0)).0.transparency, 1.0);
+        assert_eq!(
+            Materials::default().at(v3(0.0, 2.0, 0.0)),
+            (&Material::default(), 1.0)
+        );
+        // `mat` overrides the regions and `col` is the brightness.
+        let materials = materials.with_surface(Box::new(|p| (Some(0.5), Some(p.x))));
+        assert_eq!(materials.at(v3(1.0, -2.0, 0.0)).0.transparency, 1.0);
+        assert_eq!(materials.at(v3(0.0, 2.0, 0.0)).0.reflect, 0.5);
+        assert_eq!(materials.at(v3(5.0, 2.0, 0.0)).0, &Material::default());
+        assert_eq!(materials.at(v3(0.0, 2.0, 0.0)).1, 0.5);
+        assert_eq!(Material::default().opaque(), 1.0);
+    }
+
+    #[test]
+    fn bounces() {
+        let lights = [Light::new(v3(0.0, 0.0, -50.0), 1.0)];
+        let ball: Sdf = Box::new(|p| p.length() - 5.0);
+        let mut view = View::new(&ball, v3(0.0, 0.0, -20.0), ZERO3, &lights, 0.75, 16, 12);
+        let lit = view.pixel(8.0, 6.0, 1);
+        // A perfect mirror ball in front of the camera reflects the empty
+        // space behind it, half a mirror is half lit.
+        view.materials = Materials::uniform(Material::mirror(1.0));
+        assert_eq!(view.pixel(8.0, 6.0, 1), 0.75);
+        view.materials = Materials::uniform(Material::mirror(0.5));
+        assert!((view.pixel(8.0, 6.0, 1) - (0.5 * lit + 0.5 * 0.75)).abs() < 1e-5);
+        // Glass without refraction lets everything through, real glass
+        // reflects a little, and all of it ends up in the background.
+        view.materials = Materials::uniform(Material::glass(1.0));
+        assert!((view.pixel(8.0, 6.0, 1) - 0.75).abs() < 1e-5);
+        view.materials = Materials::uniform(Material::glass(1.5));
+        assert!((view.pixel(8.0, 6.0, 1) - 0.75).abs() < 0.02);
+        // Without bounces glass is black.
+        view.bounces.max_depth = 0;
+        assert_eq!(view.pixel(8.0, 6.0, 1), 0.0);
+    }
+}
@@ -0,0 +1,998 @@
+// A distance field as a tree of primitives, booleans and changes to the
+// domain, unlike an `Sdf` it can be looked at, compared and turned into a DSL
+// program, e.g.
+//
+//     SdfNode::sphere(2.0)
+//         .translate(v3(0.0, 1.0, 0.0))
+//         .smooth_union(SdfNode::cuboid(v(1.0)), 0.5)
+//         .repeat(Axis::X, 6.0)
+//
+// The tree serializes, so a scene built in one program can be saved and
+// rendered in another. `perturb` takes a DSL expression rather than a closure
+// for that reason.
+//
+// `to_statement` writes each change to the domain as an assignment to fresh
+// `x1`, `y1`, `z1` and so on, and each node as an assignment to a `d1`, `d2`
+// and so on. `from_statement` reads programs written that way back, though a
+// `round` inside changes to the domain comes back inside all of them, which
+// is the same field.
+use crate::ast::{BinOp, Expr, FunctionName, Statement};
+use crate::core::{difference, intersects, perturb, unions, v3, Sdf, I, ZERO3};
+use crate::eval::interpret;
+use crate::functions::smooth_min;
+use crate::pratt::parse;
+use crate::sdf::{sd_box, sd_capsule, sd_inf_cylinder, sd_plane, sd_sphere, sd_torus};
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    const ALL: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    // The other two axes, in the order a rotation about this one turns the
+    // first into the second.
+    fn plane(self) -> (usize, usize) {
+        let i = self.index();
+        ((i + 1) % 3, (i + 2) % 3)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SdfNode {
+    Sphere { radius: f32 },
+    // Half its size along each axis, like `bx3`.
+    Cuboid { size: Vec3 },
+    // Around the z axis, like `don`.
+    Torus { major: f32, minor: f32 },
+    // Infinite along the y axis.
+    Cylinder { radius: f32 },
+    // From the origin up the y axis.
+    Capsule { height: f32, radius: f32 },
+    Plane { normal: Vec3, offset: f32 },
+    Union(Vec<SdfNode>),
+    Intersection(Vec<SdfNode>),
+    Difference(Box<SdfNode>, Box<SdfNode>),
+    // Blended over `k` like `rU`.
+    SmoothUnion(Vec<SdfNode>, f32),
+    SmoothIntersection(Vec<SdfNode>, f32),
+    SmoothDifference(Box<SdfNode>, Box<SdfNode>, f32),
+    Translate(Box<SdfNode>, Vec3),
+    // By an angle in radians, counterclockwise looking down the axis.
+    Rotate(Box<SdfNode>, Axis, f32),
+    Scale(Box<SdfNode>, f32),
+    Round(Box<SdfNode>, f32),
+    Mirror(Box<SdfNode>, Axis),
+    Repeat(Box<SdfNode>, Axis, f32),
+    // Adds an expression in x, y and z to the distance.
+    Perturb(Box<SdfNode>, Expr),
+}
+
+use SdfNode::*;
+
+impl SdfNode {
+    pub fn sphere(radius: f32) -> Self {
+        Sphere { radius }
+    }
+
+    pub fn cuboid(size: Vec3) -> Self {
+        Cuboid { size }
+    }
+
+    pub fn torus(major: f32, minor: f32) -> Self {
+        Torus { major, minor }
+    }
+
+    pub fn cylinder(radius: f32) -> Self {
+        Cylinder { radius }
+    }
+
+    pub fn capsule(height: f32, radius: f32) -> Self {
+        Capsule { height, radius }
+    }
+
+    pub fn plane(normal: Vec3, offset: f32) -> Self {
+        Plane {
+            normal: normal.normalize(),
+            offset,
+        }
+    }
+
+    pub fn unions(nodes: Vec<SdfNode>) -> Self {
+        Union(nodes)
+    }
+
+    pub fn union(self, other: SdfNode) -> Self {
+        match self {
+            Union(mut nodes) => {
+                nodes.push(other);
+                Union(nodes)
+            }
+            node => Union(vec![node, other]),
+        }
+    }
+
+    pub fn intersect(self, other: SdfNode) -> Self {
+        match self {
+            Intersection(mut nodes) => {
+                nodes.push(other);
+                Intersection(nodes)
+            }
+            node => Intersection(vec![node, other]),
+        }
+    }
+
+    pub fn difference(self, other: SdfNode) -> Self {
+        Difference(Box::new(self), Box::new(other))
+    }
+
+    pub fn smooth_union(self, other: SdfNode, k: f32) -> Self {
+        match self {
+            SmoothUnion(mut nodes, j) if j == k => {
+                nodes.push(other);
+                SmoothUnion(nodes, k)
+            }
+            node => SmoothUnion(vec![node, other], k),
+        }
+    }
+
+    pub fn smooth_intersect(self, other: SdfNode, k: f32) -> Self {
+        match self {
+            SmoothIntersection(mut nodes, j) if j == k => {
+                nodes.push(other);
+                SmoothIntersection(nodes, k)
+            }
+            node => SmoothIntersection(vec![node, other], k),
+        }
+    }
+
+    pub fn smooth_difference(self, other: SdfNode, k: f32) -> Self {
+        SmoothDifference(Box::new(self), Box::new(other), k)
+    }
+
+    pub fn translate(self, offset: Vec3) -> Self {
+        Translate(Box::new(self), offset)
+    }
+
+    pub fn rotate(self, axis: Axis, angle: f32) -> Self {
+        Rotate(Box::new(self), axis, angle)
+    }
+
+    pub fn scale(self, s: f32) -> Self {
+        Scale(Box::new(self), s)
+    }
+
+    pub fn round(self, radius: f32) -> Self {
+        Round(Box::new(self), radius)
+    }
+
+    pub fn mirror(self, axis: Axis) -> Self {
+        Mirror(Box::new(self), axis)
+    }
+
+    pub fn repeat(self, axis: Axis, spacing: f32) -> Self {
+        Repeat(Box::new(self), axis, spacing)
+    }
+
+    // `expr` is DSL, e.g. `.perturb("0.1*sin(4*x)")`, panics if it isn't an
+    // expression.
+    pub fn perturb(self, expr: &str) -> Self {
+        match parse(&mut &*expr) {
+            Statement::Sequence(statements) => match &statements[..] {
+                [Statement::Return(e)] => Perturb(Box::new(self), *e.clone()),
+                _ => panic!("perturb expects an expression, not {}", expr),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn sdf(&self) -> Sdf {
+        let all = |nodes: &[SdfNode]| nodes.iter().map(|n| n.sdf()).collect::<Vec<_>>();
+        match self {
+            &Sphere { radius } => sd_sphere(radius, ZERO3, I),
+            &Cuboid { size } => sd_box(size, ZERO3, I),
+            &Torus { major, minor } => sd_torus(major, minor, ZERO3, I),
+            &Cylinder { radius } => sd_inf_cylinder(radius, ZERO3, I),
+            &Capsule { height, radius } => {
+                sd_capsule(radius, ZERO3, ZERO3, v3(0.0, height, 0.0), I)
+            }
+            &Plane { normal, offset } => sd_plane(normal, offset, ZERO3, I),
+            Union(nodes) => unions(all(nodes)),
+            Intersection(nodes) => intersects(all(nodes)),
+            Difference(a, b) => difference(a.sdf(), b.sdf()),
+            SmoothUnion(nodes, k) => {
+                let (sdfs, k) = (all(nodes), *k);
+                Box::new(move |p| {
+                    sdfs.iter()
+                        .map(|sdf| sdf(p))
+                        .reduce(|a, b| smooth_min(a, b, k))
+                        .unwrap()
+                })
+            }
+            SmoothIntersection(nodes, k) => {
+                let (sdfs, k) = (all(nodes), *k);
+                Box::new(move |p| {
+                    -sdfs
+                        .iter()
+                        .map(|sdf| -sdf(p))
+                        .reduce(|a, b| smooth_min(a, b, k))
+                        .unwrap()
+                })
+            }
+            SmoothDifference(a, b, k) => {
+                let (a, b, k) = (a.sdf(), b.sdf(), *k);
+                Box::new(move |p| -smooth_min(-a(p), b(p), k))
+            }
+            Translate(node, offset) => {
+                let (sdf, offset) = (node.sdf(), *offset);
+                Box::new(move |p| sdf(p - offset))
+            }
+            Rotate(node, axis, angle) => {
+                let (sdf, (u, v)) = (node.sdf(), axis.plane());
+                let (s, c) = angle.sin_cos();
+                Box::new(move |mut p| {
+                    let (pu, pv) = (p[u], p[v]);
+                    p[u] = c * pu + s * pv;
+                    p[v] = c * pv - s * pu;
+                    sdf(p)
+                })
+            }
+            Scale(node, s) => {
+                let (sdf, s) = (node.sdf(), *s);
+                Box::new(move |p| s * sdf(p / s))
+            }
+            Round(node, radius) => crate::core::round(node.sdf(), *radius),
+            Mirror(node, axis) => {
+                let (sdf, i) = (node.sdf(), axis.index());
+                Box::new(move |mut p| {
+                    p[i] = p[i].abs();
+                    sdf(p)
+                })
+            }
+            Repeat(node, axis, spacing) => {
+                let (sdf, i, s) = (node.sdf(), axis.index(), *spacing);
+                Box::new(move |mut p| {
+                    p[i] -= s * (p[i] / s).round();
+                    sdf(p)
+                })
+            }
+            Perturb(node, e) => {
+                let program = interpret(&Statement::Return(Box::new(e.clone())), &[]);
+                perturb(node.sdf(), program)
+            }
+        }
+    }
+
+    pub fn to_statement(&self) -> Statement {
+        let mut writer = Writer::default();
+        let p = ["x", "y", "z"].map(String::from);
+        let d = writer.node(self, &p);
+        writer.statements.push(Statement::Return(Box::new(var(&d))));
+        Statement::Sequence(writer.statements)
+    }
+
+    // The tree of a program written by `to_statement`, or None if it isn't
+    // one.
+    pub fn from_statement(statement: &Statement) -> Option<Self> {
+        let mut reader = Reader::default();
+        reader.statement(statement)?;
+        reader.result
+    }
+}
+
+fn num(x: f32) -> Expr {
+    Expr::Number(x)
+}
+
+fn var(name: &str) -> Expr {
+    Expr::Variable(name.to_string())
+}
+
+fn call(name: FunctionName, args: Vec<Expr>) -> Expr {
+    Expr::Function { name, args }
+}
+
+fn neg(e: Expr) -> Expr {
+    Expr::Negate(Box::new(e))
+}
+
+fn add(a: Expr, b: Expr) -> Expr {
+    Expr::BinaryOp(BinOp::Add(Box::new(a), Box::new(b)))
+}
+
+fn sub(a: Expr, b: Expr) -> Expr {
+    Expr::BinaryOp(BinOp::Sub(Box::new(a), Box::new(b)))
+}
+
+fn mul(a: Expr, b: Expr) -> Expr {
+    Expr::BinaryOp(BinOp::Mul(Box::new(a), Box::new(b)))
+}
+
+fn div(a: Expr, b: Expr) -> Expr {
+    Expr::BinaryOp(BinOp::Div(Box::new(a), Box::new(b)))
+}
+
+// `e` with its variables renamed by `f`, or None if `f` gives up on one or
+// `e` assigns to one.
+fn rename(e: &Expr, f: &dyn Fn(&str) -> Option<String>) -> Option<Expr> {
+    let r = |e: &Expr| rename(e, f).map(Box::new);
+    let op = |op: &BinOp| {
+        use BinOp::*;
+        Some(match op {
+            Add(a, b) => Add(r(a)?, r(b)?),
+            Sub(a, b) => Sub(r(a)?, r(b)?),
+            Mul(a, b) => Mul(r(a)?, r(b)?),
+            Div(a, b) => Div(r(a)?, r(b)?),
+            Eq(a, b) => Eq(r(a)?, r(b)?),
+            NotEq(a, b) => NotEq(r(a)?, r(b)?),
+            Greater(a, b) => Greater(r(a)?, r(b)?),
+            GreaterEq(a, b) => GreaterEq(r(a)?, r(b)?),
+            Less(a, b) => Less(r(a)?, r(b)?),
+            LessEq(a, b) => LessEq(r(a)?, r(b)?),
+            And(a, b) => And(r(a)?, r(b)?),
+            Or(a, b) => Or(r(a)?, r(b)?),
+            Pow(a, b) => Pow(r(a)?, r(b)?),
+        })
+    };
+    Some(match e {
+        Expr::Number(x) => Expr::Number(*x),
+        Expr::Variable(v) => Expr::Variable(f(v)?),
+        Expr::Negate(e) => Expr::Negate(r(e)?),
+        Expr::BinaryOp(o) => Expr::BinaryOp(op(o)?),
+        Expr::Function { name, args } => Expr::Function {
+            name: name.clone(),
+            args: args.iter().map(|a| rename(a, f)).collect::<Option<_>>()?,
+        },
+        Expr::TernaryOp(a, b, c) => Expr::TernaryOp(r(a)?, r(b)?, r(c)?),
+        Expr::Assign(_) => return None,
+    })
+}
+
+#[derive(Default)]
+struct Writer {
+    statements: Vec<Statement>,
+    coords: usize,
+    distances: usize,
+}
+
+impl Writer {
+    fn domain(&mut self, rhs: [Expr; 3]) -> [String; 3] {
+        self.coords += 1;
+        let vars = ["x", "y", "z"].map(|c| format!("{}{}", c, self.coords));
+        self.statements.push(Statement::AssignFromArray {
+            vars: vars.to_vec(),
+            rhs: rhs.to_vec(),
+        });
+        vars
+    }
+
+    fn distance(&mut self, rhs: Expr) -> String {
+        self.distances += 1;
+        let d = format!("d{}", self.distances);
+        self.statements.push(Statement::Assign {
+            var: d.clone(),
+            rhs: Box::new(rhs),
+        });
+        d
+    }
+
+    // Writes `node` at the point `p` and returns the variable its distance is
+    // in.
+    fn node(&mut self, node: &SdfNode, p: &[String; 3]) -> String {
+        let [x, y, z] = p.clone().map(|c| var(&c));
+        let mut all = |nodes: &[SdfNode]| -> Vec<Expr> {
+            nodes.iter().map(|n| var(&self.node(n, p))).collect()
+        };
+        let rhs = match node {
+            &Sphere { radius } => sub(call(FunctionName::Length, vec![x, y, z]), num(radius)),
+            &Cuboid { size } => call(
+                FunctionName::Box3,
+                vec![x, y, z, num(size.x), num(size.y), num(size.z)],
+            ),
+            &Torus { major, minor } => {
+                call(FunctionName::Torus, vec![x, y, z, num(major), num(minor)])
+            }
+            &Cylinder { radius } => sub(call(FunctionName::Length, vec![x, z]), num(radius)),
+            &Capsule { height, radius } => {
+                let clamped = call(FunctionName::Clamp, vec![y.clone(), num(0.0), num(height)]);
+                let length = call(FunctionName::Length, vec![x, sub(y, clamped), z]);
+                sub(length, num(radius))
+            }
+            &Plane { normal, offset } => {
+                let n = [normal.x, normal.y, normal.z].map(num);
+                let dot = call(FunctionName::Dot, [[x, y, z], n].concat());
+                add(dot, num(offset))
+            }
+            Union(nodes) => call(FunctionName::Union, all(nodes)),
+            Intersection(nodes) => call(FunctionName::Intersect, all(nodes)),
+            Difference(a, b) => {
+                let a = var(&self.node(a, p));
+                let b = var(&self.node(b, p));
+                call(FunctionName::Intersect, vec![a, neg(b)])
+            }
+            SmoothUnion(nodes, k) => {
+                let mut args = all(nodes);
+                args.push(num(*k));
+                call(FunctionName::RoundMin, args)
+            }
+            SmoothIntersection(nodes, k) => {
+                let mut args: Vec<Expr> = all(nodes).into_iter().map(neg).collect();
+                args.push(num(*k));
+                neg(call(FunctionName::RoundMin, args))
+            }
+            SmoothDifference(a, b, k) => {
+                let a = var(&self.node(a, p));
+                let b = var(&self.node(b, p));
+                neg(call(FunctionName::RoundMin, vec![neg(a), b, num(*k)]))
+            }
+            Translate(node, offset) => {
+                let p = self.domain([
+                    sub(x, num(offset.x)),
+                    sub(y, num(offset.y)),
+                    sub(z, num(offset.z)),
+                ]);
+                return self.node(node, &p);
+            }
+            Rotate(node, axis, angle) => {
+                let (u, v) = axis.plane();
+                let mut rhs = [x, y, z];
+                let (pu, pv) = (rhs[u].clone(), rhs[v].clone());
+                let c = || call(FunctionName::Cos, vec![num(*angle)]);
+                let s = || call(FunctionName::Sin, vec![num(*angle)]);
+                rhs[u] = add(mul(c(), pu.clone()), mul(s(), pv.clone()));
+                rhs[v] = sub(mul(c(), pv), mul(s(), pu));
+                let p = self.domain(rhs);
+                return self.node(node, &p);
+            }
+            Scale(node, s) => {
+                let p = self.domain([x, y, z].map(|c| div(c, num(*s))));
+                let d = self.node(node, &p);
+                mul(num(*s), var(&d))
+            }
+            Round(node, radius) => sub(var(&self.node(node, p)), num(*radius)),
+            Mirror(node, axis) => {
+                let mut rhs = [x, y, z];
+                let i = axis.index();
+                rhs[i] = call(FunctionName::Abs, vec![rhs[i].clone()]);
+                let p = self.domain(rhs);
+                return self.node(node, &p);
+            }
+            Repeat(node, axis, spacing) => {
+                let mut rhs = [x, y, z];
+                let (i, s) = (axis.index(), *spacing);
+                let cell = call(FunctionName::Round, vec![div(rhs[i].clone(), num(s))]);
+                rhs[i] = sub(rhs[i].clone(), mul(num(s), cell));
+                let p = self.domain(rhs);
+                return self.node(node, &p);
+            }
+            Perturb(node, e) => {
+                let d = var(&self.node(node, p));
+                let coords = |v: &str| {
+                    let i = ["x", "y", "z"].iter().position(|c| *c == v);
+                    Some(i.map_or(v.to_string(), |i| p[i].clone()))
+                };
+                add(d, rename(e, &coords).expect("perturb doesn't assign"))
+            }
+        };
+        self.distance(rhs)
+    }
+}
+
+// A change to the domain, the inside of a domain node.
+#[derive(Debug, Clone, Copy)]
+enum Domain {
+    Translate(Vec3),
+    Rotate(Axis, f32),
+    Scale(f32),
+    Mirror(Axis),
+    Repeat(Axis, f32),
+}
+
+impl Domain {
+    fn wrap(self, node: SdfNode) -> SdfNode {
+        match self {
+            Domain::Translate(offset) => node.translate(offset),
+            Domain::Rotate(axis, angle) => node.rotate(axis, angle),
+            Domain::Scale(s) => node.scale(s),
+            Domain::Mirror(axis) => node.mirror(axis),
+            Domain::Repeat(axis, spacing) => node.repeat(axis, spacing),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    // An axis of a frame.
+    Coord(usize, usize),
+    // A node in a frame.
+    Node(SdfNode, usize),
+}
+
+// Reads a program back by following where its variables come from. Frames
+// are the domains the coordinates are in, 0 is the scene's and the others are
+// a change to the domain of their parent.
+struct Reader {
+    frames: Vec<Option<(usize, Domain)>>,
+    env: HashMap<String, Value>,
+    result: Option<SdfNode>,
+}
+
+impl Default for Reader {
+    fn default() -> Self {
+        let env = ["x", "y", "z"]
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.to_string(), Value::Coord(0, i)))
+            .collect();
+        Reader {
+            frames: vec![None],
+            env,
+            result: None,
+        }
+    }
+}
+
+fn number(e: &Expr) -> Option<f32> {
+    match e {
+        Expr::Number(x) => Some(*x),
+        Expr::Negate(e) => number(e).map(|x| -x),
+        _ => None,
+    }
+}
+
+fn binary(e: &Expr) -> Option<&BinOp> {
+    match e {
+        Expr::BinaryOp(op) => Some(op),
+        _ => None,
+    }
+}
+
+fn function(e: &Expr, name: FunctionName) -> Option<&[Expr]> {
+    match e {
+        Expr::Function { name: n, args } if *n == name => Some(args),
+        _ => None,
+    }
+}
+
+fn negated(e: &Expr) -> Option<&Expr> {
+    match e {
+        Expr::Negate(e) => Some(e),
+        _ => None,
+    }
+}
+
+impl Reader {
+    fn statement(&mut self, statement: &Statement) -> Option<()> {
+        match statement {
+            Statement::Sequence(statements) => {
+                for s in statements {
+                    self.statement(s)?;
+                }
+            }
+            Statement::AssignFromArray { vars, rhs } if vars.len() == 3 && rhs.len() == 3 => {
+                let (frame, domain) = self.domain(rhs)?;
+                self.frames.push(Some((frame, domain)));
+                let new = self.frames.len() - 1;
+                for (i, v) in vars.iter().enumerate() {
+                    self.env.insert(v.clone(), Value::Coord(new, i));
+                }
+            }
+            Statement::Assign { var, rhs } => {
+                let (node, frame) = self.node(rhs)?;
+                self.env.insert(var.clone(), Value::Node(node, frame));
+            }
+            Statement::Return(e) => {
+                let (node, frame) = self.node(e)?;
+                self.result = Some(self.lift(node, frame, 0)?);
+            }
+            Statement::Empty => {}
+            _ => return None,
+        }
+        Some(())
+    }
+
+    fn coord(&self, e: &Expr) -> Option<(usize, usize)> {
+        match e {
+            Expr::Variable(v) => match self.env.get(v)? {
+                Value::Coord(frame, i) => Some((*frame, *i)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    // The frame of a point given as the three coordinates of one.
+    fn point(&self, es: &[Expr]) -> Option<usize> {
+        let frame = self.coord(&es[0])?.0;
+        (es.len() == 3 && (0..3).all(|i| self.coord(&es[i]) == Some((frame, i)))).then_some(frame)
+    }
+
+    fn distance(&self, e: &Expr) -> Option<(SdfNode, usize)> {
+        match e {
+            Expr::Variable(v) => match self.env.get(v)? {
+                Value::Node(node, frame) => Some((node.clone(), *frame)),
+                _ => None,
+            },
+            _ => self.node(e),
+        }
+    }
+
+    fn ancestors(&self, mut frame: usize) -> Vec<usize> {
+        let mut frames = vec![frame];
+        while let Some((parent, _)) = self.frames[frame] {
+            frames.push(parent);
+            frame = parent;
+        }
+        frames
+    }
+
+    // `node` in the frame `to`, an ancestor of its own, if it can be moved
+    // there without changing its distances.
+    fn lift(&self, mut node: SdfNode, mut frame: usize, to: usize) -> Option<SdfNode> {
+        while frame != to {
+            let (parent, domain) = self.frames[frame]?;
+            if let Domain::Scale(_) = domain {
+                return None;
+            }
+            node = domain.wrap(node);
+            frame = parent;
+        }
+        Some(node)
+    }
+
+    // The nodes of `es` lifted to the frame they have in common.
+    fn nodes(&self, es: &[Expr]) -> Option<(Vec<SdfNode>, usize)> {
+        let nodes = es
+            .iter()
+            .map(|e| self.distance(e))
+            .collect::<Option<Vec<_>>>()?;
+        let mut common = self.ancestors(nodes.first()?.1);
+        for (_, frame) in &nodes[1..] {
+            let ancestors = self.ancestors(*frame);
+            common.retain(|f| ancestors.contains(f));
+        }
+        let to = common[0];
+        let nodes = nodes
+            .into_iter()
+            .map(|(node, frame)| self.lift(node, frame, to))
+            .collect::<Option<Vec<_>>>()?;
+        Some((nodes, to))
+    }
+
+    fn domain(&self, rhs: &[Expr]) -> Option<(usize, Domain)> {
+        // [x-a, y-b, z-c]
+        let translate = || {
+            let mut offset = Vec3::ZERO;
+            let mut frame = None;
+            for (i, e) in rhs.iter().enumerate() {
+                let BinOp::Sub(c, o) = binary(e)? else {
+                    return None;
+                };
+                let (f, j) = self.coord(c)?;
+                (j == i && frame.is_none_or(|frame| frame == f)).then_some(())?;
+                frame = Some(f);
+                offset[i] = number(o)?;
+            }
+            Some((frame?, Domain::Translate(offset)))
+        };
+        // [x/s, y/s, z/s]
+        let scale = || {
+            let mut scales = Vec::new();
+            let mut frames = Vec::new();
+            for (i, e) in rhs.iter().enumerate() {
+                let BinOp::Div(c, s) = binary(e)? else {
+                    return None;
+                };
+                let (f, j) = self.coord(c)?;
+                (j == i).then_some(())?;
+                frames.push(f);
+                scales.push(number(s)?);
+            }
+            let same =
+                frames.iter().all(|&f| f == frames[0]) && scales.iter().all(|&s| s == scales[0]);
+            same.then_some((frames[0], Domain::Scale(scales[0])))
+        };
+        // The same point with one axis changed by `f`.
+        let one = |f: &dyn Fn(&Expr, usize, usize) -> Option<Domain>| {
+            let changed: Vec<usize> = (0..3).filter(|&i| self.coord(&rhs[i]).is_none()).collect();
+            let i = *changed.first()?;
+            let frame = self.coord(&rhs[(i + 1) % 3])?.0;
+            let kept = (0..3)
+                .filter(|&j| j != i)
+                .all(|j| self.coord(&rhs[j]) == Some((frame, j)));
+            (changed.len() == 1 && kept).then_some(())?;
+            Some((frame, f(&rhs[i], frame, i)?))
+        };
+        // [abs(x), y, z]
+        let mirror = |e: &Expr, frame: usize, i: usize| {
+            let args = function(e, FunctionName::Abs)?;
+            (self.coord(&args[0])? == (frame, i)).then_some(Domain::Mirror(Axis::ALL[i]))
+        };
+        // [x-s*round(x/s), y, z]
+        let repeat = |e: &Expr, frame: usize, i: usize| {
+            let BinOp::Sub(c, cell) = binary(e)? else {
+                return None;
+            };
+            let BinOp::Mul(s, _) = binary(cell)? else {
+                return None;
+            };
+            (self.coord(c)? == (frame, i)).then_some(Domain::Repeat(Axis::ALL[i], number(s)?))
+        };
+        // [x, cos(a)*y+sin(a)*z, cos(a)*z-sin(a)*y] about x and likewise
+        // about the others.
+        let rotate = || {
+            let i = (0..3).find(|&i| self.coord(&rhs[i]).is_some_and(|(_, j)| j == i))?;
+            let axis = Axis::ALL[i];
+            let frame = self.coord(&rhs[i])?.0;
+            let (u, _) = axis.plane();
+            let BinOp::Add(cu, _) = binary(&rhs[u])? else {
+                return None;
+            };
+            let BinOp::Mul(c, pu) = binary(cu)? else {
+                return None;
+            };
+            (self.coord(pu)? == (frame, u)).then_some(())?;
+            let angle = number(&function(c, FunctionName::Cos)?[0])?;
+            Some((frame, Domain::Rotate(axis, angle)))
+        };
+        translate()
+            .or_else(scale)
+            .or_else(|| one(&mirror))
+            .or_else(|| one(&repeat))
+            .or_else(rotate)
+    }
+
+    fn node(&self, e: &Expr) -> Option<(SdfNode, usize)> {
+        use FunctionName as F;
+        let primitive = |frame: Option<usize>, node: SdfNode| Some((node, frame?));
+        match e {
+            Expr::Variable(_) => self.distance(e),
+            Expr::Function { name, args } => match name {
+                F::Box3 if args.len() == 6 => {
+                    let size = v3(number(&args[3])?, number(&args[4])?, number(&args[5])?);
+                    primitive(self.point(&args[..3]), Cuboid { size })
+                }
+                F::Torus if args.len() == 5 => {
+                    let (major, minor) = (number(&args[3])?, number(&args[4])?);
+                    primitive(self.point(&args[..3]), Torus { major, minor })
+                }
+                F::Union => {
+                    let (nodes, frame) = self.nodes(args)?;
+                    Some((Union(nodes), frame))
+                }
+                F::Intersect => match &args[..] {
+                    [a, b] if negated(b).is_some() => {
+                        let (nodes, frame) = self.nodes(&[a.clone(), negated(b)?.clone()])?;
+                        let [a, b] = <[SdfNode; 2]>::try_from(nodes).ok()?;
+                        Some((a.difference(b), frame))
+                    }
+                    _ => {
+                        let (nodes, frame) = self.nodes(args)?;
+                        Some((Intersection(nodes), frame))
+                    }
+                },
+                F::RoundMin => {
+                    let (k, args) = args.split_last()?;
+                    let (nodes, frame) = self.nodes(args)?;
+                    Some((SmoothUnion(nodes, number(k)?), frame))
+                }
+                _ => None,
+            },
+            // -rU(-a, -b, k) and -rU(-a, b, k)
+            Expr::Negate(e) => {
+                let args = function(e, F::RoundMin)?;
+                let (k, args) = args.split_last()?;
+                let k = number(k)?;
+                match args {
+                    [a, b] if negated(b).is_none() => {
+                        let (nodes, frame) = self.nodes(&[negated(a)?.clone(), b.clone()])?;
+                        let [a, b] = <[SdfNode; 2]>::try_from(nodes).ok()?;
+                        Some((a.smooth_difference(b, k), frame))
+                    }
+                    _ => {
+                        let args: Vec<Expr> = args
+                            .iter()
+                            .map(|a| negated(a).cloned())
+                            .collect::<Option<_>>()?;
+                        let (nodes, frame) = self.nodes(&args)?;
+                        Some((SmoothIntersection(nodes, k), frame))
+                    }
+                }
+            }
+            Expr::BinaryOp(op) => match op {
+                BinOp::Sub(a, r) => {
+                    let r = number(r)?;
+                    if let Some(args) = function(a, F::Length) {
+                        match args {
+                            [_, _, _] => {
+                                if let Some(frame) = self.point(args) {
+                                    return primitive(Some(frame), Sphere { radius: r });
+                                }
+                                // L(x, y-cl(y,0,h), z)
+                                let BinOp::Sub(y, clamped) = binary(&args[1])? else {
+                                    return None;
+                                };
+                                let clamp = function(clamped, F::Clamp)?;
+                                let (frame, _) = self.coord(y)?;
+                                let p = [args[0].clone(), *y.clone(), args[2].clone()];
+                                (self.point(&p) == Some(frame) && clamp[0] == **y).then_some(())?;
+                                let height = number(&clamp[2])?;
+                                primitive(Some(frame), Capsule { height, radius: r })
+                            }
+                            [x, z] => {
+                                let ((f, i), (g, j)) = (self.coord(x)?, self.coord(z)?);
+                                (f == g && i == 0 && j == 2).then_some(())?;
+                                primitive(Some(f), Cylinder { radius: r })
+                            }
+                            _ => None,
+                        }
+                    } else {
+                        let (node, frame) = self.distance(a)?;
+                        Some((node.round(r), frame))
+                    }
+                }
+                // D(x, y, z, a, b, c) + d
+                BinOp::Add(dot, offset) if function(dot, F::Dot).is_some() => {
+                    let args = function(dot, F::Dot)?;
+                    (args.len() == 6).then_some(())?;
+                    let normal = v3(number(&args[3])?, number(&args[4])?, number(&args[5])?);
+                    let offset = number(offset)?;
+                    primitive(self.point(&args[..3]), Plane { normal, offset })
+                }
+                // d + e, with e in the coordinates of one frame, which d
+                // is moved to.
+                BinOp::Add(d, e) => {
+                    let (node, frame) = self.distance(d)?;
+                    let used = Cell::new(None);
+                    let coords = |v: &str| match self.env.get(v) {
+                        Some(Value::Coord(f, i)) => {
+                            used.get().is_none_or(|g| g == *f).then_some(())?;
+                            used.set(Some(*f));
+                            Some(["x", "y", "z"][*i].to_string())
+                        }
+                        Some(Value::Node(..)) => None,
+                        None => Some(v.to_string()),
+                    };
+                    let e = rename(e, &coords)?;
+                    let to = used.get().unwrap_or(frame);
+                    let node = self.lift(node, frame, to)?;
+                    Some((Perturb(Box::new(node), e), to))
+                }
+                // s*d, where d is in a frame scaled by s
+                BinOp::Mul(s, d) => {
+                    let s = number(s)?;
+                    let (node, frame) = self.distance(d)?;
+                    let scaled = self.ancestors(frame).into_iter().find(
+                        |&f| matches!(self.frames[f], Some((_, Domain::Scale(t))) if t == s),
+                    )?;
+                    let node = self.lift(node, frame, scaled)?;
+                    Some((node.scale(s), self.frames[scaled]?.0))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::v;
+    use crate::eval::make_sdf;
+    use crate::pratt::parse;
+    use std::f32::consts::PI;
+
+    fn scene() -> SdfNode {
+        let pillar = SdfNode::capsule(4.0, 0.5)
+            .round(0.1)
+            .repeat(Axis::X, 3.0)
+            .mirror(Axis::Z)
+            .translate(v3(0.0, -2.0, 2.0));
+        SdfNode::sphere(2.0)
+            .smooth_union(SdfNode::cuboid(v3(1.0, 2.0, 1.5)).rotate(Axis::Y, 0.3), 0.5)
+            .difference(SdfNode::cylinder(0.5).rotate(Axis::X, PI / 2.0))
+            .union(pillar)
+            .union(
+                SdfNode::torus(3.0, 0.3)
+                    .scale(1.5)
+                    .translate(v3(0.0, 1.0, 0.0)),
+            )
+            .union(SdfNode::plane(v3(0.0, 1.0, 0.0), 3.0))
+            .smooth_intersect(SdfNode::sphere(8.0), 1.0)
+            .smooth_difference(SdfNode::sphere(1.0).translate(v3(2.0, 2.0, -2.0)), 0.3)
+    }
+
+    #[test]
+    fn builds() {
+        let node = SdfNode::sphere(1.0)
+            .union(SdfNode::cuboid(v(1.0)))
+            .union(SdfNode::torus(2.0, 0.5));
+        assert!(matches!(&node, Union(nodes) if nodes.len() == 3));
+        let sdf = SdfNode::sphere(1.0).translate(v3(3.0, 0.0, 0.0)).sdf();
+        assert_eq!(sdf(v3(3.0, 0.0, 0.0)), -1.0);
+        let sdf = SdfNode::cuboid(v3(2.0, 1.0, 1.0))
+            .rotate(Axis::Z, PI / 2.0)
+            .sdf();
+        assert!((sdf(v3(0.0, 3.0, 0.0)) - 1.0).abs() < 1e-5);
+        let sdf = SdfNode::sphere(1.0).scale(2.0).sdf();
+        assert_eq!(sdf(v3(5.0, 0.0, 0.0)), 3.0);
+        let sdf = SdfNode::sphere(1.0)
+            .translate(v3(1.0, 0.0, 0.0))
+            .mirror(Axis::X)
+            .sdf();
+        assert_eq!(sdf(v3(-1.0, 0.0, 0.0)), -1.0);
+        let sdf = SdfNode::sphere(1.0).repeat(Axis::Z, 4.0).sdf();
+        assert_eq!(sdf(v3(0.0, 0.0, 8.0)), -1.0);
+    }
+
+    #[test]
+    fn statements() {
+        let node = scene();
+        let statement = node.to_statement();
+        let sdf = node.sdf();
+        for i in 0..200 {
+            let p = v3(
+                (i % 7) as f32 - 3.0,
+                (i % 11) as f32 * 0.7 - 3.5,
+                (i % 13) as f32 * 0.5 - 3.0,
+            );
+            let program = make_sdf(&statement, 0.0, 0.0, p);
+            assert!(
+                (program - sdf(p)).abs() < 1e-4,
+                "{} {} {}",
+                p,
+                program,
+                sdf(p)
+            );
+        }
+        assert_eq!(SdfNode::from_statement(&statement), Some(node));
+        // Written by hand the same way.
+        let statement = parse(
+            &mut "[x1,y1,z1]=[x-1,y-2,z-3];d1=L(x1,y1,z1)-2;d2=bx3(x,y,z,1,2,3);d3=U(d1,d2);d3",
+        );
+        assert_eq!(
+            SdfNode::from_statement(&statement),
+            Some(
+                SdfNode::sphere(2.0)
+                    .translate(v3(1.0, 2.0, 3.0))
+                    .union(SdfNode::cuboid(v3(1.0, 2.0, 3.0)))
+            )
+        );
+        assert_eq!(
+            SdfNode::from_statement(&parse(&mut "L(x,y,z)-sin(x)")),
+            None
+        );
+    }
+
+    #[test]
+    fn serializes() {
+        let node = scene();
+        let json: SdfNode = serde_json::from_str(&serde_json::to_string(&node).unwrap()).unwrap();
+        let ron: SdfNode = ron::from_str(&ron::to_string(&node).unwrap()).unwrap();
+        assert_eq!(json, node);
+        assert_eq!(ron, node);
+        let (sdf, saved) = (node.sdf(), json.sdf());
+        for p in [ZERO3, v3(1.0, 2.0, 3.0), v3(-2.5, 0.5, 1.0)] {
+            assert_eq!(sdf(p), saved(p));
+        }
+        let json = r#"{"translate": [{"sphere": {"radius": 1.0}}, [0.0, 2.0, 0.0]]}"#;
+        assert_eq!(
+            serde_json::from_str::<SdfNode>(json).unwrap(),
+            SdfNode::sphere(1.0).translate(v3(0.0, 2.0, 0.0))
+        );
+    }
+}
@@ -0,0 +1,431 @@
+// Seeded noise. Everything that needs noise uses this module: the DSL's
+// `nz`, `vnz` and friends, the code generated from it, and `core::Noise`.
+// The bases are `value`, `perlin` and `simplex` noise, between about -1 and
+// 1, and Worley's cellular noise. Each has a version that also returns the
+// gradient. `Fbm` sums octaves of a basis, and can also make them ridged,
+// billowy or domain warped. Different seeds give unrelated noise. `hash` and
+// `fbm_value`, the DSL's `ri` and `nz`, predate the rest and aren't seeded,
+// scenes depend on their exact values.
+use crate::core::mix;
+use glam::{Vec2, Vec3};
+use serde::{Deserialize, Serialize};
+
+// Murmur3's mixing of the cell `i` and `seed`.
+fn hash_cell(x: i32, y: i32, z: i32, seed: u32) -> u32 {
+    let mut h = seed;
+    for k in [x as u32, y as u32, z as u32] {
+        h ^= k
+            .wrapping_mul(0xcc9e2d51)
+            .rotate_left(15)
+            .wrapping_mul(0x1b873593);
+        h = h.rotate_left(13).wrapping_mul(5).wrapping_add(0xe6546b64);
+    }
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85ebca6b);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xc2b2ae35);
+    h ^ (h >> 16)
+}
+
+fn unit(h: u32) -> f32 {
+    (h >> 8) as f32 / (1 << 24) as f32
+}
+
+// A number between 0 and 1, at least for positive coordinates.
+#[allow(clippy::approx_constant)]
+pub fn hash(p: Vec3) -> f32 {
+    let mut p = (p * 0.3183099 + 0.1).fract();
+    p *= 17.0;
+    (p.x * p.y * p.z * (p.x + p.y + p.z)).fract()
+}
+
+// Value noise of `hash`, between -1 and 1 for positive coordinates.
+pub fn hash_noise(x: Vec3) -> f32 {
+    let i = x.floor();
+    let f = x.fract();
+    let f = f * f * (3.0 - 2.0 * f);
+    let h = |dx: f32, dy: f32, dz: f32| hash(i + Vec3::new(dx, dy, dz));
+    mix(
+        mix(
+            mix(h(0.0, 0.0, 0.0), h(1.0, 0.0, 0.0), f.x),
+            mix(h(0.0, 1.0, 0.0), h(1.0, 1.0, 0.0), f.x),
+            f.y,
+        ),
+        mix(
+            mix(h(0.0, 0.0, 1.0), h(1.0, 0.0, 1.0), f.x),
+            mix(h(0.0, 1.0, 1.0), h(1.0, 1.0, 1.0), f.x),
+            f.y,
+        ),
+        f.z,
+    ) * 2.0
+        - 1.0
+}
+
+// The weights of the corners of a cell at `u` in it, indexed by
+// `x + 2y + 4z`, with their derivatives.
+fn corners(u: Vec3) -> [(f32, Vec3); 8] {
+    std::array::from_fn(|i| {
+        let pick = |bit: usize, t: f32| {
+            if i >> bit & 1 == 1 {
+                (t, 1.0)
+            } else {
+                (1.0 - t, -1.0)
+            }
+        };
+        let (wx, dx) = pick(0, u.x);
+        let (wy, dy) = pick(1, u.y);
+        let (wz, dz) = pick(2, u.z);
+        (
+            wx * wy * wz,
+            Vec3::new(dx * wy * wz, wx * dy * wz, wx * wy * dz),
+        )
+    })
+}
+
+fn corner(i: usize) -> Vec3 {
+    Vec3::new((i & 1) as f32, (i >> 1 & 1) as f32, (i >> 2 & 1) as f32)
+}
+
+// Quintic smoothing, so the second derivative is continuous too.
+fn fade(f: Vec3) -> (Vec3, Vec3) {
+    let u = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+    let du = 30.0 * f * f * (f - 1.0) * (f - 1.0);
+    (u, du)
+}
+
+// The cell of `p` and where `p` is in it.
+fn cell(p: Vec3) -> ([i32; 3], Vec3) {
+    let i = p.floor();
+    ([i.x as i32, i.y as i32, i.z as i32], p - i)
+}
+
+fn hash_corner(i: [i32; 3], c: usize, seed: u32) -> u32 {
+    hash_cell(
+        i[0] + (c & 1) as i32,
+        i[1] + (c >> 1 & 1) as i32,
+        i[2] + (c >> 2 & 1) as i32,
+        seed,
+    )
+}
+
+pub fn value_d(p: Vec3, seed: u32) -> (f32, Vec3) {
+    let (i, f) = cell(p);
+    let (u, du) = fade(f);
+    let mut n = 0.0;
+    let mut dn = Vec3::ZERO;
+    for (c, (w, dw)) in corners(u).into_iter().enumerate() {
+        let v = unit(hash_corner(i, c, seed)) * 2.0 - 1.0;
+        n += w * v;
+        dn += dw * v;
+    }
+    (n, dn * du)
+}
+
+// The 12 gradients of improved Perlin noise, the edges of a cube.
+fn gradient(h: u32) -> Vec3 {
+    const G: [[f32; 3]; 12] = [
+        [1.0, 1.0, 0.0],
+        [-1.0, 1.0, 0.0],
+        [1.0, -1.0, 0.0],
+        [-1.0, -1.0, 0.0],
+        [1.0, 0.0, 1.0],
+        [-1.0, 0.0, 1.0],
+        [1.0, 0.0, -1.0],
+        [-1.0, 0.0, -1.0],
+        [0.0, 1.0, 1.0],
+        [0.0, -1.0, 1.0],
+        [0.0, 1.0, -1.0],
+        [0.0, -1.0, -1.0],
+    ];
+    Vec3::from(G[(h % 12) as usize])
+}
+
+pub fn perlin_d(p: Vec3, seed: u32) -> (f32, Vec3) {
+    let (i, f) = cell(p);
+    let (u, du) = fade(f);
+    let mut n = 0.0;
+    let mut dn = Vec3::ZERO;
+    let mut g = Vec3::ZERO;
+    for (c, (w, dw)) in corners(u).into_iter().enumerate() {
+        let gc = gradient(hash_corner(i, c, seed));
+        let v = gc.dot(f - corner(c));
+        n += w * v;
+        dn += dw * v;
+        g += w * gc;
+    }
+    (n, g + dn * du)
+}
+
+pub fn simplex_d(p: Vec3, seed: u32) -> (f32, Vec3) {
+    const F3: f32 = 1.0 / 3.0;
+    const G3: f32 = 1.0 / 6.0;
+    let s = (p.x + p.y + p.z) * F3;
+    let i = (p + s).floor();
+    let x0 = p - i + (i.x + i.y + i.z) * G3;
+    // The simplex of the cell `x0` is in, from the order of its coordinates.
+    let (i1, i2) = if x0.x >= x0.y {
+        if x0.y >= x0.z {
+            (Vec3::X, Vec3::new(1.0, 1.0, 0.0))
+        } else if x0.x >= x0.z {
+            (Vec3::X, Vec3::new(1.0, 0.0, 1.0))
+        } else {
+            (Vec3::Z, Vec3::new(1.0, 0.0, 1.0))
+        }
+    } else if x0.y < x0.z {
+        (Vec3::Z, Vec3::new(0.0, 1.0, 1.0))
+    } else if x0.x < x0.z {
+        (Vec3::Y, Vec3::new(0.0, 1.0, 1.0))
+    } else {
+        (Vec3::Y, Vec3::new(1.0, 1.0, 0.0))
+    };
+    let mut n = 0.0;
+    let mut dn = Vec3::ZERO;
+    for (k, o) in [Vec3::ZERO, i1, i2, Vec3::ONE].into_iter().enumerate() {
+        let x = x0 - o + k as f32 * G3;
+        let t = 0.5 - x.length_squared();
+        if t > 0.0 {
+            let c = i + o;
+            let g = gradient(hash_cell(c.x as i32, c.y as i32, c.z as i32, seed));
+            let v = g.dot(x);
+            let t2 = t * t;
+            n += t2 * t2 * v;
+            dn += t2 * t2 * g - 8.0 * t2 * t * v * x;
+        }
+    }
+    // Scaled to about -1 to 1.
+    (n * 70.0, dn * 70.0)
+}
+
+// The distances from `p` to the nearest and the second nearest of a random
+// point in each cell, and the direction away from the nearest.
+pub fn worley_d(p: Vec3, seed: u32) -> (Vec2, Vec3) {
+    let (i, f) = cell(p);
+    let mut f1 = f32::MAX;
+    let mut f2 = f32::MAX;
+    let mut away = Vec3::ZERO;
+    for c in 0..27 {
+        let o = [c % 3 - 1, c / 3 % 3 - 1, c / 9 - 1];
+        let h = hash_cell(i[0] + o[0], i[1] + o[1], i[2] + o[2], seed);
+        let point = Vec3::new(o[0] as f32, o[1] as f32, o[2] as f32)
+            + Vec3::new(
+                unit(h),
+                unit(hash_cell(h as i32, 1, 0, seed)),
+                unit(hash_cell(h as i32, 2, 0, seed)),
+            );
+        let r = f - point;
+        let d = r.length();
+        if d < f1 {
+            f2 = f1;
+            f1 = d;
+            away = r / d.max(f32::MIN_POSITIVE);
+        } else if d < f2 {
+            f2 = d;
+        }
+    }
+    (Vec2::new(f1, f2), away)
+}
+
+pub fn value(p: Vec3, seed: u32) -> f32 {
+    value_d(p, seed).0
+}
+
+pub fn perlin(p: Vec3, seed: u32) -> f32 {
+    perlin_d(p, seed).0
+}
+
+pub fn simplex(p: Vec3, seed: u32) -> f32 {
+    simplex_d(p, seed).0
+}
+
+pub fn worley(p: Vec3, seed: u32) -> Vec2 {
+    worley_d(p, seed).0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Basis {
+    Value,
+    Perlin,
+    Simplex,
+    // Twice the distance to the nearest point less 1, which can reach a
+    // little over 1.
+    Worley,
+}
+
+impl Basis {
+    pub fn get(self, p: Vec3, seed: u32) -> f32 {
+        self.gradient(p, seed).0
+    }
+
+    pub fn gradient(self, p: Vec3, seed: u32) -> (f32, Vec3) {
+        match self {
+            Basis::Value => value_d(p, seed),
+            Basis::Perlin => perlin_d(p, seed),
+            Basis::Simplex => simplex_d(p, seed),
+            Basis::Worley => {
+                let (f, away) = worley_d(p, seed);
+                (2.0 * f.x - 1.0, 2.0 * away)
+            }
+        }
+    }
+}
+
+// Fractal Brownian motion, `octaves` of `basis`, each `lacunarity` times the
+// frequency and `gain` times the amplitude of the one before, starting at
+// 1/2. Each octave has its own seed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fbm {
+    pub basis: Basis,
+    pub seed: u32,
+    pub octaves: u32,
+    pub lacunarity: f32,
+    pub gain: f32,
+}
+
+impl Fbm {
+    pub fn new(basis: Basis, seed: u32, octaves: u32) -> Self {
+        Fbm {
+            basis,
+            seed,
+            octaves,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+
+    // The amplitude, frequency and seed of each octave.
+    fn octaves(&self) -> impl Iterator<Item = (f32, f32, u32)> + '_ {
+        (0..self.octaves).map(|i| {
+            (
+                0.5 * self.gain.powi(i as i32),
+                self.lacunarity.powi(i as i32),
+                self.seed.wrapping_add(i),
+            )
+        })
+    }
+
+    pub fn get(&self, p: Vec3) -> f32 {
+        self.octaves()
+            .map(|(a, f, seed)| a * self.basis.get(p * f, seed))
+            .sum()
+    }
+
+    pub fn gradient(&self, p: Vec3) -> (f32, Vec3) {
+        self.octaves()
+            .fold((0.0, Vec3::ZERO), |(n, dn), (a, f, seed)| {
+                let (v, dv) = self.basis.gradient(p * f, seed);
+                (n + a * v, dn + a * f * dv)
+            })
+    }
+
+    // Sharp ridges where the basis is 0, between 0 and 1.
+    pub fn ridged(&self, p: Vec3) -> f32 {
+        self.octaves()
+            .map(|(a, f, seed)| a * (1.0 - self.basis.get(p * f, seed).abs()).powi(2))
+            .sum()
+    }
+
+    // Rounded bumps with creases between them.
+    pub fn billow(&self, p: Vec3) -> f32 {
+        self.octaves()
+            .map(|(a, f, seed)| a * (2.0 * self.basis.get(p * f, seed).abs() - 1.0))
+            .sum()
+    }
+
+    // Inigo Quilez's domain warping, the fbm at `p` moved by `strength` times
+    // three more fbms.
+    pub fn warp(&self, p: Vec3, strength: f32) -> f32 {
+        let q = Vec3::new(
+            self.get(p),
+            self.get(p + Vec3::new(5.2, 1.3, 2.8)),
+            self.get(p + Vec3::new(1.7, 9.2, 4.1)),
+        );
+        self.get(p + strength * q)
+    }
+}
+
+// The DSL's `nz`. The range of the noise depends on the number of octaves.
+pub fn fbm_value(x: f32, y: f32, z: f32, scale: f32, offset: f32, octaves: u32) -> f32 {
+    let mut p = Vec3::new(x, y, z) * scale;
+    let offset = Vec3::splat(offset);
+    let mut a = 1.0;
+    let mut sum = 0.0;
+    for _ in 1..=octaves {
+        a *= 0.5;
+        sum += a * hash_noise(p + offset);
+        p *= 2.03;
+    }
+    sum
+}
+
+pub fn fbm_perlin(x: f32, y: f32, z: f32, scale: f32, offset: f32, octaves: u32, seed: u32) -> f32 {
+    Fbm::new(Basis::Perlin, seed, octaves).get(Vec3::new(x, y, z) * scale + offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_points() -> impl Iterator<Item = Vec3> {
+        (0..500).map(|i| {
+            let t = i as f32;
+            Vec3::new(t * 0.737 - 180.0, t * 0.291 - 70.0, (t * 1.37).sin() * 9.0)
+        })
+    }
+
+    #[test]
+    fn bases() {
+        for basis in [Basis::Value, Basis::Perlin, Basis::Simplex, Basis::Worley] {
+            let mut changed = false;
+            for p in sample_points() {
+                let n = basis.get(p, 7);
+                let top = if basis == Basis::Worley { 2.0 } else { 1.0 };
+                assert!((-1.0..=top).contains(&n), "{basis:?} at {p} is {n}");
+                changed |= n != basis.get(p, 8);
+                // The gradient agrees with the differences.
+                let (n, g) = basis.gradient(p, 7);
+                let e = 1e-3;
+                let d = Vec3::new(
+                    basis.get(p + Vec3::X * e, 7) - n,
+                    basis.get(p + Vec3::Y * e, 7) - n,
+                    basis.get(p + Vec3::Z * e, 7) - n,
+                ) / e;
+                assert!(
+                    (d - g).length() < 0.1 * g.length().max(1.0),
+                    "{basis:?} at {p}"
+                );
+            }
+            assert!(changed, "{basis:?} ignores the seed");
+        }
+        // Gradient noise is 0 at the corners of the cells.
+        assert_eq!(perlin(Vec3::new(3.0, -2.0, 5.0), 1), 0.0);
+        let f = worley(Vec3::new(0.3, 0.4, 0.5), 0);
+        assert!(f.x <= f.y && f.y < 3f32.sqrt());
+    }
+
+    #[test]
+    fn fbms() {
+        let fbm = Fbm::new(Basis::Perlin, 3, 5);
+        let p = Vec3::new(1.3, 2.1, -0.4);
+        assert_eq!(fbm.get(p), fbm.gradient(p).0);
+        assert_eq!(Fbm { octaves: 0, ..fbm }.get(p), 0.0);
+        assert_ne!(fbm.warp(p, 1.0), fbm.get(p));
+        for p in sample_points() {
+            assert!((0.0..1.0).contains(&fbm.ridged(p)));
+            assert!((-1.0..1.0).contains(&fbm.billow(p)));
+        }
+    }
+
+    #[test]
+    fn unseeded() {
+        // Scenes were made with these, they mustn't change.
+        assert_eq!(hash(Vec3::new(1.0, 2.0, 3.0)), 0.32873535);
+        assert_eq!(hash(Vec3::new(-7.3, 2.5, -3.1)), -0.08642578);
+        assert_eq!(fbm_value(1.0, 2.0, 3.0, 0.1, 0.5, 3), -0.26553428);
+        assert_eq!(fbm_value(-7.3, 2.0, -3.1, 0.7, 0.0, 4), -1.6293117);
+        assert_eq!(
+            fbm_value(1.0, 2.0, 3.0, 0.1, 0.5, 1),
+            hash_noise(Vec3::new(0.6, 0.7, 0.8)) * 0.5
+        );
+    }
+}
@@ -0,0 +1,657 @@
+// Rewrites a parsed program into a smaller, equivalent one. The program is
+// first put into SSA form (every assignment gets a fresh name, `x++` inside
+// expressions becomes its own statement), which makes every variable
+// immutable. After that constant folding, algebraic simplification, copy
+// propagation, common subexpression elimination and dead-store removal are
+// simple local rewrites that we run until nothing changes.
+use crate::ast::{
+    children, map_children, operands, rebuild, AssignExpr, BinOp, Expr, FunctionName, Statement,
+};
+use crate::eval::{eval_constant, Value};
+use std::collections::{HashMap, HashSet};
+
+const MAX_PASSES: usize = 16;
+// Every statement costs the interpreter about as much as evaluating this many
+// expression nodes, so only hoist subexpressions that save more than that.
+const STATEMENT_COST: usize = 8;
+
+pub fn optimize(ast: &Statement) -> Statement {
+    let mut stmts = Vec::new();
+    flatten(ast, &mut stmts);
+    // An `i++` that only runs on one side of a ternary can't be hoisted into
+    // a statement, so leave those programs alone.
+    if stmts.iter().any(has_conditional_assign) {
+        return Statement::Sequence(stmts);
+    }
+    let mut opt = Optimizer::default();
+    let mut stmts = opt.ssa(stmts);
+    for _ in 0..MAX_PASSES {
+        let before = stmts.clone();
+        stmts = propagate(stmts);
+        stmts = opt.eliminate_common(stmts);
+        stmts = eliminate_dead(stmts);
+        if stmts == before {
+            break;
+        }
+    }
+    Statement::Sequence(stmts)
+}
+
+// Moves `x++` and `x--` out of expressions into statements of their own, for
+// code generators whose target has no such expressions. Unlike `optimize` only
+// the incremented variables get new names, and the old name is assigned the
+// new value after the statement so later statements see it. An increment on
+// one side of a ternary keeps the old value when the other side is taken.
+pub fn lower_increments(ast: &Statement) -> Statement {
+    let mut stmts = Vec::new();
+    flatten(ast, &mut stmts);
+    let last = stmts.len().saturating_sub(1);
+    let mut lowering = Lowering::default();
+    for (i, stmt) in stmts.into_iter().enumerate() {
+        let stmt = map_exprs(stmt, |e| lowering.lower(e));
+        let defined = defines(&stmt);
+        let mut renamed: Vec<(String, String)> = lowering.renamed.drain().collect();
+        renamed.sort();
+        let copies: Vec<Statement> = renamed
+            .into_iter()
+            .filter(|(var, _)| i != last && !defined.contains(&var))
+            .map(|(var, new)| Statement::Assign {
+                var,
+                rhs: Box::new(Expr::Variable(new)),
+            })
+            .collect();
+        lowering.out.push(stmt);
+        lowering.out.extend(copies);
+    }
+    Statement::Sequence(lowering.out)
+}
+
+#[derive(Default)]
+struct Lowering {
+    count: usize,
+    renamed: HashMap<String, String>,
+    out: Vec<Statement>,
+    // The conditions of the ternaries we're inside and which side we're on.
+    guards: Vec<(String, bool)>,
+}
+
+impl Lowering {
+    fn lower(&mut self, e: &Expr) -> Expr {
+        match e {
+            Expr::Variable(v) => Expr::Variable(self.renamed.get(v).unwrap_or(v).clone()),
+            Expr::Assign(assign) => {
+                let var = match assign {
+                    AssignExpr::Inc(var) | AssignExpr::Dec(var) => var,
+                };
+                let old = self.lower(&Expr::Variable(var.clone()));
+                let one = Box::new(Expr::Number(1.0));
+                let mut rhs = Expr::BinaryOp(match assign {
+                    AssignExpr::Inc(_) => BinOp::Add(Box::new(old.clone()), one),
+                    AssignExpr::Dec(_) => BinOp::Sub(Box::new(old.clone()), one),
+                });
+                for (cond, side) in self.guards.iter().rev() {
+                    let (t, f) = if *side {
+                        (rhs, old.clone())
+                    } else {
+                        (old.clone(), rhs)
+                    };
+                    let cond = Box::new(Expr::Variable(cond.clone()));
+                    rhs = Expr::TernaryOp(cond, Box::new(t), Box::new(f));
+                }
+                self.count += 1;
+                let new = format!("{}_{}", var, self.count);
+                self.out.push(Statement::Assign {
+                    var: new.clone(),
+                    rhs: Box::new(rhs),
+                });
+                self.renamed.insert(var.clone(), new.clone());
+                Expr::Variable(new)
+            }
+            Expr::TernaryOp(c, t, f) if contains_assign(t) || contains_assign(f) => {
+                let c = self.lower(c);
+                self.count += 1;
+                let cond = format!("cond_{}", self.count);
+                self.out.push(Statement::Assign {
+                    var: cond.clone(),
+                    rhs: Box::new(c),
+                });
+                self.guards.push((cond.clone(), true));
+                let t = self.lower(t);
+                self.guards.last_mut().unwrap().1 = false;
+                let f = self.lower(f);
+                self.guards.pop();
+                let cond = Box::new(Expr::Variable(cond));
+                Expr::TernaryOp(cond, Box::new(t), Box::new(f))
+            }
+            e => map_children(e, |c| self.lower(c)),
+        }
+    }
+}
+
+// The variables a statement assigns to.
+pub(crate) fn defines(stmt: &Statement) -> Vec<&String> {
+    match stmt {
+        Statement::Assign { var, .. } => vec![var],
+        Statement::AssignToArray { vars, .. } | Statement::AssignFromArray { vars, .. } => {
+            vars.iter().collect()
+        }
+        _ => vec![],
+    }
+}
+
+pub(crate) fn flatten(ast: &Statement, out: &mut Vec<Statement>) {
+    match ast {
+        Statement::Sequence(stmts) => stmts.iter().for_each(|s| flatten(s, out)),
+        Statement::Empty => {}
+        s => out.push(s.clone()),
+    }
+}
+
+#[derive(Default)]
+struct Optimizer {
+    versions: HashMap<String, usize>,
+    temps: usize,
+}
+
+impl Optimizer {
+    // The input names (x, y, z, a0, a1) are version 0 and keep their names.
+    // The DSL lexer does not accept `_`, so the new names can't collide with
+    // user variables.
+    fn current(&self, var: &str) -> String {
+        match self.versions.get(var) {
+            None | Some(0) => var.to_string(),
+            Some(n) => format!("{}_{}", var, n),
+        }
+    }
+
+    fn define(&mut self, var: &str) -> String {
+        let n = self.versions.entry(var.to_string()).or_insert(0);
+        *n += 1;
+        format!("{}_{}", var, n)
+    }
+
+    fn ssa(&mut self, stmts: Vec<Statement>) -> Vec<Statement> {
+        let mut out = Vec::new();
+        for stmt in stmts {
+            match stmt {
+                Statement::Assign { var, rhs } => {
+                    let rhs = self.rename(&rhs, &mut out);
+                    let var = self.define(&var);
+                    out.push(Statement::Assign {
+                        var,
+                        rhs: Box::new(rhs),
+                    });
+                }
+                Statement::AssignToArray { vars, rhs } => {
+                    let rhs = self.rename(&rhs, &mut out);
+                    let vars = vars.iter().map(|v| self.define(v)).collect();
+                    out.push(Statement::AssignToArray {
+                        vars,
+                        rhs: Box::new(rhs),
+                    });
+                }
+                // All of the right hand sides are evaluated before any of the
+                // assignments happen, e.g. `[x,y,z]=[y,z,x]`.
+                Statement::AssignFromArray { vars, rhs } => {
+                    let rhs: Vec<Expr> = rhs.iter().map(|r| self.rename(r, &mut out)).collect();
+                    for (var, rhs) in vars.iter().zip(rhs) {
+                        let var = self.define(var);
+                        out.push(Statement::Assign {
+                            var,
+                            rhs: Box::new(rhs),
+                        });
+                    }
+                }
+                Statement::Return(e) => {
+                    let e = self.rename(&e, &mut out);
+                    out.push(Statement::Return(Box::new(e)));
+                }
+                Statement::Sequence(_) | Statement::Empty => unreachable!("program is flattened"),
+            }
+        }
+        out
+    }
+
+    // Arguments are evaluated left to right, so an increment only affects
+    // the references that come after it.
+    fn rename(&mut self, e: &Expr, out: &mut Vec<Statement>) -> Expr {
+        match e {
+            Expr::Number(n) => Expr::Number(*n),
+            Expr::Variable(v) => Expr::Variable(self.current(v)),
+            Expr::Negate(a) => Expr::Negate(Box::new(self.rename(a, out))),
+            Expr::BinaryOp(op) => {
+                let (a, b) = operands(op);
+                let a = self.rename(a, out);
+                let b = self.rename(b, out);
+                Expr::BinaryOp(rebuild(op, a, b))
+            }
+            Expr::Function { name, args } => Expr::Function {
+                name: name.clone(),
+                args: args.iter().map(|a| self.rename(a, out)).collect(),
+            },
+            Expr::TernaryOp(c, t, f) => {
+                let c = self.rename(c, out);
+                let t = self.rename(t, out);
+                let f = self.rename(f, out);
+                Expr::TernaryOp(Box::new(c), Box::new(t), Box::new(f))
+            }
+            Expr::Assign(assign) => {
+                let var = match assign {
+                    AssignExpr::Inc(var) | AssignExpr::Dec(var) => var,
+                };
+                let old = Box::new(Expr::Variable(self.current(var)));
+                let one = Box::new(Expr::Number(1.0));
+                let new = self.define(var);
+                let rhs = match assign {
+                    AssignExpr::Inc(_) => BinOp::Add(old, one),
+                    AssignExpr::Dec(_) => BinOp::Sub(old, one),
+                };
+                out.push(Statement::Assign {
+                    var: new.clone(),
+                    rhs: Box::new(Expr::BinaryOp(rhs)),
+                });
+                Expr::Variable(new)
+            }
+        }
+    }
+
+    // Statements whose whole right hand side was already computed become
+    // copies, and any subexpression that appears more than once is computed
+    // once into a temporary placed before its first use.
+    fn eliminate_common(&mut self, stmts: Vec<Statement>) -> Vec<Statement> {
+        let mut seen: HashMap<String, String> = HashMap::new();
+        let mut stmts: Vec<Statement> = stmts
+            .into_iter()
+            .map(|stmt| match stmt {
+                Statement::Assign { var, rhs } => {
+                    let rhs = if is_trivial(&rhs) {
+                        *rhs
+                    } else {
+                        let key = format!("{:?}", rhs);
+                        match seen.get(&key) {
+                            Some(prev) => Expr::Variable(prev.clone()),
+                            None => {
+                                let rhs = replace_known(&rhs, &seen);
+                                seen.insert(key, var.clone());
+                                rhs
+                            }
+                        }
+                    };
+                    Statement::Assign {
+                        var,
+                        rhs: Box::new(rhs),
+                    }
+                }
+                s => map_exprs(s, |e| replace_known(e, &seen)),
+            })
+            .collect();
+
+        while let Some((expr, first)) = most_repeated(&stmts) {
+            self.temps += 1;
+            let temp = format!("_t{}", self.temps);
+            let key = HashMap::from([(format!("{:?}", expr), temp.clone())]);
+            for stmt in stmts.iter_mut().skip(first) {
+                *stmt = map_exprs(stmt.clone(), |e| replace_known(e, &key));
+            }
+            stmts.insert(
+                first,
+                Statement::Assign {
+                    var: temp,
+                    rhs: Box::new(expr),
+                },
+            );
+        }
+        stmts
+    }
+}
+
+fn contains_assign(e: &Expr) -> bool {
+    matches!(e, Expr::Assign(_)) || children(e).into_iter().any(contains_assign)
+}
+
+fn has_conditional_assign(stmt: &Statement) -> bool {
+    fn conditional(e: &Expr) -> bool {
+        match e {
+            Expr::TernaryOp(c, t, f) => conditional(c) || contains_assign(t) || contains_assign(f),
+            e => children(e).into_iter().any(conditional),
+        }
+    }
+    exprs(stmt).into_iter().any(conditional)
+}
+
+pub(crate) fn exprs(stmt: &Statement) -> Vec<&Expr> {
+    match stmt {
+        Statement::Assign { rhs, .. } | Statement::AssignToArray { rhs, .. } => vec![rhs],
+        Statement::AssignFromArray { rhs, .. } => rhs.iter().collect(),
+        Statement::Return(e) => vec![e],
+        Statement::Sequence(_) | Statement::Empty => vec![],
+    }
+}
+
+fn map_exprs<F: FnMut(&Expr) -> Expr>(stmt: Statement, mut f: F) -> Statement {
+    match stmt {
+        Statement::Assign { var, rhs } => Statement::Assign {
+            var,
+            rhs: Box::new(f(&rhs)),
+        },
+        Statement::AssignToArray { vars, rhs } => Statement::AssignToArray {
+            vars,
+            rhs: Box::new(f(&rhs)),
+        },
+        Statement::AssignFromArray { vars, rhs } => Statement::AssignFromArray {
+            vars,
+            rhs: rhs.iter().map(f).collect(),
+        },
+        Statement::Return(e) => Statement::Return(Box::new(f(&e))),
+        s => s,
+    }
+}
+
+fn is_trivial(e: &Expr) -> bool {
+    matches!(e, Expr::Number(_) | Expr::Variable(_))
+}
+
+// Rot0 and Rot1 read the a0/a1 parameters, so they are never constant.
+fn is_constant(e: &Expr) -> bool {
+    match e {
+        Expr::Variable(_) | Expr::Assign(_) => false,
+        Expr::Function {
+            name: FunctionName::Rot0 | FunctionName::Rot1,
+            ..
+        } => false,
+        e => children(e).into_iter().all(is_constant),
+    }
+}
+
+fn size(e: &Expr) -> usize {
+    1 + children(e).into_iter().map(size).sum::<usize>()
+}
+
+fn uses(e: &Expr, live: &mut HashSet<String>) {
+    match e {
+        Expr::Variable(v) => {
+            live.insert(v.clone());
+        }
+        e => children(e).into_iter().for_each(|c| uses(c, live)),
+    }
+}
+
+fn replace_known(e: &Expr, known: &HashMap<String, String>) -> Expr {
+    if !is_trivial(e) {
+        if let Some(var) = known.get(&format!("{:?}", e)) {
+            return Expr::Variable(var.clone());
+        }
+    }
+    map_children(e, |c| replace_known(c, known))
+}
+
+// The largest subexpression that occurs at least twice, together with the
+// index of the first statement it occurs in.
+fn most_repeated(stmts: &[Statement]) -> Option<(Expr, usize)> {
+    fn collect<'a>(e: &'a Expr, i: usize, found: &mut HashMap<String, (&'a Expr, usize, usize)>) {
+        if size(e) > 1 {
+            found
+                .entry(format!("{:?}", e))
+                .and_modify(|(_, _, count)| *count += 1)
+                .or_insert((e, i, 1));
+        }
+        children(e).into_iter().for_each(|c| collect(c, i, found));
+    }
+    let mut found = HashMap::new();
+    for (i, stmt) in stmts.iter().enumerate() {
+        for e in exprs(stmt) {
+            // A whole right hand side is already named by its statement.
+            children(e)
+                .into_iter()
+                .for_each(|c| collect(c, i, &mut found));
+        }
+    }
+    found
+        .into_values()
+        .filter(|(e, _, count)| (count - 1) * size(e) > STATEMENT_COST)
+        .max_by_key(|(e, i, _)| (size(e), usize::MAX - i))
+        .map(|(e, i, _)| (e.clone(), i))
+}
+
+fn propagate(stmts: Vec<Statement>) -> Vec<Statement> {
+    let mut known: HashMap<String, Expr> = HashMap::new();
+    stmts
+        .into_iter()
+        .map(|stmt| match stmt {
+            Statement::Assign { var, rhs } => {
+                let rhs = simplify(&rhs, &known);
+                if is_trivial(&rhs) {
+                    known.insert(var.clone(), rhs.clone());
+                }
+                Statement::Assign {
+                    var,
+                    rhs: Box::new(rhs),
+                }
+            }
+            s => map_exprs(s, |e| simplify(e, &known)),
+        })
+        .collect()
+}
+
+fn simplify(e: &Expr, known: &HashMap<String, Expr>) -> Expr {
+    if let Expr::Variable(v) = e {
+        return known.get(v).cloned().unwrap_or_else(|| e.clone());
+    }
+    let e = map_children(e, |c| simplify(c, known));
+    if !matches!(e, Expr::Number(_)) && is_constant(&e) {
+        if let Value::ScalarVal(v) = eval_constant(&e) {
+            if v.is_finite() {
+                return Expr::Number(v);
+            }
+        }
+    }
+    algebra(e)
+}
+
+fn algebra(e: Expr) -> Expr {
+    use Expr::{BinaryOp, Negate, Number};
+    match e {
+        Negate(a) => match *a {
+            Negate(b) => *b,
+            a => Negate(Box::new(a)),
+        },
+        BinaryOp(BinOp::Add(a, b)) => match (*a, *b) {
+            (a, Number(0.0)) | (Number(0.0), a) => a,
+            (a, Number(c)) => offset(a, c),
+            (a, b) => BinaryOp(BinOp::Add(Box::new(a), Box::new(b))),
+        },
+        BinaryOp(BinOp::Sub(a, b)) => match (*a, *b) {
+            (a, Number(0.0)) => a,
+            (Number(0.0), b) => Negate(Box::new(b)),
+            (a, Number(c)) => offset(a, -c),
+            (a, b) => BinaryOp(BinOp::Sub(Box::new(a), Box::new(b))),
+        },
+        BinaryOp(BinOp::Mul(a, b)) => match (*a, *b) {
+            (a, Number(1.0)) | (Number(1.0), a) => a,
+            (a, Number(-1.0)) | (Number(-1.0), a) => Negate(Box::new(a)),
+            (a, b) => BinaryOp(BinOp::Mul(Box::new(a), Box::new(b))),
+        },
+        BinaryOp(BinOp::Div(a, b)) => match (*a, *b) {
+            (a, Number(1.0)) => a,
+            (a, b) => BinaryOp(BinOp::Div(Box::new(a), Box::new(b))),
+        },
+        BinaryOp(BinOp::Pow(a, b)) => match (*a, *b) {
+            (a, Number(1.0)) => a,
+            (a, b) => BinaryOp(BinOp::Pow(Box::new(a), Box::new(b))),
+        },
+        Expr::TernaryOp(c, t, f) if is_constant(&c) => match eval_constant(&c) {
+            Value::BoolVal(true) => *t,
+            Value::BoolVal(false) => *f,
+            _ => Expr::TernaryOp(c, t, f),
+        },
+        e => e,
+    }
+}
+
+// `a + c`, merging `c` into a constant that `a` already adds or subtracts,
+// so `y+r-7` with `r=30` becomes `y+23`.
+fn offset(a: Expr, c: f32) -> Expr {
+    use Expr::{BinaryOp, Number};
+    let c0 = match &a {
+        BinaryOp(BinOp::Add(_, b)) => match **b {
+            Number(c0) => Some(c0),
+            _ => None,
+        },
+        BinaryOp(BinOp::Sub(_, b)) => match **b {
+            Number(c0) => Some(-c0),
+            _ => None,
+        },
+        _ => None,
+    };
+    let (a, c) = match (a, c0) {
+        (BinaryOp(BinOp::Add(a, _)) | BinaryOp(BinOp::Sub(a, _)), Some(c0)) => (*a, c0 + c),
+        (a, _) => (a, c),
+    };
+    if c == 0.0 {
+        a
+    } else if c < 0.0 {
+        BinaryOp(BinOp::Sub(Box::new(a), Box::new(Number(-c))))
+    } else {
+        BinaryOp(BinOp::Add(Box::new(a), Box::new(Number(c))))
+    }
+}
+
+// The value of the program is the value of its last statement, so that one
+// is always kept.
+fn eliminate_dead(stmts: Vec<Statement>) -> Vec<Statement> {
+    let mut live = HashSet::new();
+    let mut keep = vec![false; stmts.len()];
+    for (i, stmt) in stmts.iter().enumerate().rev() {
+        let needed = i == stmts.len() - 1
+            || match stmt {
+                Statement::Assign { var, .. } => live.contains(var),
+                Statement::AssignToArray { vars, .. } | Statement::AssignFromArray { vars, .. } => {
+                    vars.iter().any(|v| live.contains(v))
+                }
+                _ => false,
+            };
+        if needed {
+            keep[i] = true;
+            exprs(stmt).into_iter().for_each(|e| uses(e, &mut live));
+        }
+    }
+    let mut stmts: Vec<Statement> = stmts
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(s, k)| k.then_some(s))
+        .collect();
+    // `d=L(x,y,z)-1,d` has the same value without the final read of `d`.
+    if let [.., Statement::Assign { var, .. }, Statement::Return(e)] = stmts.as_slice() {
+        if **e == Expr::Variable(var.clone()) {
+            stmts.pop();
+        }
+    }
+    stmts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::v3;
+    use crate::eval::make_sdf;
+    use crate::pratt::parse;
+    use crate::scene::library;
+
+    fn count(ast: &Statement) -> usize {
+        match ast {
+            Statement::Sequence(stmts) => stmts.len(),
+            _ => 1,
+        }
+    }
+
+    fn cost(ast: &Statement) -> usize {
+        match ast {
+            Statement::Sequence(stmts) => stmts.iter().map(cost).sum(),
+            s => exprs(s).into_iter().map(size).sum(),
+        }
+    }
+
+    #[test]
+    fn fold_constants() {
+        let mut i = "s=2.5,h=s/2,d=(s+h)/2,L(x,y,z)-d";
+        let ast = optimize(&parse(&mut i));
+        let expected = parse(&mut "L(x,y,z)-1.875");
+        assert_eq!(ast, expected);
+    }
+
+    #[test]
+    fn increment() {
+        let mut i = "i=0,a=nz(x,y,z,.1,i++)*i,b=nz(x,y,z,.1,i++)*i,a+b";
+        let ast = parse(&mut i);
+        let opt = optimize(&ast);
+        let p = v3(1.0, 2.0, 3.0);
+        assert_eq!(make_sdf(&ast, 0.1, 0.2, p), make_sdf(&opt, 0.1, 0.2, p));
+
+        let lowered = lower_increments(&ast);
+        assert!(!format!("{:?}", lowered).contains("Inc"));
+        assert_eq!(make_sdf(&ast, 0.1, 0.2, p), make_sdf(&lowered, 0.1, 0.2, p));
+    }
+
+    #[test]
+    fn common_subexpressions() {
+        let mut i = "a=L(x,y,z)-1,b=L(x,y,z)-1,c=sin(x*y+z*x-y)+cos(x*y+z*x-y),U(a,b,c)";
+        let opt = optimize(&parse(&mut i));
+        let text = format!("{:?}", opt);
+        assert_eq!(text.matches("Length").count(), 1);
+        assert_eq!(text.matches("Mul").count(), 2);
+
+        // Too small to be worth a statement of its own.
+        let mut i = "sin(x*y)+cos(x*y)";
+        let ast = parse(&mut i);
+        assert_eq!(optimize(&ast), ast);
+    }
+
+    #[test]
+    fn dead_stores() {
+        let mut i = "a=sin(x),b=cos(y),a=a*2,c=b,a";
+        let opt = optimize(&parse(&mut i));
+        assert_eq!(count(&opt), 2);
+    }
+
+    #[test]
+    fn examples_agree() {
+        let library = library();
+        let pts: Vec<_> = (0..64)
+            .map(|i| {
+                let t = i as f32;
+                v3(
+                    (t * 1.7).sin() * 20.0,
+                    (t * 0.9).cos() * 20.0,
+                    t * 0.6 - 20.0,
+                )
+            })
+            .collect();
+        for scene in library.iter() {
+            let (name, mut input) = (&scene.name, scene.source.as_str());
+            let p0 = pts[0];
+            // A few of the examples don't parse or run in the interpreter.
+            let ast = match std::panic::catch_unwind(move || {
+                let ast = parse(&mut input);
+                make_sdf(&ast, 0.1, 0.2, p0);
+                ast
+            }) {
+                Ok(ast) => ast,
+                Err(_) => continue,
+            };
+            let opt = optimize(&ast);
+            assert!(cost(&opt) <= cost(&ast), "{} grew", name);
+            for p in pts.iter() {
+                let a = make_sdf(&ast, 0.1, 0.2, *p);
+                let b = make_sdf(&opt, 0.1, 0.2, *p);
+                assert!(
+                    (a - b).abs() <= 1e-4 * a.abs().max(1.0),
+                    "{} at {}: {} != {}",
+                    name,
+                    p,
+                    a,
+                    b
+                );
+            }
+        }
+    }
+}
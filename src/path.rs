@@ -0,0 +1,349 @@
+// A Monte-Carlo path tracer on the same sphere tracing as `march`, for stills
+// with indirect light. Surfaces reflect a `specular` fraction of the light in
+// a lobe around the mirror direction, as wide as `roughness`, and `albedo` of
+// the rest diffusely. Point and spot lights are spheres of `light_radius`,
+// and the sky, or the environment light, lights whatever the paths escape
+// to. Every camera ray follows `samples` paths of at most `max_depth`
+// bounces, ended early by Russian roulette, and `denoise` smooths what noise
+// is left between pixels that see the same surface.
+use crate::core::{Light, LightKind, Lum};
+use crate::march::{normal, reflect, View, EPSILON};
+use crate::sampling::random;
+use glam::Vec3;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+// How far off a surface its bounces start.
+const OFFSET: f32 = 10.0 * EPSILON;
+
+// Bounces before Russian roulette can end a path.
+const ROULETTE: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathTracer {
+    pub samples: u32,
+    pub max_depth: u32,
+    pub albedo: f32,
+    pub specular: f32,
+    pub roughness: f32,
+    // The brightness of the sky straight up, it fades to half at the horizon.
+    pub sky: f32,
+    pub light_radius: f32,
+    pub denoise: bool,
+}
+
+impl Default for PathTracer {
+    fn default() -> Self {
+        PathTracer {
+            samples: 64,
+            max_depth: 6,
+            albedo: 0.7,
+            specular: 0.05,
+            roughness: 0.2,
+            sky: 0.5,
+            light_radius: 5.0,
+            denoise: true,
+        }
+    }
+}
+
+// The random numbers of the paths through a point of the image, the same
+// every render.
+struct Rng {
+    x: f32,
+    y: f32,
+    i: u32,
+}
+
+impl Rng {
+    fn next(&mut self) -> f32 {
+        self.i += 1;
+        random(self.x, self.y, self.i)
+    }
+}
+
+// Two directions perpendicular to `n` and each other.
+fn basis(n: Vec3) -> (Vec3, Vec3) {
+    let a = if n.x.abs() > 0.9 { Vec3::Y } else { Vec3::X };
+    let u = n.cross(a).normalize();
+    (u, n.cross(u))
+}
+
+// A direction within `cos_max` of `n`, uniform over the solid angle.
+fn cone(n: Vec3, cos_max: f32, rng: &mut Rng) -> Vec3 {
+    let cos = 1.0 - rng.next() * (1.0 - cos_max);
+    let sin = (1.0 - cos * cos).max(0.0).sqrt();
+    let phi = 2.0 * PI * rng.next();
+    let (u, v) = basis(n);
+    u * sin * phi.cos() + v * sin * phi.sin() + n * cos
+}
+
+// A direction around `n` with probability proportional to its cosine.
+fn cosine(n: Vec3, rng: &mut Rng) -> Vec3 {
+    let r = rng.next().sqrt();
+    let phi = 2.0 * PI * rng.next();
+    let (u, v) = basis(n);
+    u * r * phi.cos() + v * r * phi.sin() + n * (1.0 - r * r).max(0.0).sqrt()
+}
+
+impl PathTracer {
+    // The size of the sphere a light is, point and spot lights are given
+    // `light_radius`.
+    fn radius(&self, light: &Light) -> Option<f32> {
+        match light.kind {
+            LightKind::Point | LightKind::Spot => Some(self.light_radius),
+            LightKind::Sphere => Some(light.radius),
+            LightKind::Directional | LightKind::Environment => None,
+        }
+    }
+
+    // How bright a light's sphere is, chosen so that a white surface facing
+    // it from the origin is as bright as the light's intensity.
+    fn emission(&self, light: &Light, radius: f32) -> Lum {
+        let d = light.position.length().max(radius);
+        light.intensity * (d / radius).powi(2)
+    }
+
+    // The environment light if there is one, otherwise the sky.
+    fn sky(&self, view: &View, rd: Vec3) -> Lum {
+        let environment = view.environment.is_some()
+            && view
+                .lights
+                .iter()
+                .any(|light| light.kind == LightKind::Environment);
+        if environment {
+            view.escaped(rd, 1)
+        } else {
+            self.sky * (0.5 + 0.5 * rd.y)
+        }
+    }
+
+    // The light reaching `p` on a surface with normal `n` straight from the
+    // lights, sampling a direction towards each and checking it isn't in
+    // shadow. The environment is only reached by bouncing.
+    fn direct(&self, view: &View, p: Vec3, n: Vec3, rng: &mut Rng) -> Lum {
+        let mut lum = 0.0;
+        for light in view.lights {
+            if light.kind == LightKind::Directional {
+                let l = light.position.normalize();
+                if n.dot(l) > 0.0 && view.hit(view.sdf, p, l).is_none() {
+                    lum += light.intensity * n.dot(l);
+                }
+                continue;
+            }
+            let Some(radius) = self.radius(light) else {
+                continue;
+            };
+            let to_light = light.position - p;
+            let d = to_light.length();
+            if d <= radius {
+                continue;
+            }
+            let cos_max = (1.0 - (radius / d).powi(2)).sqrt();
+            let l = cone(to_light / d, cos_max, rng);
+            let cos = n.dot(l);
+            if cos <= 0.0 {
+                continue;
+            }
+            if view.hit(view.sdf, p, l).is_some_and(|t| t < d - radius) {
+                continue;
+            }
+            // The Lambertian 1 / pi over the pdf 1 / (2 pi (1 - cos_max)).
+            lum += self.emission(light, radius)
+                * light.cone(p)
+                * light.attenuate(p)
+                * cos
+                * 2.0
+                * (1.0 - cos_max);
+        }
+        lum
+    }
+
+    // The light from a light's sphere if the ray hits one before `t`.
+    fn lights_hit(&self, view: &View, ro: Vec3, rd: Vec3, t: f32) -> Lum {
+        let mut lum = 0.0;
+        for light in view.lights {
+            let Some(radius) = self.radius(light) else {
+                continue;
+            };
+            let oc = ro - light.position;
+            let b = oc.dot(rd);
+            let h = b * b - oc.length_squared() + radius * radius;
+            if h > 0.0 && -b - h.sqrt() > 0.0 && -b - h.sqrt() < t {
+                lum += self.emission(light, radius) * light.cone(ro) * light.attenuate(ro);
+            }
+        }
+        lum
+    }
+
+    // The light along one path from the camera that first hits the surface
+    // `t` along `rd`.
+    fn path(&self, view: &View, rd: Vec3, t: f32, rng: &mut Rng) -> Lum {
+        let (mut ro, mut rd, mut t) = (view.camera_pos, rd, t);
+        let (mut lum, mut throughput) = (0.0, 1.0);
+        for depth in 0..self.max_depth {
+            let p = ro + rd * t;
+            let n = normal(p, view.sdf);
+            ro = p + n * OFFSET;
+            if rng.next() < self.specular {
+                // Diffuse bounces get the lights from `direct`, specular
+                // ones only if they hit them.
+                let lobe = cone(Vec3::Z, -1.0, rng) * self.roughness * rng.next().cbrt();
+                rd = (reflect(rd, n) + lobe).normalize();
+                if rd.dot(n) <= 0.0 {
+                    break;
+                }
+                let hit = view.hit(view.sdf, ro, rd);
+                lum += throughput * self.lights_hit(view, ro, rd, hit.unwrap_or(f32::INFINITY));
+                match hit {
+                    Some(hit) => t = hit,
+                    None => {
+                        lum += throughput * self.sky(view, rd);
+                        break;
+                    }
+                }
+            } else {
+                throughput *= self.albedo * view.materials.at(p).1;
+                lum += throughput * self.direct(view, ro, n, rng);
+                rd = cosine(n, rng);
+                match view.hit(view.sdf, ro, rd) {
+                    Some(hit) => t = hit,
+                    None => {
+                        lum += throughput * self.sky(view, rd);
+                        break;
+                    }
+                }
+            }
+            if depth >= ROULETTE {
+                let survive = throughput.min(0.95);
+                if rng.next() >= survive {
+                    break;
+                }
+                throughput /= survive;
+            }
+        }
+        lum
+    }
+
+    // The average of `samples` paths through the point (x, y) of the image
+    // along `rd`, and how far the camera ray went before it hit something.
+    pub fn trace(&self, view: &View, rd: Vec3, x: f32, y: f32) -> (Lum, f32) {
+        let Some(t) = view.hit(view.sdf, view.camera_pos, rd) else {
+            return (view.background, f32::INFINITY);
+        };
+        let mut rng = Rng { x, y, i: 0 };
+        let samples = self.samples.max(1);
+        let lum: Lum = (0..samples).map(|_| self.path(view, rd, t, &mut rng)).sum();
+        (lum / samples as f32, t)
+    }
+}
+
+// Averages each pixel with those within two pixels that are on the same
+// surface, judging by the distance and normal where their middle rays hit.
+pub fn denoise(view: &View, lum: &[Lum]) -> Vec<Lum> {
+    let (w, h) = (view.width as i32, view.height as i32);
+    assert_eq!(lum.len(), (w * h) as usize);
+    let guides: Vec<Option<(f32, Vec3)>> = (0..w * h)
+        .into_par_iter()
+        .map(|i| {
+            let (ro, rd) = view.camera_ray((i % w) as f32, (i / w) as f32);
+            view.hit(view.sdf, ro, rd)
+                .map(|t| (t, normal(ro + rd * t, view.sdf)))
+        })
+        .collect();
+    (0..w * h)
+        .into_par_iter()
+        .map(|i| {
+            let (x, y) = (i % w, i / w);
+            let (mut sum, mut weight) = (0.0, 0.0);
+            for dy in -2..=2 {
+                for dx in -2..=2 {
+                    let (u, v) = (x + dx, y + dy);
+                    if u < 0 || v < 0 || u >= w || v >= h {
+                        continue;
+                    }
+                    let j = (v * w + u) as usize;
+                    let same = match (guides[i as usize], guides[j]) {
+                        (None, None) => 1.0,
+                        (Some((t, n)), Some((s, m))) => {
+                            let depth = ((t - s) / (0.05 * t)).powi(2);
+                            n.dot(m).max(0.0).powi(8) * (-depth).exp()
+                        }
+                        _ => 0.0,
+                    };
+                    let k = same * (-((dx * dx + dy * dy) as f32) / 4.5).exp();
+                    sum += k * lum[j];
+                    weight += k;
+                }
+            }
+            sum / weight
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{v3, Sdf, ZERO3};
+
+    fn view<'a>(sdf: &'a Sdf, lights: &'a [Light], path: PathTracer) -> View<'a> {
+        let mut view = View::new(sdf, v3(0.0, 0.0, -20.0), ZERO3, lights, 0.75, 16, 12);
+        view.path = Some(path);
+        view
+    }
+
+    #[test]
+    fn furnace() {
+        // A floor under a sky and no lights is `albedo` times the sky it
+        // sees, nothing bounces back to it.
+        let sdf: Sdf = Box::new(|p| p.y + 5.0);
+        let path = PathTracer {
+            samples: 2000,
+            specular: 0.0,
+            sky: 1.0,
+            ..Default::default()
+        };
+        let view = view(&sdf, &[], path);
+        let (lum, t) = path.trace(&view, v3(0.0, -1.0, 1.0).normalize(), 3.0, 4.0);
+        assert!(t.is_finite());
+        // The upper hemisphere averages 0.5 + 0.5 * 2/3 with cosine weights.
+        let expected = path.albedo * (0.5 + 0.5 * 2.0 / 3.0);
+        assert!((lum - expected).abs() < 0.03, "{} {}", lum, expected);
+    }
+
+    #[test]
+    fn lights_and_shadows() {
+        let sdf: Sdf = Box::new(|p| p.length() - 5.0);
+        let lights = [Light::new(v3(0.0, 0.0, -50.0), 1.0)];
+        let path = PathTracer {
+            samples: 16,
+            sky: 0.0,
+            ..Default::default()
+        };
+        let view = view(&sdf, &lights, path);
+        // Facing the light the ball is about as bright as its albedo, a
+        // little brighter for being closer to the light than the origin is.
+        let (lum, _) = path.trace(&view, Vec3::Z, 8.0, 6.0);
+        let expected = (1.0 - path.specular) * path.albedo * (50.0f32 / 45.0).powi(2);
+        assert!((lum - expected).abs() < 0.05, "{} {}", lum, expected);
+        assert_eq!(path.trace(&view, Vec3::Z, 8.0, 6.0).0, lum);
+        // With the light behind it the side facing the camera is dark.
+        let behind = [Light::new(v3(0.0, 0.0, 50.0), 1.0)];
+        let mut view = view;
+        view.lights = &behind;
+        assert!(path.trace(&view, Vec3::Z, 8.0, 6.0).0 < 0.01);
+        assert_eq!(path.trace(&view, Vec3::Y, 8.0, 0.0).0, 0.75);
+    }
+
+    #[test]
+    fn denoising() {
+        let sdf: Sdf = Box::new(|p| p.length() - 5.0);
+        let view = view(&sdf, &[], PathTracer::default());
+        let noisy: Vec<Lum> = (0..16 * 12).map(|i| 0.5 + (i % 2) as f32 * 0.2).collect();
+        let smooth = denoise(&view, &noisy);
+        let spread = |lum: &[Lum]| lum.iter().map(|l| (l - 0.6).abs()).sum::<f32>();
+        assert!(spread(&smooth) < spread(&noisy) / 2.0);
+    }
+}
@@ -0,0 +1,374 @@
+// Filters run on a finished render, in order, before it is written. `bloom`,
+// `vignette` and `grain` work on the linear values. `dither` and `halftone`
+// make a 1-bit image for printing: they tone map the frame first, and after
+// them it is only black and white. In a scene file they are a list, e.g.
+//
+//     [[settings.post]]
+//     filter = "bloom"
+//     threshold = 0.9
+//
+//     [[settings.post]]
+//     filter = "halftone"
+//     cell = 6.0
+use crate::core::Lum;
+use crate::sampling::random;
+use crate::tonemap::ToneMap;
+use image::ImageResult;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use std::path::Path;
+
+// Light brighter than `threshold` spreads over about `radius` pixels, adding
+// `strength` times itself.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bloom {
+    pub threshold: f32,
+    pub strength: f32,
+    pub radius: f32,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom {
+            threshold: 1.0,
+            strength: 0.5,
+            radius: 8.0,
+        }
+    }
+}
+
+// Darkens towards the corners, which are `strength` darker.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Vignette {
+    pub strength: f32,
+}
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Vignette { strength: 0.5 }
+    }
+}
+
+// Noise of up to `amount` either way, different for each `seed`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Grain {
+    pub amount: f32,
+    pub seed: u32,
+}
+
+impl Default for Grain {
+    fn default() -> Self {
+        Grain {
+            amount: 0.05,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Method {
+    // Thresholds from an 8 by 8 Bayer matrix.
+    #[default]
+    Ordered,
+    // Floyd-Steinberg error diffusion.
+    Diffusion,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Dither {
+    pub method: Method,
+}
+
+// Round dots in a screen of `cell` pixel squares turned by `angle` degrees.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Halftone {
+    pub cell: f32,
+    pub angle: f32,
+}
+
+impl Default for Halftone {
+    fn default() -> Self {
+        Halftone {
+            cell: 8.0,
+            angle: 45.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "filter", rename_all = "lowercase")]
+pub enum Filter {
+    Bloom(Bloom),
+    Vignette(Vignette),
+    Grain(Grain),
+    Dither(Dither),
+    Halftone(Halftone),
+}
+
+// A render and the tone map that is still to be applied to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Lum>,
+    pub tone: ToneMap,
+}
+
+// Three box blurs of `radius` along rows, or columns when `stride` is the
+// width, are close to a Gaussian.
+fn blur(pixels: &mut [f32], len: usize, stride: usize, radius: usize) {
+    let lines = pixels.len() / len;
+    let step = if stride == 1 { len } else { 1 };
+    let mut line = vec![0.0; len];
+    for l in 0..lines {
+        let at = |i: usize| l * step + i * stride;
+        for _ in 0..3 {
+            for (i, x) in line.iter_mut().enumerate() {
+                *x = pixels[at(i)];
+            }
+            let mut sum: f32 = line[..=radius.min(len - 1)].iter().sum();
+            for i in 0..len {
+                let n = i.min(radius) + radius.min(len - 1 - i) + 1;
+                pixels[at(i)] = sum / n as f32;
+                if i + radius + 1 < len {
+                    sum += line[i + radius + 1];
+                }
+                if i >= radius {
+                    sum -= line[i - radius];
+                }
+            }
+        }
+    }
+}
+
+// The threshold at (x, y) of the 8 by 8 Bayer matrix, each of 0.5 / 64 to
+// 63.5 / 64 once.
+fn bayer(x: u32, y: u32) -> f32 {
+    let mut m = 0;
+    for bit in 0..3 {
+        let (x, y) = ((x >> bit) & 1, (y >> bit) & 1);
+        m += (2 * (x ^ y) + y) << (2 * (2 - bit));
+    }
+    (m as f32 + 0.5) / 64.0
+}
+
+impl Frame {
+    pub fn new(width: u32, height: u32, pixels: Vec<Lum>, tone: ToneMap) -> Self {
+        assert_eq!(pixels.len(), (width * height) as usize);
+        Frame {
+            width,
+            height,
+            pixels,
+            tone,
+        }
+    }
+
+    pub fn post(self, filters: &[Filter]) -> Self {
+        filters
+            .iter()
+            .fold(self, |frame, filter| frame.filter(filter))
+    }
+
+    pub fn filter(mut self, filter: &Filter) -> Self {
+        let (w, h) = (self.width as usize, self.height as usize);
+        match *filter {
+            Filter::Bloom(bloom) => {
+                let mut bright: Vec<f32> = self
+                    .pixels
+                    .iter()
+                    .map(|&l| (l - bloom.threshold).max(0.0))
+                    .collect();
+                let radius = bloom.radius.max(0.0) as usize;
+                blur(&mut bright, w, 1, radius);
+                blur(&mut bright, h, w, radius);
+                for (l, b) in self.pixels.iter_mut().zip(bright) {
+                    *l += bloom.strength * b;
+                }
+            }
+            Filter::Vignette(vignette) => {
+                let (cx, cy) = (0.5 * w as f32, 0.5 * h as f32);
+                let corner = (cx * cx + cy * cy).sqrt();
+                for (i, l) in self.pixels.iter_mut().enumerate() {
+                    let x = (i % w) as f32 + 0.5 - cx;
+                    let y = (i / w) as f32 + 0.5 - cy;
+                    let d = (x * x + y * y) / (corner * corner);
+                    *l *= 1.0 - vignette.strength * d;
+                }
+            }
+            Filter::Grain(grain) => {
+                for (i, l) in self.pixels.iter_mut().enumerate() {
+                    let noise = random((i % w) as f32, (i / w) as f32, grain.seed);
+                    *l = (*l + grain.amount * (2.0 * noise - 1.0)).max(0.0);
+                }
+            }
+            Filter::Dither(dither) => {
+                self = self.display();
+                match dither.method {
+                    Method::Ordered => {
+                        for (i, l) in self.pixels.iter_mut().enumerate() {
+                            let t = bayer((i % w) as u32 % 8, (i / w) as u32 % 8);
+                            *l = if *l > t { 1.0 } else { 0.0 };
+                        }
+                    }
+                    Method::Diffusion => {
+                        let p = &mut self.pixels;
+                        for y in 0..h {
+                            for x in 0..w {
+                                let i = y * w + x;
+                                let old = p[i];
+                                p[i] = if old > 0.5 { 1.0 } else { 0.0 };
+                                let error = old - p[i];
+                                if x + 1 < w {
+                                    p[i + 1] += error * 7.0 / 16.0;
+                                }
+                                if y + 1 < h {
+                                    if x > 0 {
+                                        p[i + w - 1] += error * 3.0 / 16.0;
+                                    }
+                                    p[i + w] += error * 5.0 / 16.0;
+                                    if x + 1 < w {
+                                        p[i + w + 1] += error / 16.0;
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            Filter::Halftone(halftone) => {
+                self = self.display();
+                let (sin, cos) = halftone.angle.to_radians().sin_cos();
+                let cell = halftone.cell.max(1.0);
+                for (i, l) in self.pixels.iter_mut().enumerate() {
+                    let (x, y) = ((i % w) as f32 + 0.5, (i / w) as f32 + 0.5);
+                    let u = (x * cos + y * sin) / cell;
+                    let v = (y * cos - x * sin) / cell;
+                    let (u, v) = (u - u.round(), v - v.round());
+                    // Black dots in the middles of the cells up to half ink,
+                    // then white dots in their corners, each the right area.
+                    let ink = 1.0 - *l;
+                    let black = if ink <= 0.5 {
+                        u.hypot(v) < (ink / PI).sqrt()
+                    } else {
+                        (0.5 - u.abs()).hypot(0.5 - v.abs()) >= ((1.0 - ink) / PI).sqrt()
+                    };
+                    *l = if black { 0.0 } else { 1.0 };
+                }
+            }
+        }
+        self
+    }
+
+    // The frame tone mapped, which leaves nothing to do but to quantize.
+    fn display(self) -> Self {
+        let pixels = self.pixels.iter().map(|&l| self.tone.map(l)).collect();
+        Frame {
+            pixels,
+            tone: ToneMap {
+                bits: self.tone.bits,
+                ..Default::default()
+            },
+            ..self
+        }
+    }
+
+    pub fn to_u8(&self) -> Vec<u8> {
+        self.tone.to_u8(&self.pixels)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> ImageResult<()> {
+        self.tone.save(path, self.width, self.height, &self.pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tonemap::Operator;
+
+    fn frame(width: u32, height: u32, lum: Lum) -> Frame {
+        Frame::new(
+            width,
+            height,
+            vec![lum; (width * height) as usize],
+            ToneMap::default(),
+        )
+    }
+
+    #[test]
+    fn linear() {
+        // A bright pixel glows onto its neighbours and keeps its light.
+        let mut spot = frame(21, 21, 0.0);
+        spot.pixels[10 * 21 + 10] = 10.0;
+        let bloom = Bloom {
+            threshold: 1.0,
+            strength: 1.0,
+            radius: 2.0,
+        };
+        let bloomed = spot.clone().filter(&Filter::Bloom(bloom));
+        assert!(bloomed.pixels[10 * 21 + 12] > 0.0);
+        assert!(bloomed.pixels[10 * 21 + 12] > bloomed.pixels[10 * 21 + 13]);
+        assert_eq!(bloomed.pixels[0], 0.0);
+        let total: f32 = bloomed.pixels.iter().sum();
+        assert!((total - 19.0).abs() < 1e-3, "{}", total);
+        // Nothing over the threshold, nothing changes.
+        let dim = frame(8, 8, 0.5);
+        assert_eq!(dim.clone().filter(&Filter::Bloom(bloom)), dim);
+
+        let vignette = frame(9, 9, 1.0).filter(&Filter::Vignette(Vignette { strength: 0.5 }));
+        assert_eq!(vignette.pixels[4 * 9 + 4], 1.0);
+        assert!(vignette.pixels[0] > 0.5 && vignette.pixels[0] < 0.7);
+
+        let grain = Filter::Grain(Grain::default());
+        let grainy = frame(16, 16, 0.5).filter(&grain);
+        assert_eq!(grainy, frame(16, 16, 0.5).filter(&grain));
+        assert!(grainy.pixels.iter().all(|l| (l - 0.5).abs() < 0.051));
+        let mean: f32 = grainy.pixels.iter().sum::<f32>() / 256.0;
+        assert!((mean - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn one_bit() {
+        let mut thresholds: Vec<f32> = (0..64).map(|i| bayer(i % 8, i / 8)).collect();
+        thresholds.sort_by(f32::total_cmp);
+        assert!((0..64).all(|i| thresholds[i] == (i as f32 + 0.5) / 64.0));
+        // Each keeps about as much white as there was grey, tone mapped.
+        let tone = ToneMap {
+            operator: Operator::Reinhard,
+            ..Default::default()
+        };
+        let filters = [
+            Filter::Dither(Dither {
+                method: Method::Ordered,
+            }),
+            Filter::Dither(Dither {
+                method: Method::Diffusion,
+            }),
+            Filter::Halftone(Halftone::default()),
+        ];
+        for filter in filters {
+            for lum in [0.0, 0.25, 1.0, 3.0] {
+                let grey = Frame {
+                    tone,
+                    ..frame(64, 64, lum)
+                };
+                let bits = grey.filter(&filter);
+                assert!(bits.pixels.iter().all(|&l| l == 0.0 || l == 1.0));
+                assert_eq!(bits.tone, ToneMap::default());
+                let white = bits.pixels.iter().sum::<f32>() / 4096.0;
+                assert!(
+                    (white - tone.map(lum)).abs() < 0.05,
+                    "{:?} {}",
+                    filter,
+                    white
+                );
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::ast::*;
 use crate::lexer::{AssignOp, Lexer, Op, Token};
+use std::collections::HashSet;
 
 pub fn parse(i: &mut &str) -> Statement {
     let mut lexer = Lexer::new(i);
@@ -8,8 +9,11 @@ pub fn parse(i: &mut &str) -> Statement {
 
 fn sequence(lexer: &mut Lexer) -> Statement {
     let mut statements = Vec::new();
+    // The variables whose value depends on the point, which `scale` can't
+    // move.
+    let mut moving: HashSet<String> = ["x", "y", "z"].map(String::from).into();
     loop {
-        let s = statement(lexer);
+        let s = desugar(statement(lexer), &mut moving);
         statements.push(s);
         match lexer.peek() {
             Token::Semicolon | Token::Comma => {
@@ -73,6 +77,99 @@ fn statement(lexer: &mut Lexer) -> Statement {
     }
 }
 
+fn desugar(stmt: Statement, moving: &mut HashSet<String>) -> Statement {
+    let stmt = match stmt {
+        Statement::Assign { var, rhs } => Statement::Assign {
+            var,
+            rhs: Box::new(unscale(&rhs, moving)),
+        },
+        Statement::AssignToArray { vars, rhs } => Statement::AssignToArray {
+            vars,
+            rhs: Box::new(unscale(&rhs, moving)),
+        },
+        Statement::AssignFromArray { vars, rhs } => Statement::AssignFromArray {
+            vars,
+            rhs: rhs.iter().map(|e| unscale(e, moving)).collect(),
+        },
+        Statement::Return(e) => Statement::Return(Box::new(unscale(&e, moving))),
+        s => s,
+    };
+    let bound: Vec<(&String, bool)> = match &stmt {
+        Statement::Assign { var, rhs } => vec![(var, reads(rhs, moving))],
+        Statement::AssignToArray { vars, rhs } => {
+            vars.iter().map(|v| (v, reads(rhs, moving))).collect()
+        }
+        Statement::AssignFromArray { vars, rhs } => vars
+            .iter()
+            .zip(rhs)
+            .map(|(v, e)| (v, reads(e, moving)))
+            .collect(),
+        _ => vec![],
+    };
+    for (v, depends) in bound {
+        if depends {
+            moving.insert(v.clone());
+        } else if v != "x" && v != "y" && v != "z" {
+            moving.remove(v);
+        }
+    }
+    stmt
+}
+
+fn reads(e: &Expr, vars: &HashSet<String>) -> bool {
+    let mut found = false;
+    walk(e, &mut |e| match e {
+        Expr::Variable(v) | Expr::Assign(AssignExpr::Inc(v) | AssignExpr::Dec(v)) => {
+            found |= vars.contains(v)
+        }
+        _ => {}
+    });
+    found
+}
+
+fn walk(e: &Expr, f: &mut impl FnMut(&Expr)) {
+    f(e);
+    children(e).into_iter().for_each(|c| walk(c, f));
+}
+
+// `scale(s, d)` is the distance `d` to a shape scaled by `s`: `d` with `x`,
+// `y` and `z` divided by `s`, times `s` so it's still a distance. That only
+// works when the shape is written out in `d`, a variable holding a distance
+// computed earlier can't be scaled after the fact.
+fn unscale(e: &Expr, moving: &HashSet<String>) -> Expr {
+    let e = map_children(e, |c| unscale(c, moving));
+    let Expr::Function {
+        name: FunctionName::Scale,
+        args,
+    } = &e
+    else {
+        return e;
+    };
+    let [s, d] = &args[..] else {
+        return e;
+    };
+    walk(s, &mut |e| {
+        if let Expr::Assign(_) = e {
+            panic!("scale(s, d) copies s, so s can't use ++ or --");
+        }
+    });
+    walk(d, &mut |e| match e {
+        Expr::Variable(v) if v != "x" && v != "y" && v != "z" && moving.contains(v) => {
+            panic!("scale(s, d) can't scale {v}, write its shape out in x, y and z")
+        }
+        _ => {}
+    });
+    fn shrink(e: &Expr, s: &Expr) -> Expr {
+        match e {
+            Expr::Variable(v) if v == "x" || v == "y" || v == "z" => {
+                Expr::BinaryOp(BinOp::Div(Box::new(e.clone()), Box::new(s.clone())))
+            }
+            e => map_children(e, |c| shrink(c, s)),
+        }
+    }
+    Expr::BinaryOp(BinOp::Mul(Box::new(shrink(d, s)), Box::new(s.clone())))
+}
+
 fn assign(lhs: Token, lexer: &mut Lexer) -> Statement {
     let v = match lhs {
         Token::Variable(x) => x,
@@ -296,7 +393,6 @@ fn postfix_binding_power(op: Token) -> Option<u8> {
 }
 
 #[cfg(test)]
-
 mod tests {
     use super::*;
 
@@ -385,6 +481,21 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn scale() {
+        let p = crate::core::v3(3.0, 0.5, 0.0);
+        let d = |mut i: &str| crate::eval::make_sdf(&parse(&mut i), 0.0, 0.0, p);
+        assert_eq!(d("scale(2,bx3(x,y,z,1))"), 1.0);
+        assert_eq!(d("[x,y,z]=scale(x,y,z,2),bx3(x,y,z,1)*2"), 1.0);
+        assert_eq!(d("s=4,scale(s/2,L(x,y,z)-1)"), p.length() - 2.0);
+        assert_eq!(d("r=1,scale(2,bx3(x,y,z,r))"), 1.0);
+        // `d` was computed at the unscaled point, there's no shape left to scale.
+        let rejected = |i: &'static str| std::panic::catch_unwind(|| d(i)).is_err();
+        assert!(rejected("d=bx3(x,y,z,1),scale(2,d)"));
+        assert!(rejected("x-=1,d=bx3(x,y,z,1),scale(2,d+1)"));
+        assert!(rejected("i=1,scale(i++,bx3(x,y,z,1))"));
+    }
+
     #[test]
     fn sponge() {
         let mut i = "k(r,-U(@xyz{bx2($,$$,9),}))";
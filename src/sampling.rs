@@ -0,0 +1,144 @@
+// Where in a pixel its rays go and how they are combined. An `aa` by `aa`
+// pixel gets one ray in each cell of a grid over the reconstruction filter,
+// jittered inside the cell unless `jitter` is off, and the rays are weighted
+// by the filter. With `adaptive` set only the pixels that differ from a
+// neighbour by more than it, or are on a silhouette, get more than one ray.
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Filter {
+    #[default]
+    Box,
+    Tent,
+    Mitchell,
+}
+
+impl Filter {
+    // How far from the middle of a pixel, in pixels, rays count.
+    pub fn radius(self) -> f32 {
+        match self {
+            Filter::Box => 0.5,
+            Filter::Tent => 1.0,
+            Filter::Mitchell => 2.0,
+        }
+    }
+
+    pub fn weight(self, x: f32) -> f32 {
+        let x = x.abs();
+        match self {
+            Filter::Box => (x <= 0.5) as u8 as f32,
+            Filter::Tent => (1.0 - x).max(0.0),
+            // Mitchell-Netravali with B = C = 1/3.
+            Filter::Mitchell => {
+                let (b, c) = (1.0 / 3.0, 1.0 / 3.0);
+                let w = if x < 1.0 {
+                    (12.0 - 9.0 * b - 6.0 * c) * x * x * x
+                        + (-18.0 + 12.0 * b + 6.0 * c) * x * x
+                        + (6.0 - 2.0 * b)
+                } else if x < 2.0 {
+                    (-b - 6.0 * c) * x * x * x
+                        + (6.0 * b + 30.0 * c) * x * x
+                        + (-12.0 * b - 48.0 * c) * x
+                        + (8.0 * b + 24.0 * c)
+                } else {
+                    0.0
+                };
+                w / 6.0
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Sampling {
+    pub filter: Filter,
+    pub jitter: bool,
+    pub adaptive: Option<f32>,
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Sampling {
+            filter: Filter::Box,
+            jitter: true,
+            adaptive: None,
+        }
+    }
+}
+
+// A number in [0, 1) that only depends on the arguments, so renders are
+// the same every time.
+pub fn random(x: f32, y: f32, i: u32) -> f32 {
+    let mut h = x.to_bits() ^ y.to_bits().rotate_left(16) ^ i.wrapping_mul(0x9e37_79b9);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7feb_352d);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846c_a68b);
+    h ^= h >> 16;
+    (h >> 8) as f32 / (1 << 24) as f32
+}
+
+impl Sampling {
+    // The offsets from the middle of pixel (x, y) of its `aa * aa` rays and
+    // their weights. A single ray goes through the middle.
+    pub fn samples(&self, x: f32, y: f32, aa: u32) -> Vec<(f32, f32, f32)> {
+        if aa <= 1 {
+            return vec![(0.0, 0.0, 1.0)];
+        }
+        let r = self.filter.radius();
+        let n = aa as f32;
+        let mut samples = Vec::with_capacity((aa * aa) as usize);
+        for m in 0..aa {
+            for k in 0..aa {
+                let i = m * aa + k;
+                let (u, v) = if self.jitter {
+                    (random(x, y, 2 * i), random(x, y, 2 * i + 1))
+                } else {
+                    (0.5, 0.5)
+                };
+                let dx = ((m as f32 + u) / n * 2.0 - 1.0) * r;
+                let dy = ((k as f32 + v) / n * 2.0 - 1.0) * r;
+                samples.push((dx, dy, self.filter.weight(dx) * self.filter.weight(dy)));
+            }
+        }
+        samples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters() {
+        for filter in [Filter::Box, Filter::Tent, Filter::Mitchell] {
+            assert_eq!(filter.weight(filter.radius() + 0.01), 0.0);
+            assert!(filter.weight(0.0) >= filter.weight(0.4));
+        }
+        // The Mitchell filter has a negative lobe and sums to one.
+        assert!(Filter::Mitchell.weight(1.5) < 0.0);
+        let sum: f32 = (-200..=200)
+            .map(|i| Filter::Mitchell.weight(i as f32 / 100.0) / 100.0)
+            .sum();
+        assert!((sum - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn stratified() {
+        let sampling = Sampling::default();
+        let samples = sampling.samples(3.0, 4.0, 4);
+        assert_eq!(samples.len(), 16);
+        // One ray in every cell of the grid.
+        for (i, (dx, dy, w)) in samples.iter().enumerate() {
+            let (m, k) = ((i / 4) as f32, (i % 4) as f32);
+            assert!((-0.5 + m / 4.0..-0.5 + (m + 1.0) / 4.0).contains(dx));
+            assert!((-0.5 + k / 4.0..-0.5 + (k + 1.0) / 4.0).contains(dy));
+            assert_eq!(*w, 1.0);
+        }
+        assert_eq!(samples, sampling.samples(3.0, 4.0, 4));
+        assert_ne!(samples, sampling.samples(4.0, 4.0, 4));
+        assert_eq!(sampling.samples(3.0, 4.0, 1), vec![(0.0, 0.0, 1.0)]);
+    }
+}
@@ -0,0 +1,627 @@
+// A scene file holds everything needed to render a picture: the DSL source,
+// the values of its named parameters, the camera, lights, background and
+// render settings. They are TOML or JSON, chosen by the file extension, and a
+// directory of them is a `Library` with the scenes named after the files.
+// Instead of, or as well as, a source a scene can have `objects`, see
+// `arrow::graph`.
+use crate::ast::Statement;
+use crate::bound::{Bound, Bounding};
+use crate::core::{v3, Light, Sdf, ZERO3};
+use crate::environment::Environment;
+use crate::eval::{interpret, outputs_with};
+use crate::fog::{Atmosphere, Fog};
+use crate::graph::{self, Ids, Object};
+use crate::march::{render_linear, View};
+use crate::material::{Bounces, Material, Materials};
+use crate::path::PathTracer;
+use crate::post::{Filter, Frame};
+use crate::pratt::parse;
+use crate::sampling::Sampling;
+use crate::sdfs::compiled;
+use crate::tonemap::ToneMap;
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// The scenes that come with arrow.
+pub const SCENES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/scenes");
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SceneFile {
+    // The file name without its extension.
+    #[serde(skip)]
+    pub name: String,
+    #[serde(default)]
+    pub source: String,
+    #[serde(default = "default_params")]
+    pub params: BTreeMap<String, f32>,
+    #[serde(default = "default_background")]
+    pub background: f32,
+    pub camera: Camera,
+    #[serde(default = "default_lights")]
+    pub lights: Vec<Light>,
+    // The first that applies to a point is its material.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub materials: Vec<Material>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub objects: Vec<Object>,
+    #[serde(default)]
+    pub settings: Settings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Camera {
+    pub position: Vec3,
+    #[serde(default)]
+    pub look_at: Vec3,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub width: u32,
+    pub height: u32,
+    pub aa: u32,
+    pub sampling: Sampling,
+    pub bounces: Bounces,
+    pub fog: Option<Fog>,
+    pub tone: ToneMap,
+    // Path trace the scene with these settings instead of lighting it directly.
+    pub path: Option<PathTracer>,
+    // Run on the finished image, see `arrow::post`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub post: Vec<Filter>,
+    // Where the marcher looks for the surface, see `arrow::bound`.
+    pub bound: Bounding,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            width: 1024,
+            height: 768,
+            aa: 3,
+            sampling: Sampling::default(),
+            bounces: Bounces::default(),
+            fog: None,
+            tone: ToneMap::default(),
+            path: None,
+            post: Vec::new(),
+            bound: Bounding::default(),
+        }
+    }
+}
+
+fn default_params() -> BTreeMap<String, f32> {
+    BTreeMap::from([("a0".to_string(), 0.1), ("a1".to_string(), 0.2)])
+}
+
+fn default_background() -> f32 {
+    0.75
+}
+
+fn default_lights() -> Vec<Light> {
+    vec![
+        Light::new(v3(0.0, 0.0, -50.0), 1.0),
+        Light::new(v3(0.0, 10.0, 40.0), 1.0),
+    ]
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(PathBuf, std::io::Error),
+    Toml(PathBuf, toml::de::Error),
+    Json(PathBuf, serde_json::Error),
+    Extension(PathBuf),
+    Invalid(PathBuf, String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            Error::Toml(path, e) => write!(f, "{}: {}", path.display(), e),
+            Error::Json(path, e) => write!(f, "{}: {}", path.display(), e),
+            Error::Extension(path) => {
+                write!(f, "{}: scene files are .toml or .json", path.display())
+            }
+            Error::Invalid(path, e) => write!(f, "{}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+fn is_scene_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("toml" | "json")
+    )
+}
+
+impl SceneFile {
+    // A scene with only a source and a camera, everything else the default.
+    pub fn new(name: &str, source: &str, camera: Vec3) -> Self {
+        SceneFile {
+            name: name.to_string(),
+            source: source.to_string(),
+            params: default_params(),
+            background: default_background(),
+            camera: Camera {
+                position: camera,
+                look_at: ZERO3,
+            },
+            lights: default_lights(),
+            materials: Vec::new(),
+            objects: Vec::new(),
+            settings: Settings::default(),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| Error::Io(path.to_path_buf(), e))?;
+        let mut scene: SceneFile = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => {
+                toml::from_str(&text).map_err(|e| Error::Toml(path.to_path_buf(), e))?
+            }
+            Some("json") => {
+                serde_json::from_str(&text).map_err(|e| Error::Json(path.to_path_buf(), e))?
+            }
+            _ => return Err(Error::Extension(path.to_path_buf())),
+        };
+        scene.name = path
+            .file_stem()
+            .map_or(String::new(), |s| s.to_string_lossy().into_owned());
+        scene
+            .check()
+            .map_err(|e| Error::Invalid(path.to_path_buf(), e))?;
+        Ok(scene)
+    }
+
+    // What's wrong with the scene that reading it doesn't catch.
+    fn check(&self) -> Result<(), String> {
+        let bits = self.settings.tone.bits;
+        if bits != 8 && bits != 16 {
+            return Err(format!("images are 8 or 16 bits, not {}", bits));
+        }
+        if self.objects.is_empty() {
+            return Ok(());
+        }
+        graph::check(&self.source, &self.objects)?;
+        for object in &self.objects {
+            if let Some(name) = &object.material {
+                if !self.materials.iter().any(|m| m.name.as_ref() == Some(name)) {
+                    return Err(format!("{}: there's no material {}", object.name, name));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("scenes are valid toml")
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("scenes are valid json")
+    }
+
+    pub fn param(&self, name: &str) -> f32 {
+        self.params.get(name).copied().unwrap_or(0.0)
+    }
+
+    // The source, or the program joining the objects, panics if one doesn't
+    // parse, like `pratt::parse`.
+    pub fn ast(&self) -> Statement {
+        if self.objects.is_empty() {
+            return parse(&mut self.source.as_str());
+        }
+        graph::program(&self.source, &self.objects)
+    }
+
+    // The interpreted, optimized program with the scene's parameters.
+    pub fn sdf(&self) -> Sdf {
+        self.interpret(&self.ast())
+    }
+
+    fn interpret(&self, ast: &Statement) -> Sdf {
+        let params: Vec<(&str, f32)> = self.params.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        Box::new(interpret(ast, &params))
+    }
+
+    // The materials with their regions interpreted, and the `col` and `mat`
+    // the source sets or the objects have, panics if one doesn't parse.
+    pub fn materials(&self) -> Materials {
+        let materials = Materials::new(
+            self.materials
+                .iter()
+                .map(|m| {
+                    let region = m.region.as_ref().map(|r| self.interpret(&parse(&mut &**r)));
+                    (m.clone(), region)
+                })
+                .collect(),
+        );
+        // Without a `mat` an object is made of its own material.
+        let by_id: Vec<Option<f32>> = self
+            .objects
+            .iter()
+            .map(|o| o.material.as_ref().map(|m| self.material(m) as f32))
+            .collect();
+        // The distance doesn't need them, so they're only in the unoptimized
+        // program.
+        let ast = self.ast();
+        if !graph::assigns(&ast, "col") && !graph::assigns(&ast, "mat") && self.objects.is_empty() {
+            return materials;
+        }
+        // Each object keeps its own `col` and `mat`, and the closest one's
+        // count.
+        let parts: Vec<(Option<f32>, [String; 2])> = if self.objects.is_empty() {
+            vec![(None, ["col".to_string(), "mat".to_string()])]
+        } else {
+            graph::parts(&self.source, &self.objects)
+                .into_iter()
+                .map(|id| (Some(id), ["col", "mat"].map(|var| graph::local(id, var))))
+                .collect()
+        };
+        let params = self.params.clone();
+        let outputs = move |p| {
+            let params = params.iter().map(|(k, v)| (k.as_str(), *v));
+            let mut names = vec!["id"];
+            names.extend(
+                parts
+                    .iter()
+                    .flat_map(|(_, vars)| vars.iter().map(|v| v.as_str())),
+            );
+            let values = outputs_with(&ast, params, p, &names);
+            let id = values[0];
+            let (col, mat) = parts
+                .iter()
+                .position(|(part, _)| part.is_none() || *part == id)
+                .map_or((None, None), |i| (values[2 * i + 1], values[2 * i + 2]));
+            (
+                col,
+                mat.or_else(|| {
+                    graph::object(id).and_then(|id| by_id.get(id as usize).copied().flatten())
+                }),
+            )
+        };
+        materials.with_surface(Box::new(outputs))
+    }
+
+    // The index of the material called `name`, panics if there isn't one.
+    pub fn material(&self, name: &str) -> usize {
+        self.materials
+            .iter()
+            .position(|m| m.name.as_deref() == Some(name))
+            .unwrap_or_else(|| panic!("Unknown material: {}", name))
+    }
+
+    // The object closest to a point, if the program sets `id`, as the objects
+    // do.
+    pub fn ids(&self) -> Option<Ids> {
+        let ast = self.ast();
+        if !graph::assigns(&ast, "id") {
+            return None;
+        }
+        let params = self.params.clone();
+        let id = move |p| {
+            let params = params.iter().map(|(k, v)| (k.as_str(), *v));
+            outputs_with(&ast, params, p, &["id"])[0]
+        };
+        Some(Box::new(move |p| graph::object(id(p))))
+    }
+
+    // The hand written version in `sdfs`, if there is one.
+    pub fn compiled(&self) -> Option<fn(Vec3) -> f32> {
+        compiled(&self.name)
+    }
+
+    // The image of the first environment light, panics if it doesn't load.
+    pub fn environment(&self) -> Option<Environment> {
+        let path = self.lights.iter().find_map(|light| light.image.as_ref())?;
+        Some(Environment::load(path).unwrap_or_else(|e| panic!("{}: {}", path, e)))
+    }
+
+    // The box the surface is inside, if `bound` in the settings can tell.
+    pub fn bound(&self) -> Option<Bound> {
+        match self.settings.bound {
+            Bounding::Auto => Bound::estimate(
+                &self.ast(),
+                self.params.iter().map(|(k, v)| (k.as_str(), *v)),
+            ),
+            Bounding::Sample => Bound::sample(&self.sdf(), 64),
+            Bounding::Off => None,
+            Bounding::Box(bound) => Some(bound),
+        }
+    }
+
+    pub fn view<'a>(&'a self, sdf: &'a Sdf) -> View<'a> {
+        let mut view = View::new(
+            sdf,
+            self.camera.position,
+            self.camera.look_at,
+            &self.lights,
+            self.background,
+            self.settings.width,
+            self.settings.height,
+        );
+        view.sampling = self.settings.sampling;
+        view.materials = self.materials();
+        view.bounces = self.settings.bounces;
+        view.environment = self.environment();
+        view.atmosphere = self.settings.fog.map(Atmosphere::new);
+        view.path = self.settings.path;
+        view.bound = self.bound();
+        view.ids = self.ids();
+        view
+    }
+
+    pub fn render(&self, sdf: &Sdf) -> Vec<u8> {
+        let Settings {
+            width,
+            height,
+            aa,
+            tone,
+            ..
+        } = self.settings;
+        Frame::new(width, height, render_linear(&self.view(sdf), aa), tone)
+            .post(&self.settings.post)
+            .to_u8()
+    }
+}
+
+// The scene files in a directory, by name.
+#[derive(Debug, Clone, Default)]
+pub struct Library {
+    scenes: BTreeMap<String, SceneFile>,
+}
+
+impl Library {
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        let dir = dir.as_ref();
+        let entries = fs::read_dir(dir).map_err(|e| Error::Io(dir.to_path_buf(), e))?;
+        let mut scenes = BTreeMap::new();
+        for entry in entries {
+            let path = entry.map_err(|e| Error::Io(dir.to_path_buf(), e))?.path();
+            if is_scene_file(&path) {
+                let scene = SceneFile::load(&path)?;
+                scenes.insert(scene.name.clone(), scene);
+            }
+        }
+        Ok(Library { scenes })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SceneFile> {
+        self.scenes.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.scenes.keys().map(|s| s.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SceneFile> {
+        self.scenes.values()
+    }
+}
+
+impl std::ops::Index<&str> for Library {
+    type Output = SceneFile;
+
+    fn index(&self, name: &str) -> &SceneFile {
+        self.get(name)
+            .unwrap_or_else(|| panic!("Unknown scene: {}", name))
+    }
+}
+
+// The scenes in `SCENES_DIR`, panics if they don't load.
+pub fn library() -> Library {
+    Library::load(SCENES_DIR).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::post::{Bloom, Dither, Method};
+
+    #[test]
+    fn round_trip() {
+        let mut scene = SceneFile::new("test", "L(x,y,z)-1", v3(0.0, 0.0, -5.0));
+        scene.params.insert("r".to_string(), 2.0);
+        scene.settings.aa = 1;
+        scene.materials = vec![
+            Material {
+                region: Some("y+1".to_string()),
+                ..Material::mirror(0.5)
+            },
+            Material::glass(1.5),
+        ];
+        scene.settings.post = vec![
+            Filter::Bloom(Bloom::default()),
+            Filter::Dither(Dither {
+                method: Method::Diffusion,
+            }),
+        ];
+        let mut toml: SceneFile = toml::from_str(&scene.to_toml()).unwrap();
+        let mut json: SceneFile = serde_json::from_str(&scene.to_json()).unwrap();
+        toml.name = "test".to_string();
+        json.name = "test".to_string();
+        assert_eq!(toml, scene);
+        assert_eq!(json, scene);
+    }
+
+    #[test]
+    fn defaults() {
+        let scene: SceneFile =
+            toml::from_str("source = \"L(x,y,z)-r\"\ncamera.position = [0, 0, -5]\nparams.r = 2")
+                .unwrap();
+        assert_eq!(scene.settings, Settings::default());
+        assert_eq!(scene.lights.len(), 2);
+        assert_eq!(scene.param("a0"), 0.0);
+        assert_eq!((scene.sdf())(v3(3.0, 0.0, 0.0)), 1.0);
+        assert!(toml::from_str::<SceneFile>(
+            "source = \"x\"\ncamera.position = [0, 0, 0]\nzoom = 2"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        let path = std::env::temp_dir().join("arrow_invalid.toml");
+        let mut scene = SceneFile::new("test", "L(x,y,z)-1", v3(0.0, 0.0, -5.0));
+        scene.settings.tone.bits = 12;
+        fs::write(&path, scene.to_toml()).unwrap();
+        assert!(matches!(SceneFile::load(&path), Err(Error::Invalid(..))));
+        scene.settings.tone.bits = 16;
+        fs::write(&path, scene.to_toml()).unwrap();
+        assert!(SceneFile::load(&path).is_ok());
+        // Objects that can't be joined, or are made of nothing there is.
+        scene.source = String::new();
+        let ball = Object::new("ball", "L(x,y,z)-1");
+        for objects in [
+            vec![Object {
+                visible: false,
+                ..ball.clone()
+            }],
+            vec![Object::new("ball", "[a,b]=[x,y]")],
+            vec![Object {
+                material: Some("gold".to_string()),
+                ..ball.clone()
+            }],
+        ] {
+            scene.objects = objects;
+            fs::write(&path, scene.to_toml()).unwrap();
+            assert!(matches!(SceneFile::load(&path), Err(Error::Invalid(..))));
+        }
+        scene.objects = vec![ball];
+        fs::write(&path, scene.to_toml()).unwrap();
+        assert!(SceneFile::load(&path).is_ok());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn scenes() {
+        let library = library();
+        for scene in library.iter() {
+            assert!(!scene.source.is_empty(), "{}", scene.name);
+        }
+        assert_eq!(library["sphere"].camera.position, v3(0.0, 0.0, -20.0));
+        assert!(library["asurf"].compiled().is_some());
+        assert!(library["sphere"].compiled().is_none());
+    }
+
+    #[test]
+    fn objects() {
+        let scene: SceneFile = toml::from_str(
+            r#"
+            source = "y+5"
+            camera.position = [0, 0, -20]
+            materials = [{ name = "matte" }, { name = "mirror", reflect = 1 }]
+
+            [[objects]]
+            name = "ball"
+            source = "L(x,y,z)-2"
+            translate = [-4, 0, 0]
+            material = "mirror"
+
+            [[objects]]
+            name = "box"
+            source = "bx3(x,y,z,2)"
+            translate = [4, 0, 0]
+
+            [[objects]]
+            name = "hidden"
+            source = "L(x,y,z)-1"
+            visible = false
+            "#,
+        )
+        .unwrap();
+        let sdf = scene.sdf();
+        assert_eq!(sdf(v3(-4.0, 3.0, 0.0)), 1.0);
+        assert_eq!(sdf(ZERO3), 2.0);
+        let materials = scene.materials();
+        assert_eq!(materials.at(v3(-4.0, 2.0, 0.0)).0.reflect, 1.0);
+        // Without one it's the first that applies.
+        assert_eq!(materials.at(v3(4.0, 2.0, 0.0)).0.reflect, 0.0);
+        // The floor is no object.
+        let ids = scene.ids().unwrap();
+        assert_eq!(ids(v3(4.0, 2.0, 0.0)), Some(1));
+        assert_eq!(ids(v3(0.0, -5.0, 0.0)), None);
+        let view = scene.view(&sdf);
+        let (width, height) = (view.width as f32, view.height as f32);
+        // Straight ahead is between them, a fifth to the right is the box.
+        assert_eq!(view.pick(width / 2.0, height / 2.0), None);
+        assert_eq!(
+            view.pick((width + 0.2 * height) / 2.0, height / 2.0),
+            Some(1)
+        );
+        assert_eq!(view.pick(0.0, height - 1.0), None);
+        assert_eq!(
+            SceneFile::new("test", "L(x,y,z)-1", ZERO3)
+                .ids()
+                .map(|_| ()),
+            None
+        );
+    }
+
+    #[test]
+    fn object_surfaces() {
+        let mut scene = SceneFile::new("test", "col=.25,y+5", v3(0.0, 0.0, -20.0));
+        scene.materials = vec![Material::default(), Material::mirror(1.0)];
+        scene.objects = vec![
+            Object {
+                translate: v3(-4.0, 0.0, 0.0),
+                ..Object::new("ball", "mat=1,L(x,y,z)-2")
+            },
+            Object {
+                translate: v3(4.0, 0.0, 0.0),
+                ..Object::new("box", "col=.5,bx3(x,y,z,2)")
+            },
+        ];
+        let materials = scene.materials();
+        assert_eq!(
+            materials.at(v3(-4.0, 2.0, 0.0)),
+            (&Material::mirror(1.0), 1.0)
+        );
+        assert_eq!(materials.at(v3(4.0, 2.0, 0.0)), (&Material::default(), 0.5));
+        assert_eq!(
+            materials.at(v3(0.0, -5.0, 0.0)),
+            (&Material::default(), 0.25)
+        );
+    }
+
+    #[test]
+    fn surface() {
+        let mut scene = SceneFile::new(
+            "test",
+            "col=.5+.5*mod(floor(x),2),mat=y>0,L(x,y,z)-5",
+            v3(0.0, 0.0, -20.0),
+        );
+        scene.materials = vec![Material::default(), Material::mirror(1.0)];
+        assert_eq!((scene.sdf())(v3(0.0, 0.0, 6.0)), 1.0);
+        let materials = scene.materials();
+        assert_eq!(
+            materials.at(v3(0.5, -1.0, 0.0)),
+            (&Material::default(), 0.5)
+        );
+        assert_eq!(
+            materials.at(v3(1.5, 1.0, 0.0)),
+            (&Material::mirror(1.0), 1.0)
+        );
+        let plain = SceneFile::new("test", "L(x,y,z)-5", v3(0.0, 0.0, -20.0));
+        assert_eq!(plain.materials().at(v3(0.5, -1.0, 0.0)).1, 1.0);
+        assert!(plain.ids().is_none());
+        // Told from the program, not from where the camera is.
+        let tagged = SceneFile::new("test", "id=x>0?1:-1,L(x,y,z)-5", v3(-20.0, 0.0, 0.0));
+        let ids = tagged.ids().unwrap();
+        assert_eq!(
+            (ids(v3(-6.0, 0.0, 0.0)), ids(v3(6.0, 0.0, 0.0))),
+            (None, Some(1))
+        );
+    }
+}
@@ -1,54 +1,72 @@
-use crate::core::{v3, Sdf};
+// Most of the distance functions are Inigo Quilez's, see
+// https://iquilezles.org/articles/distfunctions. Each primitive is a plain
+// function of the point, which the DSL functions call, and an `sd_` (or `ud_`
+// for unsigned distances) constructor that places it with a center and a
+// transform, see `Transform`. Angles are in radians.
+use crate::core::{Sdf, Sdf2, Transform};
 use glam::Vec3Swizzles;
-use glam::{Affine3A, Vec2, Vec3};
-use std::collections::HashMap;
-
-pub fn sd_plane(normal: Vec3, dist_to_origin: f32, transform: Affine3A) -> Sdf {
-    Box::new(move |p| {
-        let transform = transform.inverse();
-        let p = transform.transform_point3(p);
-        normal.normalize().dot(p) + dist_to_origin
-    })
+use glam::{Vec2, Vec3};
+
+// A shape with its own space moved by `transform` and then to `center`.
+pub(crate) fn placed(
+    center: Vec3,
+    transform: impl Into<Transform>,
+    f: impl Fn(Vec3) -> f32 + Sync + 'static,
+) -> Sdf {
+    let transform = transform.into();
+    Box::new(move |p| transform.distance(f(transform.point(p - center))))
 }
 
-pub fn sd_sphere(radius: f32, center: Vec3, transform: Affine3A) -> Sdf {
-    Box::new(move |p| {
-        let transform = transform.inverse();
-        transform.transform_point3(p - center).length() - radius
-    })
+// The plane through `center` with its `normal`, moved `dist_to_origin`
+// against it.
+pub fn sd_plane(
+    normal: Vec3,
+    dist_to_origin: f32,
+    center: Vec3,
+    transform: impl Into<Transform>,
+) -> Sdf {
+    let normal = normal.normalize();
+    placed(center, transform, move |p| normal.dot(p) + dist_to_origin)
+}
+
+pub fn sd_sphere(radius: f32, center: Vec3, transform: impl Into<Transform>) -> Sdf {
+    placed(center, transform, move |p| p.length() - radius)
 }
 
-pub fn sd_box(b: Vec3, center: Vec3, transform: Affine3A) -> Sdf {
-    Box::new(move |p| {
-        let transform = transform.inverse();
-        let p = transform.transform_point3(p - center);
+pub fn sd_box(b: Vec3, center: Vec3, transform: impl Into<Transform>) -> Sdf {
+    placed(center, transform, move |p| {
         let q = p.abs() - b;
         q.y.max(q.z).max(q.x).min(0.0) + q.max(Vec3::ZERO).length()
     })
 }
 
-pub fn sd_round_box(b: Vec3, radius: f32, center: Vec3, transform: Affine3A) -> Sdf {
-    Box::new(move |p| {
-        let transform = transform.inverse();
-        let p = transform.transform_point3(p - center);
+pub fn sd_round_box(b: Vec3, radius: f32, center: Vec3, transform: impl Into<Transform>) -> Sdf {
+    placed(center, transform, move |p| {
         let q = p.abs() - b;
         q.x.max(q.y).max(q.z).min(0.0) + q.max(Vec3::ZERO).length() - radius
     })
 }
 
-pub fn sd_torus(major_radius: f32, minor_radius: f32, center: Vec3, transform: Affine3A) -> Sdf {
-    Box::new(move |p| {
-        let transform = transform.inverse();
-        let p = transform.transform_point3(p - center);
+pub fn sd_torus(
+    major_radius: f32,
+    minor_radius: f32,
+    center: Vec3,
+    transform: impl Into<Transform>,
+) -> Sdf {
+    placed(center, transform, move |p| {
         let q = Vec2::new(p.xy().length() - major_radius, p.z);
         q.length() - minor_radius
     })
 }
 
-pub fn sd_capsule(radius: f32, center: Vec3, a: Vec3, b: Vec3, transform: Affine3A) -> Sdf {
-    Box::new(move |p| {
-        let transform = transform.inverse();
-        let p = transform.transform_point3(p - center);
+pub fn sd_capsule(
+    radius: f32,
+    center: Vec3,
+    a: Vec3,
+    b: Vec3,
+    transform: impl Into<Transform>,
+) -> Sdf {
+    placed(center, transform, move |p| {
         let pa = p - a;
         let ba = b - a;
         let h = (pa.dot(ba) / ba.dot(ba)).clamp(0.0, 1.0);
@@ -56,10 +74,14 @@ pub fn sd_capsule(radius: f32, center: Vec3, a: Vec3, b: Vec3, transform: Affine
     })
 }
 
-pub fn sd_cylinder(radius: f32, center: Vec3, bottom: Vec3, top: Vec3, transform: Affine3A) -> Sdf {
-    Box::new(move |p| {
-        let transform = transform.inverse();
-        let p = transform.transform_point3(p - center);
+pub fn sd_cylinder(
+    radius: f32,
+    center: Vec3,
+    bottom: Vec3,
+    top: Vec3,
+    transform: impl Into<Transform>,
+) -> Sdf {
+    placed(center, transform, move |p| {
         let pa = p - bottom;
         let ba = top - bottom;
         let baba = ba.dot(ba);
@@ -77,206 +99,499 @@ pub fn sd_cylinder(radius: f32, center: Vec3, bottom: Vec3, top: Vec3, transform
     })
 }
 
-pub fn sd_inf_cylinder(radius: f32, xz: Vec2, transform: Affine3A) -> Sdf {
-    Box::new(move |p| {
-        let transform = transform.inverse();
-        let p = transform.transform_point3(p);
-        (p.xz() - xz).length() - radius
-    })
+// The cylinder along y through `center`.
+pub fn sd_inf_cylinder(radius: f32, center: Vec3, transform: impl Into<Transform>) -> Sdf {
+    placed(center, transform, move |p| p.xz().length() - radius)
 }
 
-pub fn examples<'a>() -> HashMap<&'a str, (&'a str, Vec3)> {
-    let pos = v3(0.0, 0.0, -20.0);
-    let mut examples = HashMap::new();
-    examples.insert("sphere", ("b=B(y-12)-6; L(x,b,z)-6", v3(0.0, 0.0, -20.0)));
-    examples.insert(
-        "balls8a",
-        ("@xyz{$=B($)-6,} L(x,y,z)-5", v3(10.0, 20.0, -25.0)),
-    );
-    examples.insert(
-        "cubes8a",
-        (
-            "rU(bx3(x,y,z, 5), bx3(x-8, y+5,z,5), bx3(x+8,y-5,z,5), 5)",
-            v3(10.0, 20.0, -25.0),
-        ),
-    );
-    examples.insert(
-        "box_of_balls",
-        (
-            "s=1; @5{ [x,y]=r0(x,y), [x,z]=r1(x,z), @xyz{$=B($*2)-8,} s*=.5; } (L(x,y,z)-8)*s",
-            v3(0.0, 0.0, -20.0),
-        ),
-    );
-    examples.insert(
-        "sponge", 
-        ("r=bx3(x,y,z,9),s=1; @3{ @xyz{$=(mod($+9,18)-9)*3,} s/=3, r=k(r,-U(@xyz{bx2($,$$,9),})*s), }r", v3(-20.0, 20.0, -5.0))
-    );
-    examples.insert("donut", ("don(x,y-2,z,15,2)", v3(0.0, 0.0, -20.0)));
-    examples.insert(
-        "donuts",
-        ("don(x,y-3,mod(z,8)-4,8,1)", (v3(-50.0, 20.0, -20.0))),
-    );
-    examples.insert("rods", ("L(B(B(x)-3)-3,B(y)-3)-2", pos));
-    examples.insert(
-        "rounded_box",
-        ("bx3(x,y,z,7,4,4)-5", v3(-20.0, 20.0, -20.0)),
-    );
-    examples.insert("elbow", ("L(k(x,y-10),z)-5", pos));
-    examples.insert("fence", ("L(x,TR(y))-.5", v3(10.0, 10.0, -50.0)));
-    examples.insert(
-        "cross",
-        (
-            "U( bx3(mod(x,4)-2,y,z,6), bx3(x,y,mod(x,4)-2,6), L(TR(x),y)-1, L(x+20,y-20,z-20)-8)",
-            v3(-10.0, 30.0, -15.0),
-        ),
-    );
-    examples.insert(
-        "apollonius",
-        (
-            "s=2.5,h=s/2,d=(s+h)/2,q=20,y-=10,[x,y]=r0(x,y),@xyz{$/=q,}c=1,t=0,@7{@xyz{$=mod($-h,s)-h,}t=d/D([x,y,z],[x,y,z]),@xyzc{$*=t,}}d=L(x,y,z)/c*2.-.025", 
-            v3(0.0, 0.0, -30.0)
-        ),
-    );
-    examples.insert("rot_cube", ("[a,b]=r0(x,y-9); bx3(a,b,z,4)-.5", pos));
-    examples.insert(
-        "hyperplane",
-        (
-            "a=(2*x-3*z+6*y)/7,b=(7*x-2*z+26*y)/27,c=(6*z-3*x+22*y)/23,d=0,zz=0;[x,z]=r1(x,z+8),[x,y]=r0(x,y),y-=3,zz=FR(z/26-.55)*26-13,d=SM(9,-12,y+3-z*.3),U(k(k(k(bx3(x,y-5,zz,7,14,7)-1,@abc{d-B(TR($))),}L(x+99,y+445,z+32)-434)", 
-            v3(20.0, 0.0, -60.0)
-        ),
-    );
-    examples.insert(
-        "desire",
-        ("[x,y]=r0(x,y-1), [x,z]=r1(x,z), yb=B(y)-22.5, U(rG(32-k(-y-13,z-15),TR(x*.25)*4-2+4*SM(0,16,x),4),rG( B(B(L(L(x,z)-16,yb-cl(yb,-8.5,8.5))-8)-4)-2, B(B(L(B(x)-15,B(B(y)-15)-15,B(z)-15)-9)-4)-2,1 ))", pos),
-    );
-    examples.insert(
-        "singularity",
-        (
-            "[x,z]=r0(x,z),l=L(x,y,z),n=2.0 * nz(atan2(z,x),Math.acos(y/l),l,.3,0,1),d=l-20+n*5,d=B(d)-5,d=B(d)-1,b=99,@4{b=U(b,bx2(x,y-n*3+10-$*10,100,2+$*.5)),}rU(y+20-B(n)*.2,k(0-b,d)-.4,20)", 
-            v3(0.0, 0.0, -60.0)
-        ),
-    );
-    examples.insert(
-        "gnarl",
-        ("p=B(y-18)-13,n=nz(x,y,z,.2,0,2)*2,q=mod(p,12+n*z)-1.8", v3(0.0, 0.0, -50.0)),
-    );
-    examples.insert(
-        "ondu",
-        ("s=.5,y+=6, a=k(y+22,B(z+10*g(x*.005+.2))-16)-4,b=TR(x/40+.2)*40,c; [b,y]=r1(b,y), [y,z]=r0(y,z+15), r=rU( L(x-cl(x,-2,2),b*1.3,z)-3, U( L(x+5,b-1,z)-1.7, L(x+5,b-2,B(z)-1.5)-0.8, bx3(x-5,b-1,z,0.2,0.1,0.2)-0.5, bx3(x+5,b-1,z,1.9,.1,.1)-.5, L(B(x)-3.5,b-cl(b,-4,0),B(z)-1.5)-.8),1.5 )-nz(x,0,z,12,1)*0.15, s=(L(x>7?(mod(x,4)-2)/2:x,x<1?y:b/3+2,B(z)-1.5)-1.8)-nz(x,y,z,.5,1)*2, rG(U(a,g),-s,1)", pos),
-    );
-    examples.insert(
-        "source",
-        ("@zy{$f=$+nz(x,y,z,.03,1)*40,} zf+=nz(x,y,z,.1,2)*20, f=L(zf+10,yf)-20, w=y+nz(x,y,z+30,.4,2,1)*2.2+1, g=y+nz(x,y,z,0.1,1), p=min(max(g,-f),max(w,f))+nz(x,y,z,.02,2,2)*4, [ex,ey]=r0(x-18,y-6), [ey,ez]=r1(ey,z-11), es=2, e=bx3(ex,ey,ez,es*1.5,es,.1), sg=.5, @xyz{$g=e$+nz(ex/1.6,ey,ez,1,1,2)*2,} g=1e6, @xy{g=U(g,L(ez-.1,mod($g, sg)-sg/2)-sg*.07),} g=max(g,bx3(ex,ey,ez,es*1.5*.85,es*.85,2)), min(p, e, g, bx3(ex+3,B(ey)-.8,ez, 4,.1,.1), bx3(ex+3,ey,ez, .05, es*1.2,.5),bx3(ex-3,ey,ez, .05, es*.2,.2))", pos),
-    );
-    examples.insert(
-        "spheres",
-        ("y-=5,z-=3, r=L(x,y,z), ph=atan2(y,x), th=Math.acos(z/r), n=18,r0=r-33, cs=sin(n*ph)*cos(n*th), c0=L(r0,cs)-.1, c3=L(r0-cl(r0,-2,-1),r/n*(cs-.5))-.05, c4=L(r0-cl(r0,-1,.5),r/n*(cs-.75))-.05, c5=L(r0-cl(r0,-1,1),r/n*(cs-.95))-.025, n=4,r1=1.25*n*sin(th), x=r0, y=r/n*sin(th)*cos(n*ph)*sin(n*th), z=r/n*sin(th)*sin(n*ph)*cos(n*th), c1=L(x,y,z)-r1, r=L(x,y,z), ph=atan2(y,x), th=Math.acos(z/r), zr=r-r1-cl(r-r1,0,.5), n=12, x=r/n*(sin(th)*cos(n*ph)*sin(n*th)-.5), c2=L(x,zr)-.05, U(c0,c1,c2,c3,c4,c5)", pos),
-    );
-    examples.insert(
-        "arctic",
-        ("[x,z]=r0(x,z), x+=11, z+=15, y+=10, h=exp(-1.5*B(nz(x,0,z,.1,1))), g=y-10*h-nz(x,0,z,10,1)*0.05, b = y-12, a=rU( L(x-cl(x,-2,2),b*1.3,z)-3, U( L(x+5,b-1,z)-1.7, L(x+5,b-2,B(z)-1.5)-0.8, bx3(x-5,b-1,z,0.2,0.1,0.2)-0.5, bx3(x+5,b-1,z,1.9,.1,.1)-.5, L(B(x)-3.5,b-cl(b,-4,0),B(z)-1.5)-.8),1.5 )-nz(x,0,z,12,1)*0.15, s=(L(x>7?(mod(x,4)-2)/2:x,x<1?y:b/3+2,B(z)-1.5)-1.8)-nz(x,y,z,.5,1)*2, rG(U(a,g),-s,1)", pos),
-    );
-    examples.insert(
-        "quanta",
-        ("s=20,[x,z]=r0(x,z),[y,x]=r1(y,x),z+=17,y+=27,i=0,z+=ri(Z(x/s))*70,@xz{$-=nz(x,y,z,.1,i++)*5*i,$i=Z($/s),$=mod($,s)-s/2,}i=ri(xi,zi),j=ri(xi,floor(y/5)),d=i>.1?rU(L(x,z)-1*i-.5*(cos(y/4)+1),bx2(L(x,z)-(cos(floor(y/4))+1)*2,mod(y,4)-2,.1,.2)-.05,1):L(x,mod(y,5)-2.5,z)-G(j,0)*2", pos),
-    );
-    examples.insert(
-        "thepath",
-        (
-            "@xyz{$m=mod($,20)-10,$i=Z($/20),}d=99,g0=.05,y-=20,[z,x]=r0(z,x),n=nz(x,y,z,.1,1),n1=nz(x,y,z,.3,2,3),@4{x-=20,o=$*200+20,e=B(y+n1/2+sin(z*.05+o)*10)-1,e=rG(e,B(z+sin(x*.05+o)*25)-5+n1*2,.2),@xz{$1=mod($+n*10,3)-1.5,}e=rG(e,-(B(z1)-g0),.25),e=rG(e,-(B(x1)-g0),.25),d=U(d,e),[x,z]=r1(z,x),y+=20,}U(d,ri(xi,yi,zi)>.4&&L(xi,yi,zi)>3?L(xm,ym,zm)-2:10)",
-             v3(10.0, 20.0, -25.0)
-            )
-    );
-    examples.insert(
-        "mycelia",
-        (
-            "@xyz{$/=20,} xm=.9,ym=.3,zm=.7, @4{ @xyz{$=B($)-$m,} s=1/scl(L(x,y,z)**3,.1,.1,1), @xyz{$=$*s-$$m,} } L(z,y)-.1",
-            v3(10.0, 10.0, -15.0),
-        )
-    );
-    examples.insert(
-        "pawns", 
-        (
-            "i=mod(floor(x/8)+floor(z/8),2),x=mod(x,8)-4,z=mod(z,8)-4,a=L(x,y,z)-1,q=L(x,z),b=max(D([1,.3],[q,y]),-5-y),a=rU(a,b,1),y+=1,a=rU(a,L(x,y*5,z)-.8,1),y+=3,a=rU(a,L(x,y*2,z)-1,.5),y+=1,a=rU(a,L(x,y*3,z)-1.7,0.1),min(a,y+.5*i*nz(x,y,z,8,0))",
-            v3(0.0, 0.0, -20.0)
-        )
-    );
-    examples.insert(
-        "plato",
-        (
-            "d=99,l=10, x-=l*2, y-=l,z+=2.5, @3{ x+=l, a=a0*($+2),s=sin(a),c=cos(a), [x1,y1]=rot(x,y,s,c), a=a1*($+2),s=sin(a),c=cos(a), [x1,z1]=rot(x1,z,s,c), d=rU(d, bx3(x1,y1,z1,4),3), } U(d+.5, L(nz(x,y,z,.1,1,2)-.5, abs(d)-.1)-.4)",
-            v3(0.0, 30.0, -10.0)
-        )
-    );
-    examples.insert(
-        "sprenkle",
-        (
-            "y+=7, rU( don(x,y-12,mod(z,8)-4,7+3*SM(9,15,y)+4*nz(x,y,z,.3,1),2.7-2*SM(9,15,y)+nz(x,y,z,.2,2)), L(x,y+83)-90,1)-.1*SM(0,.15,B(nz(x,y,z,2,0,3)))",
-            v3(0.0, 0.0, -40.0)
-        )
-    );
-    examples.insert(
-        "target",
-        (
-            "n=nz(x,y,z,.4,0,2)*4; U(L(k(x,-12-z),-k(2-y,-19-x))-.5, L(TR(L(x,y)/4)*4+n,z-cl(-3,3,z))-.5-1.5*SM(3,-3,y)-3*SM(-3,-9,y) )",
-            v3(-3.0, 5.0, -29.0)
-        )
-    );
-    examples.insert(
-        "toy", 
-        (
-            "s=1,x1=x-.25,y1=y-4.6,z1=z; x=x1,y=y1, @4{ [x,y]=r0(x,y), [x,z]=r1(x,z), @xyz{$=sB($*2,.1)-4,} s*=.4, } rU( rU( bx3(x,y,z,4)*s-0.01, rG( L(x,y)-1.75, bx3(x,y,z,4)*0.2), 0.75), don(x1,y1,z1,9,.75),2.75)", 
-            v3(5.0, -5.0, -15.0)
-        )
-    );
-    examples.insert(
-        "shai_hulud",
-        (
-            "[x,z]=r0(x,z),m=nz(x,y,z,.4,1),r=30,t=.2,v=atan2(z,y-7),u=L(z,y-7), min( min(max(don(x+5,y+5.5,z+10,.5,.05),y+5.5), L(x*3+15,y/4+2.4,z*3+30)-1), rU(y-20+nz(x/3,y/3,z/2,1,1)+SM(30,0,15-B(x+sin(z/2)+sin(z/6)+sin(z/8)-4))*15+SM(30,0,15-B(mod(z+x+sin(x/2)+sin(x/6)+sin(x/8),60)-35))*15, max( -L(y-7,z,x+3)+4+sin(v*3-2), min( don(x-90,y+23,z,20,5+m), max(abs(don(x,y+r-7,z,r,5+m))-t,-x), max(abs(L(x,y-7,z)-5-m)-t,x), L(u/3-1,B(B(B(x*2-4)-2)-2)-1,mod(x/2+v*9,1.6)-.8)-.34) ),2) )",
-            v3(-8.0, 5.0, 40.0)
-        )
-    );
-    examples.insert(
-        "else",
-        (
-           "y-=1, r=bx3(x,y,z,9)-2,s=1,ti=U(L(x,y)-.6, L(y,z)-.6,L(z,x)-.6); @4{ @xyz{$=(mod($+9,18)-9)*3,} s/=3, r=k(r+s,-U(@xyz{L($,$$)-12,})*s)-s, } U(r, ti)",
-            v3(0.0, 20.0, -20.0) 
-        )
-    );
-    examples.insert(
-        "temple",
-        (
-            "d=99, [y,z]=r1(y,z), f=y+B(nz(x,z,1,.0,3))*5, @5{ [x,y,z]=[y,z,x], [x,z]=r0(x,z), [x,z]=r1(x,z), @xyz{$=sB($,2)-3,} d=rU(d, don(y,z,x,5,.5+$*.2), 1), } rU(f, L(d,nz(x,y,z,.5,1))-.1, .5)",
-            v3(0.0, 0.0, -35.0)
-        )
-    );
-    examples.insert(
-        "jeff",
-        (
-            "2.0 * nz(0.25 * x, y, z, 2.5, 1, 1)",
-            v3(0.0, 0.0, -30.0)
-        )
-    );
-    examples.insert(
-        "system",
-        (
-            "PI = 3.1415, [z,y]=r0(z,y), z=z+10, r=Math.sqrt(x * x + y * y), t=Math.atan2(y, x)/(2*PI), d=1e4, @20{ d=min(bx3(r-2*$,t,z,0.8,1,floor(mod($*1.2+t* mix(1,200,Math.pow(ri($,$,$)+.5,2)),3))>0?-1:1)-.2,d), } min(d,-z+nz(x,y,x,.1,1)*2)",
-            v3(0.0, -20.0, -30.0)
-        )
-    );
-    examples.insert(
-        "ghost",
-        (
-            "y-=9.8, [x,z]=r0(x,z),a=x,b=y,c=B(z)-.3, [a,b]=rot(a,b,cos(.17),sin(.17)), an=Z(.5+atan2(b,a)/a1)*a1,[a,b]=rot(a,b,cos(an),sin(an)),d=G(U(bx3(a-7,b,c,.01,2,.01)-.05,bx3(b,a,c,.02,7,.02)-.01,L(a-7,b)-.4,L(mod(cl(a,0,5),1)-.5,b)-.05,),B(z)-.3),a=B(x),b=y,an=.3,[a,b]=rot(a,b,cos(an),sin(an)),d=U(G(U(d,L(x,y)-.2,L(a,c-.3)-.1),B(z)-.7), B(y+10)-2-sin(x*0.1)), t=(8*Z(x/8)+4),h=20-sin(t)*10, U(d,rU(G(bx3(x-t,y+h*.5,z+70,3,h,3), -bx3(B(x-t)-1.5,mod(y,3)-1.5,z+68,.8,h*0.04,2)), L(y+9,z+65)-.5+nz(x,y,z,5,1)*10, 2))",
-            v3(-10.0, 20.0, -1.0)
-        )
+// GLSL's sign, which is 0 at 0.
+fn sign(x: f32) -> f32 {
+    if x > 0.0 {
+        1.0
+    } else if x < 0.0 {
+        -1.0
+    } else {
+        0.0
+    }
+}
+
+fn dot2(v: Vec3) -> f32 {
+    v.dot(v)
+}
+
+// A cone with its tip at the origin, opening downwards along y by `angle`
+// from its axis, `height` tall.
+pub fn cone(p: Vec3, angle: f32, height: f32) -> f32 {
+    let (s, c) = angle.sin_cos();
+    let q = height * Vec2::new(s / c, -1.0);
+    let w = Vec2::new(p.xz().length(), p.y);
+    let a = w - q * (w.dot(q) / q.dot(q)).clamp(0.0, 1.0);
+    let b = w - q * Vec2::new((w.x / q.x).clamp(0.0, 1.0), 1.0);
+    let k = sign(q.y);
+    let d = a.dot(a).min(b.dot(b));
+    let s = (k * (w.x * q.y - w.y * q.x)).max(k * (w.y - q.y));
+    d.sqrt() * sign(s)
+}
+
+// A cone cut at y = -`height` with radius `r1` and at y = `height` with
+// radius `r2`.
+pub fn capped_cone(p: Vec3, height: f32, r1: f32, r2: f32) -> f32 {
+    let q = Vec2::new(p.xz().length(), p.y);
+    let k1 = Vec2::new(r2, height);
+    let k2 = Vec2::new(r2 - r1, 2.0 * height);
+    let ca = Vec2::new(
+        q.x - q.x.min(if q.y < 0.0 { r1 } else { r2 }),
+        q.y.abs() - height,
     );
-    examples.insert(
-        "asurf",
-        (
-            "s=1, z+=1, x+=.7, l=L(x-3,y-1,z-1)-1.45, @xyz{$*=.5,}yy=y,zz=z,@4{ @xz{$=qB($+1,.1)-qB($-1,.1)-$,} y-=1,x-=.3, [x,y]=r0(x,y), sc=2./cl(x*x+y*y+z*z,.4,1), @xyzs{$*=sc,}} rU(l,U(yy,(L(x,y)-1.5)/s*2),.2)",
-            v3(0.0, 0.0, -30.0)
-        )
+    let cb = q - k1 + k2 * ((k1 - q).dot(k2) / k2.dot(k2)).clamp(0.0, 1.0);
+    let s = if cb.x < 0.0 && ca.y < 0.0 { -1.0 } else { 1.0 };
+    s * ca.dot(ca).min(cb.dot(cb)).sqrt()
+}
+
+// The hull of a sphere of radius `r1` at the origin and one of radius `r2`
+// at y = `height`.
+pub fn round_cone(p: Vec3, r1: f32, r2: f32, height: f32) -> f32 {
+    let b = (r1 - r2) / height;
+    let a = (1.0 - b * b).sqrt();
+    let q = Vec2::new(p.xz().length(), p.y);
+    let k = q.dot(Vec2::new(-b, a));
+    if k < 0.0 {
+        q.length() - r1
+    } else if k > a * height {
+        (q - Vec2::new(0.0, height)).length() - r2
+    } else {
+        q.dot(Vec2::new(a, b)) - r1
+    }
+}
+
+// There is no closed form for the distance to an ellipsoid. Outside, the
+// value of |p / radii| - 1 over its gradient is a bound because the function
+// is convex. Inside, where that overshoots, it is scaled by the smallest
+// radius, the inverse of the function's Lipschitz constant.
+pub fn ellipsoid(p: Vec3, radii: Vec3) -> f32 {
+    let k0 = (p / radii).length();
+    if k0 < 1.0 {
+        return (k0 - 1.0) * radii.min_element();
+    }
+    let k1 = (p / (radii * radii)).length();
+    k0 * (k0 - 1.0) / k1
+}
+
+// A hexagonal prism along z, `radius` from its axis to the middle of its
+// sides and `height` from its center to its caps.
+pub fn hex_prism(p: Vec3, radius: f32, height: f32) -> f32 {
+    let k = Vec3::new(-0.866_025_4, 0.5, 0.577_350_27);
+    let mut p = p.abs();
+    let xy = p.xy() - 2.0 * k.xy().dot(p.xy()).min(0.0) * k.xy();
+    p.x = xy.x;
+    p.y = xy.y;
+    let d = Vec2::new(
+        (p.xy() - Vec2::new(p.x.clamp(-k.z * radius, k.z * radius), radius)).length()
+            * sign(p.y - radius),
+        p.z - height,
     );
-    examples
-}
\ No newline at end of file
+    d.x.max(d.y).min(0.0) + d.max(Vec2::ZERO).length()
+}
+
+// A triangular prism along z pointing up y. This is a bound, not the exact
+// distance.
+pub fn tri_prism(p: Vec3, radius: f32, height: f32) -> f32 {
+    let q = p.abs();
+    (q.z - height).max((q.x * 0.866_025 + p.y * 0.5).max(-p.y) - radius * 0.5)
+}
+
+// The octahedron with its vertices `size` from the origin along each axis.
+pub fn octahedron(p: Vec3, size: f32) -> f32 {
+    let p = p.abs();
+    let m = p.x + p.y + p.z - size;
+    let q = if 3.0 * p.x < m {
+        p
+    } else if 3.0 * p.y < m {
+        p.yzx()
+    } else if 3.0 * p.z < m {
+        p.zxy()
+    } else {
+        return m * 0.577_350_27;
+    };
+    let k = (0.5 * (q.z - q.y + size)).clamp(0.0, size);
+    Vec3::new(q.x, q.y - size + k, q.z - k).length()
+}
+
+// A pyramid on the unit square in the xz plane with its apex at y =
+// `height`.
+pub fn pyramid(p: Vec3, height: f32) -> f32 {
+    let m2 = height * height + 0.25;
+    let (mut px, mut pz) = (p.x.abs(), p.z.abs());
+    if pz > px {
+        std::mem::swap(&mut px, &mut pz);
+    }
+    px -= 0.5;
+    pz -= 0.5;
+    let q = Vec3::new(pz, height * p.y - 0.5 * px, height * px + 0.5 * p.y);
+    let s = (-q.x).max(0.0);
+    let t = ((q.y - 0.5 * pz) / (m2 + 0.25)).clamp(0.0, 1.0);
+    let a = m2 * (q.x + s) * (q.x + s) + q.y * q.y;
+    let b = m2 * (q.x + 0.5 * t) * (q.x + 0.5 * t) + (q.y - m2 * t) * (q.y - m2 * t);
+    let d2 = if q.y.min(-q.x * m2 - q.y * 0.5) > 0.0 {
+        0.0
+    } else {
+        a.min(b)
+    };
+    ((d2 + q.z * q.z) / m2).sqrt() * sign(q.z.max(-p.y))
+}
+
+// A chain link in the xy plane, a torus with major radius `r1` and minor
+// radius `r2` pulled apart by `length` along y.
+pub fn link(p: Vec3, length: f32, r1: f32, r2: f32) -> f32 {
+    let q = Vec3::new(p.x, (p.y.abs() - length).max(0.0), p.z);
+    Vec2::new(q.xy().length() - r1, q.z).length() - r2
+}
+
+// The part of a ball of `radius` within `angle` of the y axis.
+pub fn solid_angle(p: Vec3, angle: f32, radius: f32) -> f32 {
+    let (s, c) = angle.sin_cos();
+    let c = Vec2::new(s, c);
+    let q = Vec2::new(p.xz().length(), p.y);
+    let l = q.length() - radius;
+    let m = (q - c * q.dot(c).clamp(0.0, radius)).length();
+    l.max(m * sign(c.y * q.x - c.x * q.y))
+}
+
+// A torus in the xy plane like `sd_torus`, cut to within `angle` of the y
+// axis.
+pub fn capped_torus(p: Vec3, angle: f32, major_radius: f32, minor_radius: f32) -> f32 {
+    let (s, c) = angle.sin_cos();
+    let p = Vec3::new(p.x.abs(), p.y, p.z);
+    let k = if c * p.x > s * p.y {
+        p.xy().dot(Vec2::new(s, c))
+    } else {
+        p.xy().length()
+    };
+    (p.dot(p) + major_radius * major_radius - 2.0 * major_radius * k).sqrt() - minor_radius
+}
+
+// The distance to the triangle `abc`, which has no inside.
+pub fn unsigned_triangle(p: Vec3, a: Vec3, b: Vec3, c: Vec3) -> f32 {
+    let (ba, pa) = (b - a, p - a);
+    let (cb, pb) = (c - b, p - b);
+    let (ac, pc) = (a - c, p - c);
+    let nor = ba.cross(ac);
+    let edge = |e: Vec3, q: Vec3| dot2(e * (e.dot(q) / dot2(e)).clamp(0.0, 1.0) - q);
+    let outside =
+        sign(ba.cross(nor).dot(pa)) + sign(cb.cross(nor).dot(pb)) + sign(ac.cross(nor).dot(pc))
+            < 2.0;
+    if outside {
+        edge(ba, pa).min(edge(cb, pb)).min(edge(ac, pc)).sqrt()
+    } else {
+        (nor.dot(pa) * nor.dot(pa) / dot2(nor)).sqrt()
+    }
+}
+
+// The distance to the planar quad `abcd`.
+pub fn unsigned_quad(p: Vec3, a: Vec3, b: Vec3, c: Vec3, d: Vec3) -> f32 {
+    let (ba, pa) = (b - a, p - a);
+    let (cb, pb) = (c - b, p - b);
+    let (dc, pc) = (d - c, p - c);
+    let (ad, pd) = (a - d, p - d);
+    let nor = ba.cross(ad);
+    let edge = |e: Vec3, q: Vec3| dot2(e * (e.dot(q) / dot2(e)).clamp(0.0, 1.0) - q);
+    let outside = sign(ba.cross(nor).dot(pa))
+        + sign(cb.cross(nor).dot(pb))
+        + sign(dc.cross(nor).dot(pc))
+        + sign(ad.cross(nor).dot(pd))
+        < 3.0;
+    if outside {
+        edge(ba, pa)
+            .min(edge(cb, pb))
+            .min(edge(dc, pc))
+            .min(edge(ad, pd))
+            .sqrt()
+    } else {
+        (nor.dot(pa) * nor.dot(pa) / dot2(nor)).sqrt()
+    }
+}
+
+pub fn sd_cone(angle: f32, height: f32, center: Vec3, transform: impl Into<Transform>) -> Sdf {
+    placed(center, transform, move |p| cone(p, angle, height))
+}
+
+pub fn sd_capped_cone(
+    height: f32,
+    r1: f32,
+    r2: f32,
+    center: Vec3,
+    transform: impl Into<Transform>,
+) -> Sdf {
+    placed(center, transform, move |p| capped_cone(p, height, r1, r2))
+}
+
+pub fn sd_round_cone(
+    r1: f32,
+    r2: f32,
+    height: f32,
+    center: Vec3,
+    transform: impl Into<Transform>,
+) -> Sdf {
+    placed(center, transform, move |p| round_cone(p, r1, r2, height))
+}
+
+pub fn sd_ellipsoid(radii: Vec3, center: Vec3, transform: impl Into<Transform>) -> Sdf {
+    placed(center, transform, move |p| ellipsoid(p, radii))
+}
+
+pub fn sd_hex_prism(
+    radius: f32,
+    height: f32,
+    center: Vec3,
+    transform: impl Into<Transform>,
+) -> Sdf {
+    placed(center, transform, move |p| hex_prism(p, radius, height))
+}
+
+pub fn sd_tri_prism(
+    radius: f32,
+    height: f32,
+    center: Vec3,
+    transform: impl Into<Transform>,
+) -> Sdf {
+    placed(center, transform, move |p| tri_prism(p, radius, height))
+}
+
+pub fn sd_octahedron(size: f32, center: Vec3, transform: impl Into<Transform>) -> Sdf {
+    placed(center, transform, move |p| octahedron(p, size))
+}
+
+pub fn sd_pyramid(height: f32, center: Vec3, transform: impl Into<Transform>) -> Sdf {
+    placed(center, transform, move |p| pyramid(p, height))
+}
+
+pub fn sd_link(
+    length: f32,
+    r1: f32,
+    r2: f32,
+    center: Vec3,
+    transform: impl Into<Transform>,
+) -> Sdf {
+    placed(center, transform, move |p| link(p, length, r1, r2))
+}
+
+pub fn sd_solid_angle(
+    angle: f32,
+    radius: f32,
+    center: Vec3,
+    transform: impl Into<Transform>,
+) -> Sdf {
+    placed(center, transform, move |p| solid_angle(p, angle, radius))
+}
+
+pub fn sd_capped_torus(
+    angle: f32,
+    major_radius: f32,
+    minor_radius: f32,
+    center: Vec3,
+    transform: impl Into<Transform>,
+) -> Sdf {
+    placed(center, transform, move |p| {
+        capped_torus(p, angle, major_radius, minor_radius)
+    })
+}
+
+pub fn ud_triangle(
+    center: Vec3,
+    a: Vec3,
+    b: Vec3,
+    c: Vec3,
+    transform: impl Into<Transform>,
+) -> Sdf {
+    placed(center, transform, move |p| unsigned_triangle(p, a, b, c))
+}
+
+pub fn ud_quad(
+    center: Vec3,
+    a: Vec3,
+    b: Vec3,
+    c: Vec3,
+    d: Vec3,
+    transform: impl Into<Transform>,
+) -> Sdf {
+    placed(center, transform, move |p| unsigned_quad(p, a, b, c, d))
+}
+
+// Primitives in the plane, which become solids by extrusion or revolution.
+
+pub fn circle(p: Vec2, radius: f32) -> f32 {
+    p.length() - radius
+}
+
+// The rectangle from -`size` to `size`.
+pub fn rect(p: Vec2, size: Vec2) -> f32 {
+    let d = p.abs() - size;
+    d.max(Vec2::ZERO).length() + d.x.max(d.y).min(0.0)
+}
+
+// The unsigned distance to the segment `ab`.
+pub fn segment(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let (pa, ba) = (p - a, b - a);
+    let h = (pa.dot(ba) / ba.dot(ba)).clamp(0.0, 1.0);
+    (pa - ba * h).length()
+}
+
+pub fn triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> f32 {
+    let (e0, e1, e2) = (b - a, c - b, a - c);
+    let (v0, v1, v2) = (p - a, p - b, p - c);
+    let edge = |v: Vec2, e: Vec2| v - e * (v.dot(e) / e.dot(e)).clamp(0.0, 1.0);
+    let (pq0, pq1, pq2) = (edge(v0, e0), edge(v1, e1), edge(v2, e2));
+    let s = sign(e0.x * e2.y - e0.y * e2.x);
+    let d = Vec2::new(pq0.dot(pq0), s * (v0.x * e0.y - v0.y * e0.x))
+        .min(Vec2::new(pq1.dot(pq1), s * (v1.x * e1.y - v1.y * e1.x)))
+        .min(Vec2::new(pq2.dot(pq2), s * (v2.x * e2.y - v2.y * e2.x)));
+    -d.x.sqrt() * sign(d.y)
+}
+
+// The regular hexagon `radius` from its center to the middle of its sides,
+// which are parallel to x.
+pub fn hexagon(p: Vec2, radius: f32) -> f32 {
+    let k = Vec3::new(-0.866_025_4, 0.5, 0.577_350_27);
+    let p = p.abs();
+    let p = p - 2.0 * k.xy().dot(p).min(0.0) * k.xy();
+    let p = p - Vec2::new(p.x.clamp(-k.z * radius, k.z * radius), radius);
+    p.length() * sign(p.y)
+}
+
+// The solid swept by a shape in the xy plane at distance `d` from `p` when
+// it is pulled `height` each way along z.
+pub fn extrusion(d: f32, z: f32, height: f32) -> f32 {
+    let w = Vec2::new(d, z.abs() - height);
+    w.x.max(w.y).min(0.0) + w.max(Vec2::ZERO).length()
+}
+
+pub fn sd_circle(radius: f32) -> Sdf2 {
+    Box::new(move |p| circle(p, radius))
+}
+
+pub fn sd_rect(size: Vec2) -> Sdf2 {
+    Box::new(move |p| rect(p, size))
+}
+
+pub fn sd_segment(a: Vec2, b: Vec2) -> Sdf2 {
+    Box::new(move |p| segment(p, a, b))
+}
+
+pub fn sd_triangle(a: Vec2, b: Vec2, c: Vec2) -> Sdf2 {
+    Box::new(move |p| triangle(p, a, b, c))
+}
+
+pub fn sd_hexagon(radius: f32) -> Sdf2 {
+    Box::new(move |p| hexagon(p, radius))
+}
+
+// `shape` pulled `height` each way along z.
+pub fn sd_extrude(shape: Sdf2, height: f32, center: Vec3, transform: impl Into<Transform>) -> Sdf {
+    placed(center, transform, move |p| {
+        extrusion(shape(p.xy()), p.z, height)
+    })
+}
+
+// `shape` spun around the y axis, with its x axis `offset` from it.
+pub fn sd_revolve(shape: Sdf2, offset: f32, center: Vec3, transform: impl Into<Transform>) -> Sdf {
+    placed(center, transform, move |p| {
+        shape(Vec2::new(p.xz().length() - offset, p.y))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{v3, I, ZERO3};
+    use crate::differential::sample_points;
+
+    #[test]
+    fn primitives() {
+        let exact = [
+            sd_cone(0.5, 2.0, ZERO3, I),
+            sd_capped_cone(1.0, 1.5, 0.5, ZERO3, I),
+            sd_round_cone(1.0, 0.5, 2.0, ZERO3, I),
+            sd_hex_prism(1.0, 0.5, ZERO3, I),
+            sd_octahedron(1.5, ZERO3, I),
+            sd_pyramid(1.5, ZERO3, I),
+            sd_link(1.0, 1.0, 0.3, ZERO3, I),
+            sd_solid_angle(0.7, 2.0, ZERO3, I),
+            sd_capped_torus(2.0, 1.5, 0.4, ZERO3, I),
+            ud_triangle(
+                ZERO3,
+                v3(0.0, 0.0, 0.0),
+                v3(2.0, 0.0, 0.0),
+                v3(0.0, 1.0, 1.0),
+                I,
+            ),
+            ud_quad(
+                ZERO3,
+                v3(-1.0, 0.0, -1.0),
+                v3(1.0, 0.0, -1.0),
+                v3(1.0, 0.0, 1.0),
+                v3(-1.0, 0.0, 1.0),
+                I,
+            ),
+            sd_extrude(sd_hexagon(1.0), 0.5, ZERO3, I),
+            sd_revolve(
+                sd_triangle(
+                    Vec2::new(0.0, 0.0),
+                    Vec2::new(1.0, 0.0),
+                    Vec2::new(0.0, 1.0),
+                ),
+                1.0,
+                ZERO3,
+                I,
+            ),
+        ];
+        // An exact distance changes no faster than the point moves.
+        let points = sample_points(ZERO3, 4.0, 500);
+        for (i, sdf) in exact.iter().enumerate() {
+            for w in points.windows(2) {
+                assert!(
+                    (sdf(w[0]) - sdf(w[1])).abs() <= w[0].distance(w[1]) + 1e-4,
+                    "{}",
+                    i
+                );
+            }
+        }
+        assert!((octahedron(v3(2.0, 0.0, 0.0), 1.0) - 1.0).abs() < 1e-6);
+        assert!((cone(v3(0.0, 1.0, 0.0), 0.5, 2.0) - 1.0).abs() < 1e-6);
+        assert!(pyramid(v3(0.0, 0.5, 0.0), 1.0) < 0.0);
+        assert!((capped_torus(v3(0.0, 1.0, 0.0), 2.0, 1.0, 0.25) + 0.25).abs() < 1e-6);
+        let (a, b, c) = (ZERO3, v3(2.0, 0.0, 0.0), v3(0.0, 0.0, 2.0));
+        assert_eq!(unsigned_triangle(v3(0.5, -1.0, 0.5), a, b, c), 1.0);
+        assert_eq!(unsigned_triangle(v3(-3.0, 0.0, 0.0), a, b, c), 3.0);
+        assert_eq!(rect(Vec2::new(0.0, 3.0), Vec2::new(1.0, 2.0)), 1.0);
+        assert!((hexagon(Vec2::new(0.0, 0.5), 1.0) + 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn placement() {
+        // The center moves every shape, the plane included.
+        let floor = sd_plane(v3(0.0, 2.0, 0.0), 0.0, v3(0.0, -1.0, 0.0), I);
+        assert_eq!(floor(ZERO3), 1.0);
+        let twice = glam::Affine3A::from_scale(v3(2.0, 2.0, 2.0));
+        let ball = sd_sphere(1.0, v3(5.0, 0.0, 0.0), twice);
+        assert_eq!(ball(v3(8.0, 0.0, 0.0)), 1.0);
+        let post = sd_inf_cylinder(1.0, v3(3.0, 0.0, 0.0), I);
+        assert_eq!(post(v3(3.0, 7.0, 2.0)), 1.0);
+    }
+
+    #[test]
+    fn ellipsoid_bound() {
+        let radii = v3(1.0, 2.0, 0.5);
+        let surface: Vec<Vec3> = sample_points(ZERO3, 1.0, 20000)
+            .into_iter()
+            .map(|p| p.normalize() * radii)
+            .collect();
+        for p in sample_points(ZERO3, 3.0, 300) {
+            let nearest = surface
+                .iter()
+                .map(|q| p.distance(*q))
+                .fold(f32::INFINITY, f32::min);
+            assert!(ellipsoid(p, radii).abs() <= nearest + 1e-3, "{}", p);
+        }
+        assert_eq!(ellipsoid(v3(3.0, 0.0, 0.0), v3(1.0, 1.0, 1.0)), 2.0);
+    }
+}
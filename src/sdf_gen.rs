@@ -1,8 +1,12 @@
-use arrow::{codegen::generate_code, pratt::parse, sdf::examples};
+use arrow::{codegen::generate_module, optimize::optimize, scene::library};
 
+// Prints a Rust module for a scene, e.g. `cargo run --bin sdf_gen -- asurf`.
 fn main() {
-    let examples = examples();
-    let (mut input, _) = examples.get("asurf").unwrap();
-    let ast = parse(&mut input);
-    print!("{}", generate_code(&ast, 0.1, 0.2));
+    let name = std::env::args().nth(1).unwrap_or("asurf".to_string());
+    let scene = &library()[name.as_str()];
+    let ast = optimize(&scene.ast());
+    print!(
+        "{}",
+        generate_module(&ast, &name, scene.param("a0"), scene.param("a1"))
+    );
 }
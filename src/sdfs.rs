@@ -1,93 +1,97 @@
-use crate::core::{fbm_perlin, fbm_value, v3, I, ZERO3};
+use crate::core::{v3, I, ZERO3};
+use crate::noise::fbm_value;
 use crate::sdf::sd_box;
-use crate::{box2, box3, dot, functions::*, length, perlin_noise, value_noise};
+use crate::{box2, box3, dot, functions::*, length, value_noise};
 use glam::{Vec2, Vec3};
+use std::f32::consts::PI;
 
+// A hand written scene, `name` is the scene file it compiles.
 pub struct Scene {
+    pub name: &'static str,
     pub sdf: fn(Vec3) -> f32,
-    pub camera: Vec3,
 }
 
-pub fn scene(sdf_name: &'static str) -> Scene {
-    match sdf_name {
-        "cross" => Scene {
-            sdf: cross,
-            camera: v3(-10.0, 30.0, -15.0),
-        },
-        "box_of_balls" => Scene {
-            sdf: box_of_balls,
-            camera: v3(0.0, 0.0, -20.0),
-        },
-        "sponge" => Scene {
-            sdf: sponge,
-            camera: v3(-20.0, 20.0, -5.0),
-        },
-        "donuts" => Scene {
-            sdf: donuts,
-            camera: v3(0.0, 0.0, -20.0),
-        },
-        "desire" => Scene {
-            sdf: desire,
-            camera: v3(0.0, 0.0, -20.0),
-        },
-        "apollonius" => Scene {
-            sdf: apollonius,
-            camera: v3(20.0, 0.0, -60.0),
-        },
-        "hyperplane" => Scene {
-            sdf: hyperplane,
-            camera: v3(20.0, 0.0, -60.0),
-        },
-        "singularity" => Scene {
-            sdf: singularity,
-            camera: v3(0.0, 0.0, -60.0),
-        },
-        "mycelia" => Scene {
-            sdf: mycelia,
-            camera: v3(10.0, 10.0, -15.0),
-        },
-        "else" => Scene {
-            sdf: els,
-            camera: v3(0.0, 20.0, -20.0),
-        },
-        "gnarl" => Scene {
-            sdf: gnarl,
-            camera: v3(0.0, 0.0, -50.0),
-        },
-        "system" => Scene {
-            sdf: system,
-            camera: v3(0.0, 10.0, -50.0),
-        },
-        "temple" => Scene {
-            sdf: temple,
-            camera: v3(0.0, -5.0, -40.0),
-        },
-        "toy" => Scene {
-            sdf: toy,
-            camera: v3(-0.5, -5.0, -10.0),
-        },
-        "ghost" => Scene {
-            sdf: ghost,
-            camera: v3(-10.0, 2.0, -40.0),
-        },
-        "shai_hulud" => Scene {
-            sdf: shai_hulud,
-            camera: v3(-8.0, 5.0, 30.0),
-        },
-        "plato" => Scene {
-            sdf: plato,
-            camera: v3(0.0, 30.0, -10.0),
-        },
-        "pawns" => Scene {
-            sdf: pawns,
-            camera: v3(5.0, 8.0, -20.0),
-        },
-        "asurf" => Scene {
-            sdf: asurf,
-            camera: v3(2.0, 5.0, -1.0),
-        },
-        _ => panic!("Unknown scene: {}", sdf_name),
-    }
+pub const SCENES: [Scene; 19] = [
+    Scene {
+        name: "cross",
+        sdf: cross,
+    },
+    Scene {
+        name: "box_of_balls",
+        sdf: box_of_balls,
+    },
+    Scene {
+        name: "sponge",
+        sdf: sponge,
+    },
+    Scene {
+        name: "donuts",
+        sdf: donuts,
+    },
+    Scene {
+        name: "desire",
+        sdf: desire,
+    },
+    Scene {
+        name: "apollonius",
+        sdf: apollonius,
+    },
+    Scene {
+        name: "hyperplane",
+        sdf: hyperplane,
+    },
+    Scene {
+        name: "singularity",
+        sdf: singularity,
+    },
+    Scene {
+        name: "mycelia",
+        sdf: mycelia,
+    },
+    Scene {
+        name: "else",
+        sdf: els,
+    },
+    Scene {
+        name: "gnarl",
+        sdf: gnarl,
+    },
+    Scene {
+        name: "system",
+        sdf: system,
+    },
+    Scene {
+        name: "temple",
+        sdf: temple,
+    },
+    Scene {
+        name: "toy",
+        sdf: toy,
+    },
+    Scene {
+        name: "ghost",
+        sdf: ghost,
+    },
+    Scene {
+        name: "shai_hulud",
+        sdf: shai_hulud,
+    },
+    Scene {
+        name: "plato",
+        sdf: plato,
+    },
+    Scene {
+        name: "pawns",
+        sdf: pawns,
+    },
+    Scene {
+        name: "asurf",
+        sdf: asurf,
+    },
+];
+
+pub fn compiled(name: &str) -> Option<fn(Vec3) -> f32> {
+    SCENES.iter().find(|s| s.name == name).map(|s| s.sdf)
 }
 
 // Camera: v3(-10.0, 30.0, -15.0)
@@ -172,15 +176,14 @@ pub fn sponge(p: Vec3) -> f32 {
     let y = (modulo(y + 9f32, 18f32) - 9f32) * 3f32;
     let z = (modulo(z + 9f32, 18f32) - 9f32) * 3f32;
     let s = s / 3f32;
-    let r = corner(
+    corner(
         r,
         -union(vec![
             box2!(x, y, 9f32),
             box2!(y, z, 9f32),
             box2!(z, x, 9f32),
         ]) * s,
-    );
-    r
+    )
 }
 
 pub fn donuts(p: Vec3) -> f32 {
@@ -478,11 +481,11 @@ pub fn gnarl(p: Vec3) -> f32 {
 pub fn system(p: Vec3) -> f32 {
     let Vec3 { x, y, z } = p;
     let a0 = 0.2;
-    let pi = 3.1415f32;
+    let pi = PI;
     let [z, y] = rot0(z, y, a0);
     let z = z + 10f32;
     let r = sqrt(x * x + y * y);
-    let t = atan2(y, x) / 2f32 * pi;
+    let t = atan2(y, x) / (2f32 * pi);
     let d = 10000f32;
     let d = min(
         box3!(
@@ -0,0 +1,269 @@
+// Renders an image in square tiles, in passes from big blocks of pixels down
+// to single pixels with full anti-aliasing. After every batch of tiles the
+// caller hears how far along the pass is and sees the image so far. A render
+// can be cancelled between tiles, and a `TiledImage` saved part way through
+// picks up where it left off.
+use crate::core::Lum;
+use crate::march::View;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+pub const TILE: u32 = 32;
+
+// The size of the pixel blocks and the anti-aliasing of each pass.
+pub fn passes(aa: u32) -> Vec<(u32, u32)> {
+    let mut passes = vec![(8, 1), (4, 1), (2, 1), (1, 1)];
+    if aa > 1 {
+        passes.push((1, aa));
+    }
+    passes
+}
+
+// Shared between the renderer and whoever wants to stop it.
+#[derive(Debug, Clone, Default)]
+pub struct Cancel(Arc<AtomicBool>);
+
+impl Cancel {
+    pub fn new() -> Self {
+        Cancel::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+// How far along the current pass is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub block: u32,
+    pub aa: u32,
+    pub done: usize,
+    pub tiles: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TiledImage {
+    pub width: u32,
+    pub height: u32,
+    // Set by the caller to tell its checkpoints apart, e.g. a hash of the scene.
+    pub key: u64,
+    // Linear, see `tonemap` for turning them into an image.
+    pub pixels: Vec<Lum>,
+    // The block size and anti-aliasing each tile was rendered with, if it was.
+    quality: Vec<Option<(u32, u32)>>,
+}
+
+impl TiledImage {
+    pub fn new(width: u32, height: u32, key: u64) -> Self {
+        let tiles = (width.div_ceil(TILE) * height.div_ceil(TILE)) as usize;
+        TiledImage {
+            width,
+            height,
+            key,
+            pixels: vec![0.0; (width * height) as usize],
+            quality: vec![None; tiles],
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    fn needs(&self, tile: usize, block: u32, aa: u32) -> bool {
+        !self.quality[tile].is_some_and(|(b, a)| b <= block && a >= aa)
+    }
+
+    // Whether every tile is at least as good as a pass with `block` and `aa`.
+    pub fn is_done(&self, block: u32, aa: u32) -> bool {
+        (0..self.quality.len()).all(|tile| !self.needs(tile, block, aa))
+    }
+
+    // The origin and size of a tile.
+    fn bounds(&self, tile: usize) -> (u32, u32, u32, u32) {
+        let columns = self.width.div_ceil(TILE) as usize;
+        let x = (tile % columns) as u32 * TILE;
+        let y = (tile / columns) as u32 * TILE;
+        (x, y, TILE.min(self.width - x), TILE.min(self.height - y))
+    }
+
+    fn render_tile(&self, view: &View, tile: usize, block: u32, aa: u32) -> Vec<Lum> {
+        let (x0, y0, w, h) = self.bounds(tile);
+        if block == 1 {
+            return view.region(x0, y0, w, h, aa);
+        }
+        let mut pixels = vec![0.0; (w * h) as usize];
+        for by in (0..h).step_by(block as usize) {
+            for bx in (0..w).step_by(block as usize) {
+                let (bw, bh) = (block.min(w - bx), block.min(h - by));
+                // The middle of the block.
+                let x = (x0 + bx) as f32 + (bw - 1) as f32 / 2.0;
+                let y = (y0 + by) as f32 + (bh - 1) as f32 / 2.0;
+                let col = view.pixel(x, y, aa);
+                for y in by..by + bh {
+                    let row = (y * w) as usize;
+                    pixels[row + bx as usize..row + (bx + bw) as usize].fill(col);
+                }
+            }
+        }
+        pixels
+    }
+
+    // Renders the tiles that aren't already as good as `block` and `aa`.
+    pub fn pass(
+        &mut self,
+        view: &View,
+        block: u32,
+        aa: u32,
+        cancel: &Cancel,
+        mut progress: impl FnMut(Progress, &TiledImage),
+    ) -> Result<(), Cancelled> {
+        assert_eq!((view.width, view.height), (self.width, self.height));
+        let todo: Vec<usize> = (0..self.quality.len())
+            .filter(|&tile| self.needs(tile, block, aa))
+            .collect();
+        let batch = rayon::current_num_threads() * 4;
+        for (i, tiles) in todo.chunks(batch).enumerate() {
+            let rendered: Vec<(usize, Vec<Lum>)> = tiles
+                .par_iter()
+                .filter(|_| !cancel.is_cancelled())
+                .map(|&tile| (tile, self.render_tile(view, tile, block, aa)))
+                .collect();
+            for (tile, pixels) in rendered {
+                let (x0, y0, w, _) = self.bounds(tile);
+                for (y, row) in pixels.chunks(w as usize).enumerate() {
+                    let start = ((y0 + y as u32) * self.width + x0) as usize;
+                    self.pixels[start..start + w as usize].copy_from_slice(row);
+                }
+                self.quality[tile] = Some((block, aa));
+            }
+            if cancel.is_cancelled() {
+                return Err(Cancelled);
+            }
+            let done = (i * batch + tiles.len()).min(todo.len());
+            progress(
+                Progress {
+                    block,
+                    aa,
+                    done,
+                    tiles: todo.len(),
+                },
+                self,
+            );
+        }
+        Ok(())
+    }
+
+    // All of `passes` in order, skipping the work a checkpoint already did.
+    pub fn render(
+        &mut self,
+        view: &View,
+        passes: &[(u32, u32)],
+        cancel: &Cancel,
+        mut progress: impl FnMut(Progress, &TiledImage),
+    ) -> Result<(), Cancelled> {
+        for &(block, aa) in passes {
+            self.pass(view, block, aa, cancel, &mut progress)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{v3, Light, Sdf, ZERO3};
+    use crate::march::render_linear;
+    use glam::Vec3;
+    use std::sync::atomic::AtomicUsize;
+
+    const LIGHTS: &[Light] = &[Light::new(Vec3::new(0.0, 0.0, -50.0), 1.0)];
+
+    fn ball() -> Sdf {
+        Box::new(|p| p.length() - 5.0)
+    }
+
+    #[test]
+    fn matches_render() {
+        let sdf = ball();
+        let view = View::new(&sdf, v3(0.0, 0.0, -20.0), ZERO3, LIGHTS, 0.75, 70, 40);
+        let mut image = TiledImage::new(70, 40, 0);
+        let mut passes_done = 0;
+        image
+            .render(&view, &passes(2), &Cancel::new(), |p, _| {
+                passes_done += (p.done == p.tiles) as usize
+            })
+            .unwrap();
+        assert_eq!(passes_done, passes(2).len());
+        assert!(image.is_done(1, 2));
+        assert_eq!(image.pixels, render_linear(&view, 2));
+    }
+
+    #[test]
+    fn adaptive() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let sdf: Sdf = Box::new(|p| {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            p.length() - 5.0
+        });
+        let mut view = View::new(&sdf, v3(0.0, 0.0, -20.0), ZERO3, LIGHTS, 0.75, 70, 40);
+        let full = render_linear(&view, 3);
+        let full_calls = CALLS.swap(0, Ordering::Relaxed);
+        view.sampling.adaptive = Some(0.02);
+        let adaptive = render_linear(&view, 3);
+        assert!(CALLS.load(Ordering::Relaxed) < full_calls / 2);
+        // Only the pixels around the edge of the ball get all their rays, the
+        // rest are close to what they would be with them.
+        assert!(full
+            .iter()
+            .zip(&adaptive)
+            .all(|(a, b)| (a - b).abs() <= 8.0 / 255.0));
+        let mut image = TiledImage::new(70, 40, 0);
+        image.pass(&view, 1, 3, &Cancel::new(), |_, _| {}).unwrap();
+        assert_eq!(image.pixels, adaptive);
+    }
+
+    #[test]
+    fn cancel_and_resume() {
+        let sdf = ball();
+        let view = View::new(&sdf, v3(0.0, 0.0, -20.0), ZERO3, LIGHTS, 0.75, 200, 150);
+        let mut image = TiledImage::new(200, 150, 7);
+        let cancel = Cancel::new();
+        cancel.cancel();
+        assert_eq!(image.pass(&view, 1, 1, &cancel, |_, _| {}), Err(Cancelled));
+        assert!(!image.is_done(8, 1));
+        image.pass(&view, 4, 1, &Cancel::new(), |_, _| {}).unwrap();
+        assert!(image.is_done(8, 1) && !image.is_done(2, 1));
+
+        let path = std::env::temp_dir().join("arrow_tiles_checkpoint.json");
+        image.save(&path).unwrap();
+        let mut resumed = TiledImage::load(&path).unwrap();
+        assert_eq!(resumed, image);
+        let mut tiles = 0;
+        resumed
+            .pass(&view, 8, 1, &Cancel::new(), |p, _| tiles += p.tiles)
+            .unwrap();
+        assert_eq!(tiles, 0);
+        resumed
+            .pass(&view, 1, 1, &Cancel::new(), |_, _| {})
+            .unwrap();
+        assert_eq!(resumed.pixels, render_linear(&view, 1));
+    }
+}
@@ -0,0 +1,178 @@
+// Turns the linear luminance a render computes into an image file. PNGs are
+// scaled by 2 to the `exposure`, squeezed into 0 to 1 by the `operator`,
+// optionally sRGB encoded and written with 8 or 16 `bits`. Radiance HDR and
+// OpenEXR files keep the linear values as they are. The default, clipping
+// at 1 with no encoding, is how arrow has always written its images.
+use crate::core::Lum;
+use image::codecs::hdr::HdrEncoder;
+use image::{ImageBuffer, ImageResult, Luma, Rgb};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operator {
+    #[default]
+    Clip,
+    Reinhard,
+    Aces,
+}
+
+impl Operator {
+    pub fn apply(self, x: f32) -> f32 {
+        match self {
+            Operator::Clip => x,
+            Operator::Reinhard => x / (1.0 + x),
+            // Narkowicz's fit of the ACES filmic curve.
+            Operator::Aces => (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14),
+        }
+        .clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToneMap {
+    pub operator: Operator,
+    pub exposure: f32,
+    pub srgb: bool,
+    pub bits: u8,
+}
+
+impl Default for ToneMap {
+    fn default() -> Self {
+        ToneMap {
+            operator: Operator::Clip,
+            exposure: 0.0,
+            srgb: false,
+            bits: 8,
+        }
+    }
+}
+
+pub fn srgb(x: f32) -> f32 {
+    if x <= 0.0031308 {
+        12.92 * x
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// The inverse of `srgb`, the linear value of an 8 or 16 bit image's.
+pub fn linear(x: f32) -> f32 {
+    if x <= 0.04045 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+impl ToneMap {
+    // The displayed value of `lum`, from 0 to 1.
+    pub fn map(&self, lum: Lum) -> f32 {
+        let x = self.operator.apply(lum * self.exposure.exp2());
+        if self.srgb {
+            srgb(x)
+        } else {
+            x
+        }
+    }
+
+    pub fn to_u8(&self, lum: &[Lum]) -> Vec<u8> {
+        lum.iter().map(|&l| (self.map(l) * 255.0) as u8).collect()
+    }
+
+    pub fn to_u16(&self, lum: &[Lum]) -> Vec<u16> {
+        lum.iter()
+            .map(|&l| (self.map(l) * 65535.0) as u16)
+            .collect()
+    }
+
+    // Writes a `width` by `height` image, its format chosen by the extension.
+    pub fn save(
+        &self,
+        path: impl AsRef<Path>,
+        width: u32,
+        height: u32,
+        lum: &[Lum],
+    ) -> ImageResult<()> {
+        let path = path.as_ref();
+        match path.extension().and_then(|e| e.to_str()) {
+            // The image crate only writes Radiance files through the encoder.
+            Some("hdr") => {
+                let rgb: Vec<Rgb<f32>> = lum.iter().map(|&l| Rgb([l, l, l])).collect();
+                let file = BufWriter::new(File::create(path)?);
+                HdrEncoder::new(file).encode(&rgb, width as usize, height as usize)
+            }
+            Some("exr") => {
+                let rgb = lum.iter().flat_map(|&l| [l, l, l]).collect();
+                ImageBuffer::<Rgb<f32>, Vec<f32>>::from_raw(width, height, rgb)
+                    .expect("the image is width by height")
+                    .save(path)
+            }
+            _ if self.bits == 16 => {
+                ImageBuffer::<Luma<u16>, Vec<u16>>::from_raw(width, height, self.to_u16(lum))
+                    .expect("the image is width by height")
+                    .save(path)
+            }
+            _ => image::save_buffer(path, &self.to_u8(lum), width, height, image::ColorType::L8),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operators() {
+        let clip = ToneMap::default();
+        assert_eq!(clip.to_u8(&[0.5, 1.5, -1.0]), vec![127, 255, 0]);
+        for operator in [Operator::Reinhard, Operator::Aces] {
+            let tone = ToneMap {
+                operator,
+                ..Default::default()
+            };
+            // Brighter is still brighter, but never past white.
+            assert!(tone.map(4.0) > tone.map(2.0) && tone.map(100.0) <= 1.0);
+        }
+        let brighter = ToneMap {
+            exposure: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(brighter.map(0.25), 0.5);
+        assert!((srgb(0.5) - 0.7354).abs() < 1e-4);
+        assert_eq!(srgb(0.0), 0.0);
+        assert!((srgb(1.0) - 1.0).abs() < 1e-6);
+        for x in [0.001, 0.2, 0.5, 1.0] {
+            assert!((linear(srgb(x)) - x).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn formats() {
+        let lum = vec![0.0, 0.5, 2.0, 10.0];
+        let dir = std::env::temp_dir();
+        let deep = ToneMap {
+            operator: Operator::Reinhard,
+            bits: 16,
+            ..Default::default()
+        };
+        deep.save(dir.join("arrow_tone.png"), 2, 2, &lum).unwrap();
+        let png = image::open(dir.join("arrow_tone.png"))
+            .unwrap()
+            .into_luma16();
+        assert_eq!(png.into_raw(), deep.to_u16(&lum));
+        for extension in ["hdr", "exr"] {
+            let path = dir.join(format!("arrow_tone.{}", extension));
+            ToneMap::default().save(&path, 2, 2, &lum).unwrap();
+            let hdr = image::open(&path).unwrap().into_rgb32f();
+            let back: Vec<f32> = hdr.pixels().map(|p| p[0]).collect();
+            for (a, b) in back.iter().zip(&lum) {
+                assert!((a - b).abs() < 0.05 * b.max(0.01), "{} {}", a, b);
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+// Watches a scene file, or a `.arrow` file of DSL source, and renders it to a
+// PNG every time it changes. Each change is rendered in the passes of
+// `tiles::passes`, each pass overwriting the PNG, and a change during a pass
+// cancels it. Parse and eval errors are printed and the watcher waits for the
+// next change.
+use crate::core::Sdf;
+use crate::post::Frame;
+use crate::scene::SceneFile;
+use crate::tiles::{passes, Cancel, TiledImage};
+use glam::Vec3;
+use std::any::Any;
+use std::fs;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use std::thread::sleep;
+use std::time::{Duration, Instant, SystemTime};
+
+pub const POLL: Duration = Duration::from_millis(250);
+
+// The camera of a `.arrow` file.
+pub const CAMERA: Vec3 = Vec3::new(0.0, 0.0, -20.0);
+
+fn message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(s) => *s,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(s) => s.to_string(),
+            Err(_) => "unknown error".to_string(),
+        },
+    }
+}
+
+// The parser and interpreter panic on errors, this turns them into messages.
+fn catch<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    catch_unwind(AssertUnwindSafe(f)).map_err(message)
+}
+
+pub fn load(path: &Path) -> Result<SceneFile, String> {
+    if path.extension().is_some_and(|e| e == "arrow") {
+        let source = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        Ok(SceneFile::new(&name, &source, CAMERA))
+    } else {
+        SceneFile::load(path).map_err(|e| e.to_string())
+    }
+}
+
+// Parses the source and evaluates it once at the camera, so most errors
+// show up before rendering.
+pub fn prepare(scene: &SceneFile) -> Result<Sdf, String> {
+    let sdf = catch(|| scene.sdf())?;
+    catch(|| sdf(scene.camera.position))?;
+    Ok(sdf)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+pub fn watch(path: &Path, output: &Path) -> ! {
+    // Errors are reported by `catch`, not by the default hook.
+    std::panic::set_hook(Box::new(|_| {}));
+    println!("watching {}, writing {}", path.display(), output.display());
+    let mut seen = None;
+    loop {
+        let changed = modified(path);
+        if changed.is_none() || changed == seen {
+            sleep(POLL);
+            continue;
+        }
+        seen = changed;
+        let (scene, sdf) = match load(path).and_then(|s| prepare(&s).map(|sdf| (s, sdf))) {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                eprintln!("error: {}", e);
+                continue;
+            }
+        };
+        let (width, height) = (scene.settings.width, scene.settings.height);
+        // Loading the environment light's image can fail too.
+        let view = match catch(|| scene.view(&sdf)) {
+            Ok(view) => view,
+            Err(e) => {
+                eprintln!("error: {}", e);
+                continue;
+            }
+        };
+        let mut image = TiledImage::new(width, height, 0);
+        let cancel = Cancel::new();
+        let mut start = Instant::now();
+        let rendered = catch(|| {
+            image.render(&view, &passes(scene.settings.aa), &cancel, |p, image| {
+                // Start over if the file changes during a pass.
+                if modified(path) != seen {
+                    cancel.cancel();
+                }
+                if p.done < p.tiles {
+                    return;
+                }
+                let frame = Frame::new(width, height, image.pixels.clone(), scene.settings.tone);
+                match frame.post(&scene.settings.post).save(output) {
+                    Ok(()) => println!(
+                        "{}x{} aa {} in {:.2}s",
+                        width.div_ceil(p.block),
+                        height.div_ceil(p.block),
+                        p.aa,
+                        start.elapsed().as_secs_f32()
+                    ),
+                    Err(e) => eprintln!("error: {}", e),
+                }
+                start = Instant::now();
+            })
+        });
+        if let Err(e) = rendered {
+            eprintln!("error: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors() {
+        let error = |source| prepare(&SceneFile::new("test", source, CAMERA)).err();
+        assert!(error("L(x,y,z)-5)").unwrap().contains("bad token"));
+        assert!(error("L(x,y,z)-q")
+            .unwrap()
+            .contains("variable not found: q"));
+        assert!(prepare(&SceneFile::new("ball", "L(x,y,z)-5", CAMERA)).is_ok());
+    }
+}
@@ -0,0 +1,87 @@
+// The modules in tests/generated are the output of `generate_traced_module`
+// for every scene in the library, so building this test compile-checks the generator. Run with
+// ARROW_BLESS=1 to rewrite them after changing the code generator.
+mod generated;
+
+use arrow::codegen::generate_traced_module;
+use arrow::scene::library;
+use std::fs;
+use std::panic::catch_unwind;
+use std::path::Path;
+use std::process::Command;
+
+// These read variables they never assign, so there's no code for them.
+const UNGENERATED: [&str; 1] = ["ondu"];
+
+fn module_name(name: &str) -> String {
+    match name {
+        "else" => "r#else".to_string(),
+        name => name.to_string(),
+    }
+}
+
+#[test]
+fn generated_modules_are_current() {
+    let bless = std::env::var("ARROW_BLESS").is_ok();
+    // Without rustfmt the generated code isn't formatted like the checked in
+    // files, but it still has to compile, which it does by being here.
+    if !bless && Command::new("rustfmt").arg("--version").output().is_err() {
+        return;
+    }
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/generated");
+    let library = library();
+    let mut modules = String::new();
+    let mut scenes = String::new();
+    let mut stale = Vec::new();
+    for scene in library.iter() {
+        let name = scene.name.as_str();
+        let ast = scene.ast();
+        let code = catch_unwind(|| {
+            generate_traced_module(&ast, name, scene.param("a0"), scene.param("a1"))
+        });
+        let Ok(code) = code else {
+            assert!(UNGENERATED.contains(&name), "{} can't be generated", name);
+            continue;
+        };
+        assert!(!UNGENERATED.contains(&name), "{} can be generated", name);
+        modules.push_str(&format!("pub mod {};\n", module_name(name)));
+        scenes.push_str(&format!(
+            "        (\"{}\", {}::scene(), {}::traced),\n",
+            name,
+            module_name(name),
+            module_name(name)
+        ));
+        let path = dir.join(format!("{}.rs", name));
+        if bless {
+            fs::write(&path, code).unwrap();
+        } else if fs::read_to_string(&path).ok().as_deref() != Some(code.as_str()) {
+            stale.push(name);
+        }
+    }
+    let modules = format!(
+        "// Generated by tests/codegen.rs.\nuse arrow::differential::Traced;\nuse arrow::sdfs::Scene;\n\n{}\npub fn scenes() -> Vec<(&'static str, Scene, Traced)> {{\n    vec![\n{}    ]\n}}\n",
+        modules, scenes
+    );
+    let path = dir.join("mod.rs");
+    if bless {
+        fs::write(&path, modules).unwrap();
+    } else if fs::read_to_string(&path).ok().as_deref() != Some(modules.as_str()) {
+        stale.push("mod");
+    }
+    assert!(
+        stale.is_empty(),
+        "regenerate {:?} with ARROW_BLESS=1",
+        stale
+    );
+}
+
+#[test]
+fn generated_scenes() {
+    let library = library();
+    for (name, scene, _) in generated::scenes() {
+        assert_eq!(scene.name, name);
+        let camera = library[name].camera.position;
+        assert!((scene.sdf)(camera).is_finite(), "{}", name);
+    }
+    assert!(generated::sphere::sphere(glam::Vec3::new(0.0, 18.0, 0.0)) < 0.0);
+}
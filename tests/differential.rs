@@ -0,0 +1,52 @@
+// Samples every scene at POINTS points in the cube around the origin that
+// reaches its camera, and checks that the interpreter, the generated code in
+// tests/generated and the hand written scenes in `sdfs` compute the same
+// distances.
+mod generated;
+
+use arrow::differential::{compare, compare_traced, sample_points};
+use arrow::scene::library;
+use arrow::sdfs::SCENES;
+use rayon::prelude::*;
+
+const POINTS: usize = 2000;
+
+// The hand written scenes that were edited after they were generated.
+const EDITED: [&str; 2] = ["gnarl", "pawns"];
+
+#[test]
+fn generated_code_matches_interpreter() {
+    let library = library();
+    let failures: Vec<String> = generated::scenes()
+        .into_par_iter()
+        .filter_map(|(name, _, traced)| {
+            let scene = &library[name];
+            let (a0, a1) = (scene.param("a0"), scene.param("a1"));
+            let points = sample_points(glam::Vec3::ZERO, scene.camera.position.length(), POINTS);
+            let divergence = compare_traced(&scene.ast(), a0, a1, traced, &points)?;
+            Some(format!("{} {}", name, divergence))
+        })
+        .collect();
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn hand_written_scenes_match_interpreter() {
+    let library = library();
+    let failures: Vec<String> = SCENES
+        .par_iter()
+        .filter_map(|compiled| {
+            let (name, scene) = (compiled.name, &library[compiled.name]);
+            let (a0, a1) = (scene.param("a0"), scene.param("a1"));
+            let points = sample_points(glam::Vec3::ZERO, scene.camera.position.length(), POINTS);
+            match compare(&scene.ast(), a0, a1, compiled.sdf, &points) {
+                Some(divergence) if !EDITED.contains(&name) => {
+                    Some(format!("{} {}", name, divergence))
+                }
+                None if EDITED.contains(&name) => Some(format!("{} isn't edited", name)),
+                _ => None,
+            }
+        })
+        .collect();
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
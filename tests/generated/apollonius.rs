@@ -0,0 +1,162 @@
+// Generated by `arrow::codegen` from the `apollonius` scene.
+
+use arrow::core::v3;
+use arrow::eval::Value;
+use arrow::functions::{modulo, rot0};
+use arrow::sdfs::Scene;
+use arrow::{dot, length};
+use glam::Vec3;
+
+pub fn apollonius(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.1f32;
+    let s = 2.5f32;
+    trace("s", s.into());
+    let h = s / 2f32;
+    trace("h", h.into());
+    let d = (s + h) / 2f32;
+    trace("d", d.into());
+    let q = 20f32;
+    trace("q", q.into());
+    let y = y - 10f32;
+    trace("y", y.into());
+    let [x, y] = rot0(x, y, a0);
+    trace("x", x.into());
+    trace("y", y.into());
+    let x = x / q;
+    trace("x", x.into());
+    let y = y / q;
+    trace("y", y.into());
+    let z = z / q;
+    trace("z", z.into());
+    let c = 1f32;
+    trace("c", c.into());
+    let t = 0f32;
+    trace("t", t.into());
+    let x = modulo(x - h, s) - h;
+    trace("x", x.into());
+    let y = modulo(y - h, s) - h;
+    trace("y", y.into());
+    let z = modulo(z - h, s) - h;
+    trace("z", z.into());
+    let t = d / dot!(x, y, z, x, y, z);
+    trace("t", t.into());
+    let x = x * t;
+    trace("x", x.into());
+    let y = y * t;
+    trace("y", y.into());
+    let z = z * t;
+    trace("z", z.into());
+    let c = c * t;
+    trace("c", c.into());
+    let x = modulo(x - h, s) - h;
+    trace("x", x.into());
+    let y = modulo(y - h, s) - h;
+    trace("y", y.into());
+    let z = modulo(z - h, s) - h;
+    trace("z", z.into());
+    let t = d / dot!(x, y, z, x, y, z);
+    trace("t", t.into());
+    let x = x * t;
+    trace("x", x.into());
+    let y = y * t;
+    trace("y", y.into());
+    let z = z * t;
+    trace("z", z.into());
+    let c = c * t;
+    trace("c", c.into());
+    let x = modulo(x - h, s) - h;
+    trace("x", x.into());
+    let y = modulo(y - h, s) - h;
+    trace("y", y.into());
+    let z = modulo(z - h, s) - h;
+    trace("z", z.into());
+    let t = d / dot!(x, y, z, x, y, z);
+    trace("t", t.into());
+    let x = x * t;
+    trace("x", x.into());
+    let y = y * t;
+    trace("y", y.into());
+    let z = z * t;
+    trace("z", z.into());
+    let c = c * t;
+    trace("c", c.into());
+    let x = modulo(x - h, s) - h;
+    trace("x", x.into());
+    let y = modulo(y - h, s) - h;
+    trace("y", y.into());
+    let z = modulo(z - h, s) - h;
+    trace("z", z.into());
+    let t = d / dot!(x, y, z, x, y, z);
+    trace("t", t.into());
+    let x = x * t;
+    trace("x", x.into());
+    let y = y * t;
+    trace("y", y.into());
+    let z = z * t;
+    trace("z", z.into());
+    let c = c * t;
+    trace("c", c.into());
+    let x = modulo(x - h, s) - h;
+    trace("x", x.into());
+    let y = modulo(y - h, s) - h;
+    trace("y", y.into());
+    let z = modulo(z - h, s) - h;
+    trace("z", z.into());
+    let t = d / dot!(x, y, z, x, y, z);
+    trace("t", t.into());
+    let x = x * t;
+    trace("x", x.into());
+    let y = y * t;
+    trace("y", y.into());
+    let z = z * t;
+    trace("z", z.into());
+    let c = c * t;
+    trace("c", c.into());
+    let x = modulo(x - h, s) - h;
+    trace("x", x.into());
+    let y = modulo(y - h, s) - h;
+    trace("y", y.into());
+    let z = modulo(z - h, s) - h;
+    trace("z", z.into());
+    let t = d / dot!(x, y, z, x, y, z);
+    trace("t", t.into());
+    let x = x * t;
+    trace("x", x.into());
+    let y = y * t;
+    trace("y", y.into());
+    let z = z * t;
+    trace("z", z.into());
+    let c = c * t;
+    trace("c", c.into());
+    let x = modulo(x - h, s) - h;
+    trace("x", x.into());
+    let y = modulo(y - h, s) - h;
+    trace("y", y.into());
+    let z = modulo(z - h, s) - h;
+    trace("z", z.into());
+    let t = d / dot!(x, y, z, x, y, z);
+    trace("t", t.into());
+    let x = x * t;
+    trace("x", x.into());
+    let y = y * t;
+    trace("y", y.into());
+    let z = z * t;
+    trace("z", z.into());
+    let c = c * t;
+    trace("c", c.into());
+    let d = length!(x, y, z) / c * 2f32 - 0.025f32;
+    trace("d", d.into());
+    d
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "apollonius",
+        sdf: apollonius,
+    }
+}
@@ -0,0 +1,65 @@
+// Generated by `arrow::codegen` from the `arctic` scene.
+
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::{abs, clamp, exp, modulo, rot0, round_max, round_min, union};
+use arrow::noise::fbm_value;
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use arrow::{box3, length, value_noise};
+use glam::Vec3;
+
+pub fn arctic(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.1f32;
+    let [x, z] = rot0(x, z, a0);
+    trace("x", x.into());
+    trace("z", z.into());
+    let x = x + 11f32;
+    trace("x", x.into());
+    let z = z + 15f32;
+    trace("z", z.into());
+    let y = y + 10f32;
+    trace("y", y.into());
+    let h = exp(-1.5f32 * abs(value_noise!(x, 0f32, z, 0.1f32, 1f32)));
+    trace("h", h.into());
+    let g = y - 10f32 * h - value_noise!(x, 0f32, z, 10f32, 1f32) * 0.05f32;
+    trace("g", g.into());
+    let b = y - 12f32;
+    trace("b", b.into());
+    let a = round_min(vec![
+        length!(x - clamp(x, -2f32, 2f32), b * 1.3f32, z) - 3f32,
+        union(vec![
+            length!(x + 5f32, b - 1f32, z) - 1.7f32,
+            length!(x + 5f32, b - 2f32, abs(z) - 1.5f32) - 0.8f32,
+            box3!(x - 5f32, b - 1f32, z, 0.2f32, 0.1f32, 0.2f32) - 0.5f32,
+            box3!(x + 5f32, b - 1f32, z, 1.9f32, 0.1f32, 0.1f32) - 0.5f32,
+            length!(abs(x) - 3.5f32, b - clamp(b, -4f32, 0f32), abs(z) - 1.5f32) - 0.8f32,
+        ]),
+        1.5f32,
+    ]) - value_noise!(x, 0f32, z, 12f32, 1f32) * 0.15f32;
+    trace("a", a.into());
+    let s = length!(
+        if x > 7f32 {
+            (modulo(x, 4f32) - 2f32) / 2f32
+        } else {
+            x
+        },
+        if x < 1f32 { y } else { b / 3f32 + 2f32 },
+        abs(z) - 1.5f32
+    ) - 1.8f32
+        - value_noise!(x, y, z, 0.5f32, 1f32) * 2f32;
+    trace("s", s.into());
+    round_max(vec![union(vec![a, g]), -s, 1f32])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "arctic",
+        sdf: arctic,
+    }
+}
@@ -0,0 +1,131 @@
+// Generated by `arrow::codegen` from the `asurf` scene.
+
+use arrow::core::v3;
+use arrow::eval::Value;
+use arrow::functions::{clamp, poly_smooth_abs, rot0, round_min, union};
+use arrow::length;
+use arrow::sdfs::Scene;
+use glam::{Vec2, Vec3};
+
+pub fn asurf(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.25f32;
+    let s = 1f32;
+    trace("s", s.into());
+    let z = z + 1f32;
+    trace("z", z.into());
+    let x = x + 0.7f32;
+    trace("x", x.into());
+    let l = length!(x - 3f32, y - 1f32, z - 1f32) - 1.45f32;
+    trace("l", l.into());
+    let x = x * 0.5f32;
+    trace("x", x.into());
+    let y = y * 0.5f32;
+    trace("y", y.into());
+    let z = z * 0.5f32;
+    trace("z", z.into());
+    let yy = y;
+    trace("yy", yy.into());
+    let zz = z;
+    trace("zz", zz.into());
+    let x = poly_smooth_abs(x + 1f32, 0.1f32) - poly_smooth_abs(x - 1f32, 0.1f32) - x;
+    trace("x", x.into());
+    let z = poly_smooth_abs(z + 1f32, 0.1f32) - poly_smooth_abs(z - 1f32, 0.1f32) - z;
+    trace("z", z.into());
+    let y = y - 1f32;
+    trace("y", y.into());
+    let x = x - 0.3f32;
+    trace("x", x.into());
+    let [x, y] = rot0(x, y, a0);
+    trace("x", x.into());
+    trace("y", y.into());
+    let sc = 2f32 / clamp(x * x + y * y + z * z, 0.4f32, 1f32);
+    trace("sc", sc.into());
+    let x = x * sc;
+    trace("x", x.into());
+    let y = y * sc;
+    trace("y", y.into());
+    let z = z * sc;
+    trace("z", z.into());
+    let s = s * sc;
+    trace("s", s.into());
+    let x = poly_smooth_abs(x + 1f32, 0.1f32) - poly_smooth_abs(x - 1f32, 0.1f32) - x;
+    trace("x", x.into());
+    let z = poly_smooth_abs(z + 1f32, 0.1f32) - poly_smooth_abs(z - 1f32, 0.1f32) - z;
+    trace("z", z.into());
+    let y = y - 1f32;
+    trace("y", y.into());
+    let x = x - 0.3f32;
+    trace("x", x.into());
+    let [x, y] = rot0(x, y, a0);
+    trace("x", x.into());
+    trace("y", y.into());
+    let sc = 2f32 / clamp(x * x + y * y + z * z, 0.4f32, 1f32);
+    trace("sc", sc.into());
+    let x = x * sc;
+    trace("x", x.into());
+    let y = y * sc;
+    trace("y", y.into());
+    let z = z * sc;
+    trace("z", z.into());
+    let s = s * sc;
+    trace("s", s.into());
+    let x = poly_smooth_abs(x + 1f32, 0.1f32) - poly_smooth_abs(x - 1f32, 0.1f32) - x;
+    trace("x", x.into());
+    let z = poly_smooth_abs(z + 1f32, 0.1f32) - poly_smooth_abs(z - 1f32, 0.1f32) - z;
+    trace("z", z.into());
+    let y = y - 1f32;
+    trace("y", y.into());
+    let x = x - 0.3f32;
+    trace("x", x.into());
+    let [x, y] = rot0(x, y, a0);
+    trace("x", x.into());
+    trace("y", y.into());
+    let sc = 2f32 / clamp(x * x + y * y + z * z, 0.4f32, 1f32);
+    trace("sc", sc.into());
+    let x = x * sc;
+    trace("x", x.into());
+    let y = y * sc;
+    trace("y", y.into());
+    let z = z * sc;
+    trace("z", z.into());
+    let s = s * sc;
+    trace("s", s.into());
+    let x = poly_smooth_abs(x + 1f32, 0.1f32) - poly_smooth_abs(x - 1f32, 0.1f32) - x;
+    trace("x", x.into());
+    let z = poly_smooth_abs(z + 1f32, 0.1f32) - poly_smooth_abs(z - 1f32, 0.1f32) - z;
+    trace("z", z.into());
+    let y = y - 1f32;
+    trace("y", y.into());
+    let x = x - 0.3f32;
+    trace("x", x.into());
+    let [x, y] = rot0(x, y, a0);
+    trace("x", x.into());
+    trace("y", y.into());
+    let sc = 2f32 / clamp(x * x + y * y + z * z, 0.4f32, 1f32);
+    trace("sc", sc.into());
+    let x = x * sc;
+    trace("x", x.into());
+    let y = y * sc;
+    trace("y", y.into());
+    let z = z * sc;
+    trace("z", z.into());
+    let s = s * sc;
+    trace("s", s.into());
+    round_min(vec![
+        l,
+        union(vec![yy, (length!(x, y) - 1.5f32) / s * 2f32]),
+        0.2f32,
+    ])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "asurf",
+        sdf: asurf,
+    }
+}
@@ -0,0 +1,30 @@
+// Generated by `arrow::codegen` from the `balls8a` scene.
+
+use arrow::core::v3;
+use arrow::eval::Value;
+use arrow::functions::abs;
+use arrow::length;
+use arrow::sdfs::Scene;
+use glam::Vec3;
+
+pub fn balls8a(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let x = abs(x) - 6f32;
+    trace("x", x.into());
+    let y = abs(y) - 6f32;
+    trace("y", y.into());
+    let z = abs(z) - 6f32;
+    trace("z", z.into());
+    length!(x, y, z) - 5f32
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "balls8a",
+        sdf: balls8a,
+    }
+}
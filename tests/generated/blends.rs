@@ -0,0 +1,222 @@
+// Generated by `arrow::codegen` from the `blends` scene.
+
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::{
+    chamfer_intersect, chamfer_union, circular_intersect, circular_union, columns_intersect,
+    columns_union, cubic_intersect, cubic_union, exp_intersect, exp_union, groove, pipe,
+    poly_intersect, poly_union, root_intersect, root_union, stairs_intersect, stairs_union, tongue,
+    union,
+};
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use arrow::{box3, length};
+use glam::Vec3;
+
+pub fn blends(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let u = x + 12f32;
+    trace("u", u.into());
+    let v = y - 9f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d0 = poly_union(vec![a, b, length!(u, v + 1.5f32, z) - 0.8f32, 0.8f32]);
+    trace("d0", d0.into());
+    let u = x + 6f32;
+    trace("u", u.into());
+    let v = y - 9f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d1 = poly_intersect(vec![a, b, 0.8f32]);
+    trace("d1", d1.into());
+    let u = x - 0f32;
+    trace("u", u.into());
+    let v = y - 9f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d2 = cubic_union(vec![a, b, length!(u, v + 1.5f32, z) - 0.8f32, 0.8f32]);
+    trace("d2", d2.into());
+    let u = x - 6f32;
+    trace("u", u.into());
+    let v = y - 9f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d3 = cubic_intersect(vec![a, b, 0.8f32]);
+    trace("d3", d3.into());
+    let u = x - 12f32;
+    trace("u", u.into());
+    let v = y - 9f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d4 = exp_union(vec![a, b, length!(u, v + 1.5f32, z) - 0.8f32, 0.8f32]);
+    trace("d4", d4.into());
+    let u = x + 12f32;
+    trace("u", u.into());
+    let v = y - 3f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d5 = exp_intersect(vec![a, b, 0.8f32]);
+    trace("d5", d5.into());
+    let u = x + 6f32;
+    trace("u", u.into());
+    let v = y - 3f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d6 = root_union(vec![a, b, length!(u, v + 1.5f32, z) - 0.8f32, 0.8f32]);
+    trace("d6", d6.into());
+    let u = x - 0f32;
+    trace("u", u.into());
+    let v = y - 3f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d7 = root_intersect(vec![a, b, 0.8f32]);
+    trace("d7", d7.into());
+    let u = x - 6f32;
+    trace("u", u.into());
+    let v = y - 3f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d8 = circular_union(vec![a, b, length!(u, v + 1.5f32, z) - 0.8f32, 0.8f32]);
+    trace("d8", d8.into());
+    let u = x - 12f32;
+    trace("u", u.into());
+    let v = y - 3f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d9 = circular_intersect(vec![a, b, 0.8f32]);
+    trace("d9", d9.into());
+    let u = x + 12f32;
+    trace("u", u.into());
+    let v = y + 3f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d10 = chamfer_union(vec![a, b, length!(u, v + 1.5f32, z) - 0.8f32, 0.8f32]);
+    trace("d10", d10.into());
+    let u = x + 6f32;
+    trace("u", u.into());
+    let v = y + 3f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d11 = chamfer_intersect(vec![a, b, 0.8f32]);
+    trace("d11", d11.into());
+    let u = x - 0f32;
+    trace("u", u.into());
+    let v = y + 3f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d12 = stairs_union(vec![a, b, 0.8f32, 3f32]);
+    trace("d12", d12.into());
+    let u = x - 6f32;
+    trace("u", u.into());
+    let v = y + 3f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d13 = stairs_intersect(vec![a, b, 0.8f32, 3f32]);
+    trace("d13", d13.into());
+    let u = x - 12f32;
+    trace("u", u.into());
+    let v = y + 3f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d14 = columns_union(vec![a, b, 0.8f32, 3f32]);
+    trace("d14", d14.into());
+    let u = x + 12f32;
+    trace("u", u.into());
+    let v = y + 9f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d15 = columns_intersect(vec![a, b, 0.8f32, 3f32]);
+    trace("d15", d15.into());
+    let u = x + 6f32;
+    trace("u", u.into());
+    let v = y + 9f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d16 = groove(vec![box3!(u, v, z, 2f32, 1.5f32, 1f32), u, 0.3f32, 0.4f32]);
+    trace("d16", d16.into());
+    let u = x - 0f32;
+    trace("u", u.into());
+    let v = y + 9f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d17 = tongue(vec![box3!(u, v, z, 2f32, 1.5f32, 1f32), u, 0.3f32, 0.4f32]);
+    trace("d17", d17.into());
+    let u = x - 6f32;
+    trace("u", u.into());
+    let v = y + 9f32;
+    trace("v", v.into());
+    let a = length!(u, v, z) - 1.5f32;
+    trace("a", a.into());
+    let b = box3!(u - 1.5f32, v, z, 1f32);
+    trace("b", b.into());
+    let d18 = pipe(vec![a, b, 0.2f32]);
+    trace("d18", d18.into());
+    union(vec![
+        d0, d1, d2, d3, d4, d5, d6, d7, d8, d9, d10, d11, d12, d13, d14, d15, d16, d17, d18,
+    ])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "blends",
+        sdf: blends,
+    }
+}
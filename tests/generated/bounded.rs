@@ -0,0 +1,50 @@
+// Generated by `arrow::codegen` from the `bounded` scene.
+
+use arrow::bound::MARGIN;
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::{kifs, mandelbulb, menger, scale, union};
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use arrow::{bound, box3, length};
+use glam::Vec3;
+
+pub fn bounded(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let [a, b, c] = scale(x + 10f32, y, z, 3.5f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let d1 = bound!(box3!(a, b, c, 1.25f32), mandelbulb(a, b, c, 8f32, 8f32)) * 3.5f32;
+    trace("d1", d1.into());
+    let [a, b, c] = scale(x, y, z, 4f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let d2 = bound!(box3!(a, b, c, 1.05f32), menger(a, b, c, 4f32)) * 4f32;
+    trace("d2", d2.into());
+    let [a, b, c] = scale(x - 10f32, y, z, 3f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let d3 = bound!(
+        length!(a, b, c) - 1.2f32,
+        kifs(vec![
+            a, b, c, 2f32, 1f32, 1f32, 1f32, 6f32, 1f32, 1f32, 0f32, 1f32, 0f32, 1f32, 0f32, 1f32,
+            1f32
+        ])
+    ) * 3f32;
+    trace("d3", d3.into());
+    union(vec![d1, d2, d3])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "bounded",
+        sdf: bounded,
+    }
+}
@@ -0,0 +1,98 @@
+// Generated by `arrow::codegen` from the `box_of_balls` scene.
+
+use arrow::core::v3;
+use arrow::eval::Value;
+use arrow::functions::{abs, rot0, rot1};
+use arrow::length;
+use arrow::sdfs::Scene;
+use glam::Vec3;
+
+pub fn box_of_balls(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.1f32;
+    let a1 = 0.2f32;
+    let s = 1f32;
+    trace("s", s.into());
+    let [x, y] = rot0(x, y, a0);
+    trace("x", x.into());
+    trace("y", y.into());
+    let [x, z] = rot1(x, z, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let x = abs(x * 2f32) - 8f32;
+    trace("x", x.into());
+    let y = abs(y * 2f32) - 8f32;
+    trace("y", y.into());
+    let z = abs(z * 2f32) - 8f32;
+    trace("z", z.into());
+    let s = s * 0.5f32;
+    trace("s", s.into());
+    let [x, y] = rot0(x, y, a0);
+    trace("x", x.into());
+    trace("y", y.into());
+    let [x, z] = rot1(x, z, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let x = abs(x * 2f32) - 8f32;
+    trace("x", x.into());
+    let y = abs(y * 2f32) - 8f32;
+    trace("y", y.into());
+    let z = abs(z * 2f32) - 8f32;
+    trace("z", z.into());
+    let s = s * 0.5f32;
+    trace("s", s.into());
+    let [x, y] = rot0(x, y, a0);
+    trace("x", x.into());
+    trace("y", y.into());
+    let [x, z] = rot1(x, z, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let x = abs(x * 2f32) - 8f32;
+    trace("x", x.into());
+    let y = abs(y * 2f32) - 8f32;
+    trace("y", y.into());
+    let z = abs(z * 2f32) - 8f32;
+    trace("z", z.into());
+    let s = s * 0.5f32;
+    trace("s", s.into());
+    let [x, y] = rot0(x, y, a0);
+    trace("x", x.into());
+    trace("y", y.into());
+    let [x, z] = rot1(x, z, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let x = abs(x * 2f32) - 8f32;
+    trace("x", x.into());
+    let y = abs(y * 2f32) - 8f32;
+    trace("y", y.into());
+    let z = abs(z * 2f32) - 8f32;
+    trace("z", z.into());
+    let s = s * 0.5f32;
+    trace("s", s.into());
+    let [x, y] = rot0(x, y, a0);
+    trace("x", x.into());
+    trace("y", y.into());
+    let [x, z] = rot1(x, z, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let x = abs(x * 2f32) - 8f32;
+    trace("x", x.into());
+    let y = abs(y * 2f32) - 8f32;
+    trace("y", y.into());
+    let z = abs(z * 2f32) - 8f32;
+    trace("z", z.into());
+    let s = s * 0.5f32;
+    trace("s", s.into());
+    (length!(x, y, z) - 8f32) * s
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "box_of_balls",
+        sdf: box_of_balls,
+    }
+}
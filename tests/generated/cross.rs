@@ -0,0 +1,30 @@
+// Generated by `arrow::codegen` from the `cross` scene.
+
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::{modulo, triangle, union};
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use arrow::{box3, length};
+use glam::{Vec2, Vec3};
+
+pub fn cross(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, _trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    union(vec![
+        box3!(modulo(x, 4f32) - 2f32, y, z, 6f32),
+        box3!(x, y, modulo(x, 4f32) - 2f32, 6f32),
+        length!(triangle(x), y) - 1f32,
+        length!(x + 20f32, y - 20f32, z - 20f32) - 8f32,
+    ])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "cross",
+        sdf: cross,
+    }
+}
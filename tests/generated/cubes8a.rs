@@ -0,0 +1,30 @@
+// Generated by `arrow::codegen` from the `cubes8a` scene.
+
+use arrow::box3;
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::round_min;
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use glam::Vec3;
+
+pub fn cubes8a(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, _trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    round_min(vec![
+        box3!(x, y, z, 5f32),
+        box3!(x - 8f32, y + 5f32, z, 5f32),
+        box3!(x + 8f32, y - 5f32, z, 5f32),
+        5f32,
+    ])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "cubes8a",
+        sdf: cubes8a,
+    }
+}
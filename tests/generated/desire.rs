@@ -0,0 +1,49 @@
+// Generated by `arrow::codegen` from the `desire` scene.
+
+use arrow::core::v3;
+use arrow::eval::Value;
+use arrow::functions::{abs, clamp, corner, rot0, rot1, round_max, smoothstep, triangle, union};
+use arrow::length;
+use arrow::sdfs::Scene;
+use glam::{Vec2, Vec3};
+
+pub fn desire(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.1f32;
+    let a1 = 0.2f32;
+    let [x, y] = rot0(x, y - 1f32, a0);
+    trace("x", x.into());
+    trace("y", y.into());
+    let [x, z] = rot1(x, z, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let yb = abs(y) - 22.5f32;
+    trace("yb", yb.into());
+    union(vec![
+        round_max(vec![
+            32f32 - corner(-y - 13f32, z - 15f32),
+            triangle(x * 0.25f32) * 4f32 - 2f32 + 4f32 * smoothstep(0f32, 16f32, x),
+            4f32,
+        ]),
+        round_max(vec![
+            abs(abs(length!(length!(x, z) - 16f32, yb - clamp(yb, -8.5f32, 8.5f32)) - 8f32) - 4f32)
+                - 2f32,
+            abs(
+                abs(length!(abs(x) - 15f32, abs(abs(y) - 15f32) - 15f32, abs(z) - 15f32) - 9f32)
+                    - 4f32,
+            ) - 2f32,
+            1f32,
+        ]),
+    ])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "desire",
+        sdf: desire,
+    }
+}
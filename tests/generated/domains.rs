@@ -0,0 +1,66 @@
+// Generated by `arrow::codegen` from the `domains` scene.
+
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::{
+    bend, elongate, hexagon, intersect, onion, polar, repeat, revolve, twist, union,
+};
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use arrow::{box3, length};
+use glam::Vec3;
+
+pub fn domains(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let [a, b, c] = twist(x + 8f32, y - 4f32, z, 0.4f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let d1 = box3!(a, b, c, 1.2f32, 3f32, 1.2f32) * 0.7f32;
+    trace("d1", d1.into());
+    let [a, b, c] = bend(x, y - 5f32, z, 0.15f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let d2 = box3!(a, b, c, 4f32, 0.5f32, 1f32) * 0.8f32;
+    trace("d2", d2.into());
+    let [a, b, c] = elongate(x - 8f32, y - 4f32, z, 1f32, 2f32, 0f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let d3 = length!(a, b, c) - 1f32;
+    trace("d3", d3.into());
+    let d4 = intersect(vec![
+        onion(length!(x + 8f32, y + 4f32, z) - 2f32, 0.2f32),
+        -z,
+    ]);
+    trace("d4", d4.into());
+    let [u, v] = revolve(x, y + 4f32, z, 2f32);
+    trace("u", u.into());
+    trace("v", v.into());
+    let d5 = hexagon(u, v, 0.8f32);
+    trace("d5", d5.into());
+    let u = repeat(x - 8f32, 1.2f32, 1f32);
+    trace("u", u.into());
+    let v = repeat(y + 4f32, 1.2f32, 1f32);
+    trace("v", v.into());
+    let d6 = length!(u, v, z) - 0.4f32;
+    trace("d6", d6.into());
+    let [u, w] = polar(x, z, 6f32);
+    trace("u", u.into());
+    trace("w", w.into());
+    let d7 = length!(u - 3f32, y + 9f32, w) - 0.6f32;
+    trace("d7", d7.into());
+    union(vec![d1, d2, d3, d4, d5, d6, d7])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "domains",
+        sdf: domains,
+    }
+}
@@ -0,0 +1,22 @@
+// Generated by `arrow::codegen` from the `donut` scene.
+
+use arrow::eval::Value;
+use arrow::functions::torus;
+use arrow::sdfs::Scene;
+use glam::Vec3;
+
+pub fn donut(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, _trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    torus(x, y - 2f32, z, 15f32, 2f32)
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "donut",
+        sdf: donut,
+    }
+}
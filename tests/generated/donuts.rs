@@ -0,0 +1,22 @@
+// Generated by `arrow::codegen` from the `donuts` scene.
+
+use arrow::eval::Value;
+use arrow::functions::{modulo, torus};
+use arrow::sdfs::Scene;
+use glam::Vec3;
+
+pub fn donuts(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, _trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    torus(x, y - 3f32, modulo(z, 8f32) - 4f32, 8f32, 1f32)
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "donuts",
+        sdf: donuts,
+    }
+}
@@ -0,0 +1,23 @@
+// Generated by `arrow::codegen` from the `elbow` scene.
+
+use arrow::eval::Value;
+use arrow::functions::corner;
+use arrow::length;
+use arrow::sdfs::Scene;
+use glam::{Vec2, Vec3};
+
+pub fn elbow(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, _trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    length!(corner(x, y - 10f32), z) - 5f32
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "elbow",
+        sdf: elbow,
+    }
+}
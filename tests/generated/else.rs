@@ -0,0 +1,105 @@
+// Generated by `arrow::codegen` from the `else` scene.
+
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::{corner, modulo, union};
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use arrow::{box3, length};
+use glam::{Vec2, Vec3};
+
+pub fn r#else(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let y = y - 1f32;
+    trace("y", y.into());
+    let r = box3!(x, y, z, 9f32) - 2f32;
+    trace("r", r.into());
+    let s = 1f32;
+    trace("s", s.into());
+    let ti = union(vec![
+        length!(x, y) - 0.6f32,
+        length!(y, z) - 0.6f32,
+        length!(z, x) - 0.6f32,
+    ]);
+    trace("ti", ti.into());
+    let x = (modulo(x + 9f32, 18f32) - 9f32) * 3f32;
+    trace("x", x.into());
+    let y = (modulo(y + 9f32, 18f32) - 9f32) * 3f32;
+    trace("y", y.into());
+    let z = (modulo(z + 9f32, 18f32) - 9f32) * 3f32;
+    trace("z", z.into());
+    let s = s / 3f32;
+    trace("s", s.into());
+    let r = corner(
+        r + s,
+        -union(vec![
+            length!(x, y) - 12f32,
+            length!(y, z) - 12f32,
+            length!(z, x) - 12f32,
+        ]) * s,
+    ) - s;
+    trace("r", r.into());
+    let x = (modulo(x + 9f32, 18f32) - 9f32) * 3f32;
+    trace("x", x.into());
+    let y = (modulo(y + 9f32, 18f32) - 9f32) * 3f32;
+    trace("y", y.into());
+    let z = (modulo(z + 9f32, 18f32) - 9f32) * 3f32;
+    trace("z", z.into());
+    let s = s / 3f32;
+    trace("s", s.into());
+    let r = corner(
+        r + s,
+        -union(vec![
+            length!(x, y) - 12f32,
+            length!(y, z) - 12f32,
+            length!(z, x) - 12f32,
+        ]) * s,
+    ) - s;
+    trace("r", r.into());
+    let x = (modulo(x + 9f32, 18f32) - 9f32) * 3f32;
+    trace("x", x.into());
+    let y = (modulo(y + 9f32, 18f32) - 9f32) * 3f32;
+    trace("y", y.into());
+    let z = (modulo(z + 9f32, 18f32) - 9f32) * 3f32;
+    trace("z", z.into());
+    let s = s / 3f32;
+    trace("s", s.into());
+    let r = corner(
+        r + s,
+        -union(vec![
+            length!(x, y) - 12f32,
+            length!(y, z) - 12f32,
+            length!(z, x) - 12f32,
+        ]) * s,
+    ) - s;
+    trace("r", r.into());
+    let x = (modulo(x + 9f32, 18f32) - 9f32) * 3f32;
+    trace("x", x.into());
+    let y = (modulo(y + 9f32, 18f32) - 9f32) * 3f32;
+    trace("y", y.into());
+    let z = (modulo(z + 9f32, 18f32) - 9f32) * 3f32;
+    trace("z", z.into());
+    let s = s / 3f32;
+    trace("s", s.into());
+    let r = corner(
+        r + s,
+        -union(vec![
+            length!(x, y) - 12f32,
+            length!(y, z) - 12f32,
+            length!(z, x) - 12f32,
+        ]) * s,
+    ) - s;
+    trace("r", r.into());
+    union(vec![r, ti])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "else",
+        sdf: r#else,
+    }
+}
@@ -0,0 +1,23 @@
+// Generated by `arrow::codegen` from the `fence` scene.
+
+use arrow::eval::Value;
+use arrow::functions::triangle;
+use arrow::length;
+use arrow::sdfs::Scene;
+use glam::{Vec2, Vec3};
+
+pub fn fence(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, _trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z: _ } = p;
+    length!(x, triangle(y)) - 0.5f32
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "fence",
+        sdf: fence,
+    }
+}
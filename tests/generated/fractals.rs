@@ -0,0 +1,64 @@
+// Generated by `arrow::codegen` from the `fractals` scene.
+
+use arrow::eval::Value;
+use arrow::functions::{
+    clamp, kifs, mandelbox, mandelbulb, mandelbulb_trap, menger, scale, sierpinski, union,
+};
+use arrow::sdfs::Scene;
+use glam::Vec3;
+
+pub fn fractals(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let [a, b, c] = scale(x + 9f32, y - 5f32, z, 4f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let d1 = mandelbulb(a, b, c, 8f32, 6f32) * 4f32;
+    trace("d1", d1.into());
+    let t = mandelbulb_trap(a, b, c, 8f32, 6f32);
+    trace("t", t.into());
+    let [a, b, c] = scale(x, y - 5f32, z, 3f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let d2 = menger(a, b, c, 3f32) * 3f32;
+    trace("d2", d2.into());
+    let [a, b, c] = scale(x - 9f32, y - 5f32, z, 3f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let d3 = sierpinski(a, b, c, 5f32) * 3f32;
+    trace("d3", d3.into());
+    let [a, b, c] = scale(x + 6f32, y + 5f32, z, 1.5f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let d4 = mandelbox(a, b, c, -1.5f32, 10f32) * 1.5f32;
+    trace("d4", d4.into());
+    let [a, b, c] = scale(x - 6f32, y + 5f32, z, 3f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let d5 = kifs(vec![
+        a, b, c, 2f32, 1f32, 1f32, 1f32, 6f32, 1f32, 1f32, 0f32, 1f32, 0f32, 1f32, 0f32, 1f32, 1f32,
+    ]) * 3f32;
+    trace("d5", d5.into());
+    let col = if d1 < 0.1f32 {
+        0.4f32 + clamp(t, 0f32, 1f32) * 0.6f32
+    } else {
+        1f32
+    };
+    trace("col", col.into());
+    union(vec![d1, d2, d3, d4, d5])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "fractals",
+        sdf: fractals,
+    }
+}
@@ -0,0 +1,101 @@
+// Generated by `arrow::codegen` from the `ghost` scene.
+
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::{
+    abs, atan2, clamp, cos, floor, intersect, modulo, rot, rot0, round_min, sin, union,
+};
+use arrow::noise::fbm_value;
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use arrow::{box3, length, value_noise};
+use glam::{Vec2, Vec3};
+
+pub fn ghost(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.1f32;
+    let a1 = 0.3f32;
+    let y = y - 9.8f32;
+    trace("y", y.into());
+    let [x, z] = rot0(x, z, a0);
+    trace("x", x.into());
+    trace("z", z.into());
+    let a = x;
+    trace("a", a.into());
+    let b = y;
+    trace("b", b.into());
+    let c = abs(z) - 0.3f32;
+    trace("c", c.into());
+    let [a, b] = rot(a, b, cos(0.17f32), sin(0.17f32));
+    trace("a", a.into());
+    trace("b", b.into());
+    let an = floor(0.5f32 + atan2(b, a) / a1) * a1;
+    trace("an", an.into());
+    let [a, b] = rot(a, b, cos(an), sin(an));
+    trace("a", a.into());
+    trace("b", b.into());
+    let d = intersect(vec![
+        union(vec![
+            box3!(a - 7f32, b, c, 0.01f32, 2f32, 0.01f32) - 0.05f32,
+            box3!(b, a, c, 0.02f32, 7f32, 0.02f32) - 0.01f32,
+            length!(a - 7f32, b) - 0.4f32,
+            length!(modulo(clamp(a, 0f32, 5f32), 1f32) - 0.5f32, b) - 0.05f32,
+        ]),
+        abs(z) - 0.3f32,
+    ]);
+    trace("d", d.into());
+    let a = abs(x);
+    trace("a", a.into());
+    let b = y;
+    trace("b", b.into());
+    let an = 0.3f32;
+    trace("an", an.into());
+    let [a, b] = rot(a, b, cos(an), sin(an));
+    trace("a", a.into());
+    trace("b", b.into());
+    let d = union(vec![
+        intersect(vec![
+            union(vec![
+                d,
+                length!(x, y) - 0.2f32,
+                length!(a, c - 0.3f32) - 0.1f32,
+            ]),
+            abs(z) - 0.7f32,
+        ]),
+        abs(y + 10f32) - 2f32 - sin(x * 0.1f32),
+    ]);
+    trace("d", d.into());
+    let t = 8f32 * floor(x / 8f32) + 4f32;
+    trace("t", t.into());
+    let h = 20f32 - sin(t) * 10f32;
+    trace("h", h.into());
+    union(vec![
+        d,
+        round_min(vec![
+            intersect(vec![
+                box3!(x - t, y + h * 0.5f32, z + 70f32, 3f32, h, 3f32),
+                -box3!(
+                    abs(x - t) - 1.5f32,
+                    modulo(y, 3f32) - 1.5f32,
+                    z + 68f32,
+                    0.8f32,
+                    h * 0.04f32,
+                    2f32
+                ),
+            ]),
+            length!(y + 9f32, z + 65f32) - 0.5f32 + value_noise!(x, y, z, 5f32, 1f32) * 10f32,
+            2f32,
+        ]),
+    ])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "ghost",
+        sdf: ghost,
+    }
+}
@@ -0,0 +1,30 @@
+// Generated by `arrow::codegen` from the `gnarl` scene.
+
+use arrow::eval::Value;
+use arrow::functions::{abs, modulo};
+use arrow::noise::fbm_value;
+use arrow::sdfs::Scene;
+use arrow::value_noise;
+use glam::Vec3;
+
+pub fn gnarl(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let p = abs(y - 18f32) - 13f32;
+    trace("p", p.into());
+    let n = value_noise!(x, y, z, 0.2f32, 0f32, 2f32) * 2f32;
+    trace("n", n.into());
+    let q = modulo(p, 12f32 + n * z) - 1.8f32;
+    trace("q", q.into());
+    q
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "gnarl",
+        sdf: gnarl,
+    }
+}
@@ -0,0 +1,61 @@
+// Generated by `arrow::codegen` from the `hyperplane` scene.
+
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::{abs, corner, fract, rot0, rot1, smoothstep, triangle, union};
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use arrow::{box3, length};
+use glam::Vec3;
+
+pub fn hyperplane(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.1f32;
+    let a1 = 0.2f32;
+    let a = (2f32 * x - 3f32 * z + 6f32 * y) / 7f32;
+    trace("a", a.into());
+    let b = (7f32 * x - 2f32 * z + 26f32 * y) / 27f32;
+    trace("b", b.into());
+    let c = (6f32 * z - 3f32 * x + 22f32 * y) / 23f32;
+    trace("c", c.into());
+    let d = 0f32;
+    trace("d", d.into());
+    let zz = 0f32;
+    trace("zz", zz.into());
+    let [x, z] = rot1(x, z + 8f32, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let [x, y] = rot0(x, y, a0);
+    trace("x", x.into());
+    trace("y", y.into());
+    let y = y - 3f32;
+    trace("y", y.into());
+    let zz = fract(z / 26f32 - 0.55f32) * 26f32 - 13f32;
+    trace("zz", zz.into());
+    let d = smoothstep(9f32, -12f32, y + 3f32 - z * 0.3f32);
+    trace("d", d.into());
+    union(vec![
+        corner(
+            corner(
+                corner(
+                    box3!(x, y - 5f32, zz, 7f32, 14f32, 7f32) - 1f32,
+                    d - abs(triangle(a)),
+                ),
+                d - abs(triangle(b)),
+            ),
+            d - abs(triangle(c)),
+        ),
+        length!(x + 99f32, y + 445f32, z + 32f32) - 434f32,
+    ])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "hyperplane",
+        sdf: hyperplane,
+    }
+}
@@ -0,0 +1,23 @@
+// Generated by `arrow::codegen` from the `jeff` scene.
+
+use arrow::eval::Value;
+use arrow::noise::fbm_value;
+use arrow::sdfs::Scene;
+use arrow::value_noise;
+use glam::Vec3;
+
+pub fn jeff(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, _trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    2f32 * value_noise!(0.25f32 * x, y, z, 2.5f32, 1f32, 1f32)
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "jeff",
+        sdf: jeff,
+    }
+}
@@ -0,0 +1,97 @@
+// Generated by tests/codegen.rs.
+use arrow::differential::Traced;
+use arrow::sdfs::Scene;
+
+pub mod apollonius;
+pub mod arctic;
+pub mod asurf;
+pub mod balls8a;
+pub mod blends;
+pub mod bounded;
+pub mod box_of_balls;
+pub mod cross;
+pub mod cubes8a;
+pub mod desire;
+pub mod domains;
+pub mod donut;
+pub mod donuts;
+pub mod elbow;
+pub mod r#else;
+pub mod fence;
+pub mod fractals;
+pub mod ghost;
+pub mod gnarl;
+pub mod hyperplane;
+pub mod jeff;
+pub mod mycelia;
+pub mod noise;
+pub mod objects;
+pub mod pawns;
+pub mod plato;
+pub mod primitives;
+pub mod quanta;
+pub mod rods;
+pub mod rot_cube;
+pub mod rounded_box;
+pub mod shai_hulud;
+pub mod singularity;
+pub mod source;
+pub mod sphere;
+pub mod spheres;
+pub mod sponge;
+pub mod sprenkle;
+pub mod system;
+pub mod target;
+pub mod temple;
+pub mod thepath;
+pub mod toy;
+pub mod transforms;
+
+pub fn scenes() -> Vec<(&'static str, Scene, Traced)> {
+    vec![
+        ("apollonius", apollonius::scene(), apollonius::traced),
+        ("arctic", arctic::scene(), arctic::traced),
+        ("asurf", asurf::scene(), asurf::traced),
+        ("balls8a", balls8a::scene(), balls8a::traced),
+        ("blends", blends::scene(), blends::traced),
+        ("bounded", bounded::scene(), bounded::traced),
+        ("box_of_balls", box_of_balls::scene(), box_of_balls::traced),
+        ("cross", cross::scene(), cross::traced),
+        ("cubes8a", cubes8a::scene(), cubes8a::traced),
+        ("desire", desire::scene(), desire::traced),
+        ("domains", domains::scene(), domains::traced),
+        ("donut", donut::scene(), donut::traced),
+        ("donuts", donuts::scene(), donuts::traced),
+        ("elbow", elbow::scene(), elbow::traced),
+        ("else", r#else::scene(), r#else::traced),
+        ("fence", fence::scene(), fence::traced),
+        ("fractals", fractals::scene(), fractals::traced),
+        ("ghost", ghost::scene(), ghost::traced),
+        ("gnarl", gnarl::scene(), gnarl::traced),
+        ("hyperplane", hyperplane::scene(), hyperplane::traced),
+        ("jeff", jeff::scene(), jeff::traced),
+        ("mycelia", mycelia::scene(), mycelia::traced),
+        ("noise", noise::scene(), noise::traced),
+        ("objects", objects::scene(), objects::traced),
+        ("pawns", pawns::scene(), pawns::traced),
+        ("plato", plato::scene(), plato::traced),
+        ("primitives", primitives::scene(), primitives::traced),
+        ("quanta", quanta::scene(), quanta::traced),
+        ("rods", rods::scene(), rods::traced),
+        ("rot_cube", rot_cube::scene(), rot_cube::traced),
+        ("rounded_box", rounded_box::scene(), rounded_box::traced),
+        ("shai_hulud", shai_hulud::scene(), shai_hulud::traced),
+        ("singularity", singularity::scene(), singularity::traced),
+        ("source", source::scene(), source::traced),
+        ("sphere", sphere::scene(), sphere::traced),
+        ("spheres", spheres::scene(), spheres::traced),
+        ("sponge", sponge::scene(), sponge::traced),
+        ("sprenkle", sprenkle::scene(), sprenkle::traced),
+        ("system", system::scene(), system::traced),
+        ("target", target::scene(), target::traced),
+        ("temple", temple::scene(), temple::traced),
+        ("thepath", thepath::scene(), thepath::traced),
+        ("toy", toy::scene(), toy::traced),
+        ("transforms", transforms::scene(), transforms::traced),
+    ]
+}
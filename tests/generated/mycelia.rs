@@ -0,0 +1,92 @@
+// Generated by `arrow::codegen` from the `mycelia` scene.
+
+use arrow::core::v3;
+use arrow::eval::Value;
+use arrow::functions::{abs, smooth_clamp};
+use arrow::length;
+use arrow::sdfs::Scene;
+use glam::{Vec2, Vec3};
+
+pub fn mycelia(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let x = x / 20f32;
+    trace("x", x.into());
+    let y = y / 20f32;
+    trace("y", y.into());
+    let z = z / 20f32;
+    trace("z", z.into());
+    let xm = 0.9f32;
+    trace("xm", xm.into());
+    let ym = 0.3f32;
+    trace("ym", ym.into());
+    let zm = 0.7f32;
+    trace("zm", zm.into());
+    let x = abs(x) - xm;
+    trace("x", x.into());
+    let y = abs(y) - ym;
+    trace("y", y.into());
+    let z = abs(z) - zm;
+    trace("z", z.into());
+    let s = 1f32 / smooth_clamp(length!(x, y, z).powf(3f32), 0.1f32, 0.1f32, 1f32);
+    trace("s", s.into());
+    let x = x * s - ym;
+    trace("x", x.into());
+    let y = y * s - zm;
+    trace("y", y.into());
+    let z = z * s - xm;
+    trace("z", z.into());
+    let x = abs(x) - xm;
+    trace("x", x.into());
+    let y = abs(y) - ym;
+    trace("y", y.into());
+    let z = abs(z) - zm;
+    trace("z", z.into());
+    let s = 1f32 / smooth_clamp(length!(x, y, z).powf(3f32), 0.1f32, 0.1f32, 1f32);
+    trace("s", s.into());
+    let x = x * s - ym;
+    trace("x", x.into());
+    let y = y * s - zm;
+    trace("y", y.into());
+    let z = z * s - xm;
+    trace("z", z.into());
+    let x = abs(x) - xm;
+    trace("x", x.into());
+    let y = abs(y) - ym;
+    trace("y", y.into());
+    let z = abs(z) - zm;
+    trace("z", z.into());
+    let s = 1f32 / smooth_clamp(length!(x, y, z).powf(3f32), 0.1f32, 0.1f32, 1f32);
+    trace("s", s.into());
+    let x = x * s - ym;
+    trace("x", x.into());
+    let y = y * s - zm;
+    trace("y", y.into());
+    let z = z * s - xm;
+    trace("z", z.into());
+    let x = abs(x) - xm;
+    trace("x", x.into());
+    let y = abs(y) - ym;
+    trace("y", y.into());
+    let z = abs(z) - zm;
+    trace("z", z.into());
+    let s = 1f32 / smooth_clamp(length!(x, y, z).powf(3f32), 0.1f32, 0.1f32, 1f32);
+    trace("s", s.into());
+    let x = x * s - ym;
+    trace("x", x.into());
+    let y = y * s - zm;
+    trace("y", y.into());
+    let z = z * s - xm;
+    trace("z", z.into());
+    length!(z, y) - 0.1f32
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "mycelia",
+        sdf: mycelia,
+    }
+}
@@ -0,0 +1,64 @@
+// Generated by `arrow::codegen` from the `noise` scene.
+
+use arrow::core::v3;
+use arrow::eval::Value;
+use arrow::functions::{
+    billow, cellular, clamp, perlin, perlin_gradient, ridged, seeded_value, simplex, union, warp,
+    worley,
+};
+use arrow::length;
+use arrow::sdfs::Scene;
+use glam::Vec3;
+
+pub fn noise(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let d1 = (length!(x + 10.5f32, y - 4.5f32, z) - 3f32
+        + perlin(x, y, z, 0.6f32, 1f32, 4f32) * 1.2f32)
+        * 0.6f32;
+    trace("d1", d1.into());
+    let d2 = (length!(x + 3.5f32, y - 4.5f32, z) - 3f32
+        + simplex(x, y, z, 0.6f32, 2f32, 4f32) * 1.2f32)
+        * 0.6f32;
+    trace("d2", d2.into());
+    let d3 = (length!(x - 3.5f32, y - 4.5f32, z) - 3f32
+        + worley(x, y, z, 0.8f32, 3f32, 2f32) * 0.6f32)
+        * 0.6f32;
+    trace("d3", d3.into());
+    let d4 =
+        (length!(x - 10.5f32, y - 4.5f32, z) - 3f32 - ridged(x, y, z, 0.5f32, 4f32, 4f32) * 1.2f32)
+            * 0.5f32;
+    trace("d4", d4.into());
+    let d5 = (length!(x + 10.5f32, y + 4.5f32, z) - 3f32
+        + billow(x, y, z, 0.5f32, 5f32, 4f32) * 0.8f32)
+        * 0.6f32;
+    trace("d5", d5.into());
+    let d6 = (length!(x + 3.5f32, y + 4.5f32, z) - 3f32
+        + warp(x, y, z, 0.4f32, 6f32, 3f32, 2f32) * 1.5f32)
+        * 0.5f32;
+    trace("d6", d6.into());
+    let [u, v] = cellular(x, y, z, 0.8f32, 7f32);
+    trace("u", u.into());
+    trace("v", v.into());
+    let d7 = (length!(x - 3.5f32, y + 4.5f32, z) - 3f32 - clamp(v - u, 0f32, 0.3f32)) * 0.8f32;
+    trace("d7", d7.into());
+    let [gx, gy, gz] = perlin_gradient(x, y, z, 0.5f32, 8f32, 3f32);
+    trace("gx", gx.into());
+    trace("gy", gy.into());
+    trace("gz", gz.into());
+    let d8 = (length!(x - 10.5f32, y + 4.5f32, z) - 3f32 + length!(gx, gy, gz) * 0.3f32) * 0.5f32;
+    trace("d8", d8.into());
+    let d9 = length!(x, y, z) - 1.2f32 + seeded_value(x, y, z, 1f32, 9f32, 3f32) * 0.5f32;
+    trace("d9", d9.into());
+    union(vec![d1, d2, d3, d4, d5, d6, d7, d8, d9])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "noise",
+        sdf: noise,
+    }
+}
@@ -0,0 +1,76 @@
+// Generated by `arrow::codegen` from the `objects` scene.
+
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::{closest, rotate, scale, torus, translate};
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use arrow::{box3, length};
+use glam::Vec3;
+
+pub fn objects(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let [sx, sy, sz] = [x, y, z];
+    trace("sx", sx.into());
+    trace("sy", sy.into());
+    trace("sz", sz.into());
+    let sd = sy + 6f32;
+    trace("sd", sd.into());
+    let [o0x, o0y, o0z] = [x, y, z];
+    trace("o0x", o0x.into());
+    trace("o0y", o0y.into());
+    trace("o0z", o0z.into());
+    let [o0x, o0y, o0z] = translate(o0x, o0y, o0z, -6f32, -3f32, 2f32);
+    trace("o0x", o0x.into());
+    trace("o0y", o0y.into());
+    trace("o0z", o0z.into());
+    let o0d = length!(o0x, o0y, o0z) - 3f32;
+    trace("o0d", o0d.into());
+    let [o1x, o1y, o1z] = [x, y, z];
+    trace("o1x", o1x.into());
+    trace("o1y", o1y.into());
+    trace("o1z", o1z.into());
+    let [o1x, o1y, o1z] = translate(o1x, o1y, o1z, 0f32, -2f32, 0f32);
+    trace("o1x", o1x.into());
+    trace("o1y", o1y.into());
+    trace("o1z", o1z.into());
+    let [o1x, o1y, o1z] = rotate(o1x, o1y, o1z, 1f32, 1f32, 0f32, 0.6f32);
+    trace("o1x", o1x.into());
+    trace("o1y", o1y.into());
+    trace("o1z", o1z.into());
+    let o1d = box3!(o1x, o1y, o1z, 1.7f32) - 0.3f32;
+    trace("o1d", o1d.into());
+    let [o2x, o2y, o2z] = [x, y, z];
+    trace("o2x", o2x.into());
+    trace("o2y", o2y.into());
+    trace("o2z", o2z.into());
+    let [o2x, o2y, o2z] = translate(o2x, o2y, o2z, 6.5f32, -2f32, 1f32);
+    trace("o2x", o2x.into());
+    trace("o2y", o2y.into());
+    trace("o2z", o2z.into());
+    let [o2x, o2y, o2z] = rotate(o2x, o2y, o2z, 0f32, 1f32, 0f32, 0.8f32);
+    trace("o2x", o2x.into());
+    trace("o2y", o2y.into());
+    trace("o2z", o2z.into());
+    let [o2x, o2y, o2z] = scale(o2x, o2y, o2z, 1.2f32);
+    trace("o2x", o2x.into());
+    trace("o2y", o2y.into());
+    trace("o2z", o2z.into());
+    let o2d = torus(o2x, o2y, o2z, 2.5f32, 0.8f32) * 1.2f32;
+    trace("o2d", o2d.into());
+    let [d, id] = closest(vec![-1f32, sd, 0f32, o0d, 1f32, o1d, 2f32, o2d]);
+    trace("d", d.into());
+    trace("id", id.into());
+    d
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "objects",
+        sdf: objects,
+    }
+}
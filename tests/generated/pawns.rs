@@ -0,0 +1,53 @@
+// Generated by `arrow::codegen` from the `pawns` scene.
+
+use arrow::core::v3;
+use arrow::eval::Value;
+use arrow::functions::{floor, max, min, modulo, round_min};
+use arrow::noise::fbm_value;
+use arrow::sdfs::Scene;
+use arrow::{dot, length, value_noise};
+use glam::{Vec2, Vec3};
+
+pub fn pawns(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let i = modulo(floor(x / 8f32) + floor(z / 8f32), 2f32);
+    trace("i", i.into());
+    let col = 0.4f32 + 0.6f32 * i;
+    trace("col", col.into());
+    let x = modulo(x, 8f32) - 4f32;
+    trace("x", x.into());
+    let z = modulo(z, 8f32) - 4f32;
+    trace("z", z.into());
+    let a = length!(x, y, z) - 1f32;
+    trace("a", a.into());
+    let q = length!(x, z);
+    trace("q", q.into());
+    let b = max(dot!(1f32, 0.3f32, q, y), -5f32 - y);
+    trace("b", b.into());
+    let a = round_min(vec![a, b, 1f32]);
+    trace("a", a.into());
+    let y = y + 1f32;
+    trace("y", y.into());
+    let a = round_min(vec![a, length!(x, y * 5f32, z) - 0.8f32, 1f32]);
+    trace("a", a.into());
+    let y = y + 3f32;
+    trace("y", y.into());
+    let a = round_min(vec![a, length!(x, y * 2f32, z) - 1f32, 0.5f32]);
+    trace("a", a.into());
+    let y = y + 1f32;
+    trace("y", y.into());
+    let a = round_min(vec![a, length!(x, y * 3f32, z) - 1.7f32, 0.1f32]);
+    trace("a", a.into());
+    min(a, y + 0.5f32 * i * value_noise!(x, y, z, 8f32, 0f32))
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "pawns",
+        sdf: pawns,
+    }
+}
@@ -0,0 +1,110 @@
+// Generated by `arrow::codegen` from the `plato` scene.
+
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::{abs, cos, rot, round_min, sin, union};
+use arrow::noise::fbm_value;
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use arrow::{box3, length, value_noise};
+use glam::{Vec2, Vec3};
+
+pub fn plato(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.1f32;
+    let a1 = 0.2f32;
+    let d = 99f32;
+    trace("d", d.into());
+    let l = 10f32;
+    trace("l", l.into());
+    let x = x - l * 2f32;
+    trace("x", x.into());
+    let y = y - l;
+    trace("y", y.into());
+    let z = z + 2.5f32;
+    trace("z", z.into());
+    let x = x + l;
+    trace("x", x.into());
+    let a = a0 * (1f32 + 2f32);
+    trace("a", a.into());
+    let s = sin(a);
+    trace("s", s.into());
+    let c = cos(a);
+    trace("c", c.into());
+    let [x1, y1] = rot(x, y, s, c);
+    trace("x1", x1.into());
+    trace("y1", y1.into());
+    let a = a1 * (1f32 + 2f32);
+    trace("a", a.into());
+    let s = sin(a);
+    trace("s", s.into());
+    let c = cos(a);
+    trace("c", c.into());
+    let [x1, z1] = rot(x1, z, s, c);
+    trace("x1", x1.into());
+    trace("z1", z1.into());
+    let d = round_min(vec![d, box3!(x1, y1, z1, 4f32), 3f32]);
+    trace("d", d.into());
+    let x = x + l;
+    trace("x", x.into());
+    let a = a0 * (2f32 + 2f32);
+    trace("a", a.into());
+    let s = sin(a);
+    trace("s", s.into());
+    let c = cos(a);
+    trace("c", c.into());
+    let [x1, y1] = rot(x, y, s, c);
+    trace("x1", x1.into());
+    trace("y1", y1.into());
+    let a = a1 * (2f32 + 2f32);
+    trace("a", a.into());
+    let s = sin(a);
+    trace("s", s.into());
+    let c = cos(a);
+    trace("c", c.into());
+    let [x1, z1] = rot(x1, z, s, c);
+    trace("x1", x1.into());
+    trace("z1", z1.into());
+    let d = round_min(vec![d, box3!(x1, y1, z1, 4f32), 3f32]);
+    trace("d", d.into());
+    let x = x + l;
+    trace("x", x.into());
+    let a = a0 * (3f32 + 2f32);
+    trace("a", a.into());
+    let s = sin(a);
+    trace("s", s.into());
+    let c = cos(a);
+    trace("c", c.into());
+    let [x1, y1] = rot(x, y, s, c);
+    trace("x1", x1.into());
+    trace("y1", y1.into());
+    let a = a1 * (3f32 + 2f32);
+    trace("a", a.into());
+    let s = sin(a);
+    trace("s", s.into());
+    let c = cos(a);
+    trace("c", c.into());
+    let [x1, z1] = rot(x1, z, s, c);
+    trace("x1", x1.into());
+    trace("z1", z1.into());
+    let d = round_min(vec![d, box3!(x1, y1, z1, 4f32), 3f32]);
+    trace("d", d.into());
+    union(vec![
+        d + 0.5f32,
+        length!(
+            value_noise!(x, y, z, 0.1f32, 1f32, 2f32) - 0.5f32,
+            abs(d) - 0.1f32
+        ) - 0.4f32,
+    ])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "plato",
+        sdf: plato,
+    }
+}
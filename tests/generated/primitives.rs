@@ -0,0 +1,84 @@
+// Generated by `arrow::codegen` from the `primitives` scene.
+
+use arrow::eval::Value;
+use arrow::functions::{
+    capped_cone, capped_torus, cone, ellipsoid, extrude, hex_prism, hexagon, link, octahedron,
+    pyramid, round_cone, segment, solid_angle, tri_prism, triangle2, ud_quad, ud_triangle, union,
+};
+use arrow::sdfs::Scene;
+use glam::Vec3;
+
+pub fn primitives(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, _trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    union(vec![
+        cone(x + 9f32, y - 7f32, z, 0.5f32, 3f32),
+        capped_cone(x + 4f32, y - 5f32, z, 1.5f32, 2f32, 0.7f32),
+        round_cone(x, y - 3.5f32, z, 1.2f32, 0.5f32, 2.5f32),
+        ellipsoid(x - 4f32, y - 5f32, z, 1.5f32, 2f32, 1f32),
+        hex_prism(x - 9f32, y - 5f32, z, 1.5f32, 1f32),
+        tri_prism(x + 9f32, y, z, 2.5f32, 1f32),
+        octahedron(x + 4f32, y, z, 1.8f32),
+        pyramid(x / 3f32, (y + 1.5f32) / 3f32, z / 3f32, 1f32) * 3f32,
+        link(x - 4f32, y, z, 1f32, 1.2f32, 0.3f32),
+        solid_angle(x - 9f32, y + 1f32, z, 0.6f32, 2.5f32),
+        capped_torus(x + 9f32, y + 5f32, z, 2f32, 1.5f32, 0.4f32),
+        ud_triangle(
+            x + 4f32,
+            y + 5f32,
+            z,
+            -1.5f32,
+            -1f32,
+            0f32,
+            1.5f32,
+            -1f32,
+            0f32,
+            0f32,
+            1.5f32,
+            -1f32,
+        ) - 0.1f32,
+        ud_quad(
+            x,
+            y + 6f32,
+            z,
+            -1.5f32,
+            -1f32,
+            -1f32,
+            1.5f32,
+            -1f32,
+            -1f32,
+            1.5f32,
+            1f32,
+            1f32,
+            -1.5f32,
+            1f32,
+            1f32,
+        ) - 0.1f32,
+        extrude(hexagon(x - 4f32, y + 5f32, 1.5f32), z, 0.5f32),
+        extrude(
+            triangle2(
+                x - 9f32,
+                y + 6f32,
+                -1.5f32,
+                -1f32,
+                1.5f32,
+                -1f32,
+                0f32,
+                1.5f32,
+            ),
+            z,
+            0.7f32,
+        ) - 0.1f32,
+        extrude(segment(x, y - 8f32, -2f32, 0f32, 2f32, 0f32), z, 0.2f32) - 0.3f32,
+    ])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "primitives",
+        sdf: primitives,
+    }
+}
@@ -0,0 +1,82 @@
+// Generated by `arrow::codegen` from the `quanta` scene.
+
+use arrow::core::v3;
+use arrow::eval::Value;
+use arrow::functions::{cos, floor, hash, intersect, modulo, rot0, rot1, round_min};
+use arrow::noise::fbm_value;
+use arrow::sdfs::Scene;
+use arrow::{box2, length, value_noise};
+use glam::{Vec2, Vec3};
+
+pub fn quanta(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.1f32;
+    let a1 = 0.2f32;
+    let s = 20f32;
+    trace("s", s.into());
+    let [x, z] = rot0(x, z, a0);
+    trace("x", x.into());
+    trace("z", z.into());
+    let [y, x] = rot1(y, x, a1);
+    trace("y", y.into());
+    trace("x", x.into());
+    let z = z + 17f32;
+    trace("z", z.into());
+    let y = y + 27f32;
+    trace("y", y.into());
+    let i = 0f32;
+    trace("i", i.into());
+    let z = z + hash(floor(x / s), 0f32, 0f32) * 70f32;
+    trace("z", z.into());
+    let i_1 = i + 1f32;
+    trace("i_1", i_1.into());
+    let x = x - value_noise!(x, y, z, 0.1f32, i_1) * 5f32 * i_1;
+    trace("x", x.into());
+    let i = i_1;
+    trace("i", i.into());
+    let xi = floor(x / s);
+    trace("xi", xi.into());
+    let x = modulo(x, s) - s / 2f32;
+    trace("x", x.into());
+    let i_2 = i + 1f32;
+    trace("i_2", i_2.into());
+    let z = z - value_noise!(x, y, z, 0.1f32, i_2) * 5f32 * i_2;
+    trace("z", z.into());
+    let i = i_2;
+    trace("i", i.into());
+    let zi = floor(z / s);
+    trace("zi", zi.into());
+    let z = modulo(z, s) - s / 2f32;
+    trace("z", z.into());
+    let i = hash(xi, zi, 0f32);
+    trace("i", i.into());
+    let j = hash(xi, floor(y / 5f32), 0f32);
+    trace("j", j.into());
+    let d = if i > 0.1f32 {
+        round_min(vec![
+            length!(x, z) - 1f32 * i - 0.5f32 * (cos(y / 4f32) + 1f32),
+            box2!(
+                length!(x, z) - (cos(floor(y / 4f32)) + 1f32) * 2f32,
+                modulo(y, 4f32) - 2f32,
+                0.1f32,
+                0.2f32
+            ) - 0.05f32,
+            1f32,
+        ])
+    } else {
+        length!(x, modulo(y, 5f32) - 2.5f32, z) - intersect(vec![j, 0f32]) * 2f32
+    };
+    trace("d", d.into());
+    d
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "quanta",
+        sdf: quanta,
+    }
+}
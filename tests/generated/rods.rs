@@ -0,0 +1,23 @@
+// Generated by `arrow::codegen` from the `rods` scene.
+
+use arrow::eval::Value;
+use arrow::functions::abs;
+use arrow::length;
+use arrow::sdfs::Scene;
+use glam::{Vec2, Vec3};
+
+pub fn rods(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, _trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z: _ } = p;
+    length!(abs(abs(x) - 3f32) - 3f32, abs(y) - 3f32) - 2f32
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "rods",
+        sdf: rods,
+    }
+}
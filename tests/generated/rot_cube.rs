@@ -0,0 +1,29 @@
+// Generated by `arrow::codegen` from the `rot_cube` scene.
+
+use arrow::box3;
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::rot0;
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use glam::Vec3;
+
+pub fn rot_cube(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.1f32;
+    let [a, b] = rot0(x, y - 9f32, a0);
+    trace("a", a.into());
+    trace("b", b.into());
+    box3!(a, b, z, 4f32) - 0.5f32
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "rot_cube",
+        sdf: rot_cube,
+    }
+}
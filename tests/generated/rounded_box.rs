@@ -0,0 +1,24 @@
+// Generated by `arrow::codegen` from the `rounded_box` scene.
+
+use arrow::box3;
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use glam::Vec3;
+
+pub fn rounded_box(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, _trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    box3!(x, y, z, 7f32, 4f32, 4f32) - 5f32
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "rounded_box",
+        sdf: rounded_box,
+    }
+}
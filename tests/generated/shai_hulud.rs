@@ -0,0 +1,83 @@
+// Generated by `arrow::codegen` from the `shai_hulud` scene.
+
+use arrow::core::v3;
+use arrow::eval::Value;
+use arrow::functions::{abs, atan2, max, min, modulo, rot0, round_min, sin, smoothstep, torus};
+use arrow::noise::fbm_value;
+use arrow::sdfs::Scene;
+use arrow::{length, value_noise};
+use glam::{Vec2, Vec3};
+
+pub fn shai_hulud(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.1f32;
+    let [x, z] = rot0(x, z, a0);
+    trace("x", x.into());
+    trace("z", z.into());
+    let m = value_noise!(x, y, z, 0.4f32, 1f32);
+    trace("m", m.into());
+    let r = 30f32;
+    trace("r", r.into());
+    let t = 0.2f32;
+    trace("t", t.into());
+    let v = atan2(z, y - 7f32);
+    trace("v", v.into());
+    let u = length!(z, y - 7f32);
+    trace("u", u.into());
+    min(
+        min(
+            max(
+                torus(x + 5f32, y + 5.5f32, z + 10f32, 0.5f32, 0.05f32),
+                y + 5.5f32,
+            ),
+            length!(x * 3f32 + 15f32, y / 4f32 + 2.4f32, z * 3f32 + 30f32) - 1f32,
+        ),
+        round_min(vec![
+            y - 20f32
+                + value_noise!(x / 3f32, y / 3f32, z / 2f32, 1f32, 1f32)
+                + smoothstep(
+                    30f32,
+                    0f32,
+                    15f32 - abs(x + sin(z / 2f32) + sin(z / 6f32) + sin(z / 8f32) - 4f32),
+                ) * 15f32
+                + smoothstep(
+                    30f32,
+                    0f32,
+                    15f32
+                        - abs(
+                            modulo(z + x + sin(x / 2f32) + sin(x / 6f32) + sin(x / 8f32), 60f32)
+                                - 35f32,
+                        ),
+                ) * 15f32,
+            max(
+                -length!(y - 7f32, z, x + 3f32) + 4f32 + sin(v * 3f32 - 2f32),
+                min(
+                    min(
+                        min(
+                            torus(x - 90f32, y + 23f32, z, 20f32, 5f32 + m),
+                            max(abs(torus(x, y + r - 7f32, z, r, 5f32 + m)) - t, -x),
+                        ),
+                        max(abs(length!(x, y - 7f32, z) - 5f32 - m) - t, x),
+                    ),
+                    length!(
+                        u / 3f32 - 1f32,
+                        abs(abs(abs(x * 2f32 - 4f32) - 2f32) - 2f32) - 1f32,
+                        modulo(x / 2f32 + v * 9f32, 1.6f32) - 0.8f32
+                    ) - 0.34f32,
+                ),
+            ),
+            2f32,
+        ]),
+    )
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "shai_hulud",
+        sdf: shai_hulud,
+    }
+}
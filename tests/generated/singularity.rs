@@ -0,0 +1,85 @@
+// Generated by `arrow::codegen` from the `singularity` scene.
+
+use arrow::core::v3;
+use arrow::eval::Value;
+use arrow::functions::{abs, acos, atan2, corner, rot0, round_min, union};
+use arrow::noise::fbm_value;
+use arrow::sdfs::Scene;
+use arrow::{box2, length, value_noise};
+use glam::{Vec2, Vec3};
+
+pub fn singularity(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.1f32;
+    let [x, z] = rot0(x, z, a0);
+    trace("x", x.into());
+    trace("z", z.into());
+    let l = length!(x, y, z);
+    trace("l", l.into());
+    let n = 2f32 * value_noise!(atan2(z, x), acos(y / l), l, 0.3f32, 0f32, 1f32);
+    trace("n", n.into());
+    let d = l - 20f32 + n * 5f32;
+    trace("d", d.into());
+    let d = abs(d) - 5f32;
+    trace("d", d.into());
+    let d = abs(d) - 1f32;
+    trace("d", d.into());
+    let b = 99f32;
+    trace("b", b.into());
+    let b = union(vec![
+        b,
+        box2!(
+            x,
+            y - n * 3f32 + 10f32 - 1f32 * 10f32,
+            100f32,
+            2f32 + 1f32 * 0.5f32
+        ),
+    ]);
+    trace("b", b.into());
+    let b = union(vec![
+        b,
+        box2!(
+            x,
+            y - n * 3f32 + 10f32 - 2f32 * 10f32,
+            100f32,
+            2f32 + 2f32 * 0.5f32
+        ),
+    ]);
+    trace("b", b.into());
+    let b = union(vec![
+        b,
+        box2!(
+            x,
+            y - n * 3f32 + 10f32 - 3f32 * 10f32,
+            100f32,
+            2f32 + 3f32 * 0.5f32
+        ),
+    ]);
+    trace("b", b.into());
+    let b = union(vec![
+        b,
+        box2!(
+            x,
+            y - n * 3f32 + 10f32 - 4f32 * 10f32,
+            100f32,
+            2f32 + 4f32 * 0.5f32
+        ),
+    ]);
+    trace("b", b.into());
+    round_min(vec![
+        y + 20f32 - abs(n) * 0.2f32,
+        corner(0f32 - b, d) - 0.4f32,
+        20f32,
+    ])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "singularity",
+        sdf: singularity,
+    }
+}
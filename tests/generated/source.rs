@@ -0,0 +1,86 @@
+// Generated by `arrow::codegen` from the `source` scene.
+
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::{abs, max, min, modulo, rot0, rot1, union};
+use arrow::noise::fbm_value;
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use arrow::{box3, length, value_noise};
+use glam::{Vec2, Vec3};
+
+pub fn source(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.1f32;
+    let a1 = 0.2f32;
+    let zf = z + value_noise!(x, y, z, 0.03f32, 1f32) * 40f32;
+    trace("zf", zf.into());
+    let yf = y + value_noise!(x, y, z, 0.03f32, 1f32) * 40f32;
+    trace("yf", yf.into());
+    let zf = zf + value_noise!(x, y, z, 0.1f32, 2f32) * 20f32;
+    trace("zf", zf.into());
+    let f = length!(zf + 10f32, yf) - 20f32;
+    trace("f", f.into());
+    let w = y + value_noise!(x, y, z + 30f32, 0.4f32, 2f32, 1f32) * 2.2f32 + 1f32;
+    trace("w", w.into());
+    let g = y + value_noise!(x, y, z, 0.1f32, 1f32);
+    trace("g", g.into());
+    let p = min(max(g, -f), max(w, f)) + value_noise!(x, y, z, 0.02f32, 2f32, 2f32) * 4f32;
+    trace("p", p.into());
+    let [ex, ey] = rot0(x - 18f32, y - 6f32, a0);
+    trace("ex", ex.into());
+    trace("ey", ey.into());
+    let [ey, ez] = rot1(ey, z - 11f32, a1);
+    trace("ey", ey.into());
+    trace("ez", ez.into());
+    let es = 2f32;
+    trace("es", es.into());
+    let e = box3!(ex, ey, ez, es * 1.5f32, es, 0.1f32);
+    trace("e", e.into());
+    let sg = 0.5f32;
+    trace("sg", sg.into());
+    let xg = ex + value_noise!(ex / 1.6f32, ey, ez, 1f32, 1f32, 2f32) * 2f32;
+    trace("xg", xg.into());
+    let yg = ey + value_noise!(ex / 1.6f32, ey, ez, 1f32, 1f32, 2f32) * 2f32;
+    trace("yg", yg.into());
+    let zg = ez + value_noise!(ex / 1.6f32, ey, ez, 1f32, 1f32, 2f32) * 2f32;
+    trace("zg", zg.into());
+    let g = 1000000f32;
+    trace("g", g.into());
+    let g = union(vec![
+        g,
+        length!(ez - 0.1f32, modulo(xg, sg) - sg / 2f32) - sg * 0.07f32,
+    ]);
+    trace("g", g.into());
+    let g = union(vec![
+        g,
+        length!(ez - 0.1f32, modulo(yg, sg) - sg / 2f32) - sg * 0.07f32,
+    ]);
+    trace("g", g.into());
+    let g = max(
+        g,
+        box3!(ex, ey, ez, es * 1.5f32 * 0.85f32, es * 0.85f32, 2f32),
+    );
+    trace("g", g.into());
+    min(
+        min(
+            min(
+                min(min(p, e), g),
+                box3!(ex + 3f32, abs(ey) - 0.8f32, ez, 4f32, 0.1f32, 0.1f32),
+            ),
+            box3!(ex + 3f32, ey, ez, 0.05f32, es * 1.2f32, 0.5f32),
+        ),
+        box3!(ex - 3f32, ey, ez, 0.05f32, es * 0.2f32, 0.2f32),
+    )
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "source",
+        sdf: source,
+    }
+}
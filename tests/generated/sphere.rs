@@ -0,0 +1,26 @@
+// Generated by `arrow::codegen` from the `sphere` scene.
+
+use arrow::core::v3;
+use arrow::eval::Value;
+use arrow::functions::abs;
+use arrow::length;
+use arrow::sdfs::Scene;
+use glam::Vec3;
+
+pub fn sphere(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let b = abs(y - 12f32) - 6f32;
+    trace("b", b.into());
+    length!(x, b, z) - 6f32
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "sphere",
+        sdf: sphere,
+    }
+}
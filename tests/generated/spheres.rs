@@ -0,0 +1,74 @@
+// Generated by `arrow::codegen` from the `spheres` scene.
+
+use arrow::core::v3;
+use arrow::eval::Value;
+use arrow::functions::{acos, atan2, clamp, cos, sin, union};
+use arrow::length;
+use arrow::sdfs::Scene;
+use glam::{Vec2, Vec3};
+
+pub fn spheres(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let y = y - 5f32;
+    trace("y", y.into());
+    let z = z - 3f32;
+    trace("z", z.into());
+    let r = length!(x, y, z);
+    trace("r", r.into());
+    let ph = atan2(y, x);
+    trace("ph", ph.into());
+    let th = acos(z / r);
+    trace("th", th.into());
+    let n = 18f32;
+    trace("n", n.into());
+    let r0 = r - 33f32;
+    trace("r0", r0.into());
+    let cs = sin(n * ph) * cos(n * th);
+    trace("cs", cs.into());
+    let c0 = length!(r0, cs) - 0.1f32;
+    trace("c0", c0.into());
+    let c3 = length!(r0 - clamp(r0, -2f32, -1f32), r / n * (cs - 0.5f32)) - 0.05f32;
+    trace("c3", c3.into());
+    let c4 = length!(r0 - clamp(r0, -1f32, 0.5f32), r / n * (cs - 0.75f32)) - 0.05f32;
+    trace("c4", c4.into());
+    let c5 = length!(r0 - clamp(r0, -1f32, 1f32), r / n * (cs - 0.95f32)) - 0.025f32;
+    trace("c5", c5.into());
+    let n = 4f32;
+    trace("n", n.into());
+    let r1 = 1.25f32 * n * sin(th);
+    trace("r1", r1.into());
+    let x = r0;
+    trace("x", x.into());
+    let y = r / n * sin(th) * cos(n * ph) * sin(n * th);
+    trace("y", y.into());
+    let z = r / n * sin(th) * sin(n * ph) * cos(n * th);
+    trace("z", z.into());
+    let c1 = length!(x, y, z) - r1;
+    trace("c1", c1.into());
+    let r = length!(x, y, z);
+    trace("r", r.into());
+    let ph = atan2(y, x);
+    trace("ph", ph.into());
+    let th = acos(z / r);
+    trace("th", th.into());
+    let zr = r - r1 - clamp(r - r1, 0f32, 0.5f32);
+    trace("zr", zr.into());
+    let n = 12f32;
+    trace("n", n.into());
+    let x = r / n * (sin(th) * cos(n * ph) * sin(n * th) - 0.5f32);
+    trace("x", x.into());
+    let c2 = length!(x, zr) - 0.05f32;
+    trace("c2", c2.into());
+    union(vec![c0, c1, c2, c3, c4, c5])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "spheres",
+        sdf: spheres,
+    }
+}
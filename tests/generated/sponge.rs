@@ -0,0 +1,80 @@
+// Generated by `arrow::codegen` from the `sponge` scene.
+
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::{corner, modulo, union};
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use arrow::{box2, box3};
+use glam::{Vec2, Vec3};
+
+pub fn sponge(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let r = box3!(x, y, z, 9f32);
+    trace("r", r.into());
+    let s = 1f32;
+    trace("s", s.into());
+    let x = (modulo(x + 9f32, 18f32) - 9f32) * 3f32;
+    trace("x", x.into());
+    let y = (modulo(y + 9f32, 18f32) - 9f32) * 3f32;
+    trace("y", y.into());
+    let z = (modulo(z + 9f32, 18f32) - 9f32) * 3f32;
+    trace("z", z.into());
+    let s = s / 3f32;
+    trace("s", s.into());
+    let r = corner(
+        r,
+        -union(vec![
+            box2!(x, y, 9f32),
+            box2!(y, z, 9f32),
+            box2!(z, x, 9f32),
+        ]) * s,
+    );
+    trace("r", r.into());
+    let x = (modulo(x + 9f32, 18f32) - 9f32) * 3f32;
+    trace("x", x.into());
+    let y = (modulo(y + 9f32, 18f32) - 9f32) * 3f32;
+    trace("y", y.into());
+    let z = (modulo(z + 9f32, 18f32) - 9f32) * 3f32;
+    trace("z", z.into());
+    let s = s / 3f32;
+    trace("s", s.into());
+    let r = corner(
+        r,
+        -union(vec![
+            box2!(x, y, 9f32),
+            box2!(y, z, 9f32),
+            box2!(z, x, 9f32),
+        ]) * s,
+    );
+    trace("r", r.into());
+    let x = (modulo(x + 9f32, 18f32) - 9f32) * 3f32;
+    trace("x", x.into());
+    let y = (modulo(y + 9f32, 18f32) - 9f32) * 3f32;
+    trace("y", y.into());
+    let z = (modulo(z + 9f32, 18f32) - 9f32) * 3f32;
+    trace("z", z.into());
+    let s = s / 3f32;
+    trace("s", s.into());
+    let r = corner(
+        r,
+        -union(vec![
+            box2!(x, y, 9f32),
+            box2!(y, z, 9f32),
+            box2!(z, x, 9f32),
+        ]) * s,
+    );
+    trace("r", r.into());
+    r
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "sponge",
+        sdf: sponge,
+    }
+}
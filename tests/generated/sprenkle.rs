@@ -0,0 +1,36 @@
+// Generated by `arrow::codegen` from the `sprenkle` scene.
+
+use arrow::eval::Value;
+use arrow::functions::{abs, modulo, round_min, smoothstep, torus};
+use arrow::noise::fbm_value;
+use arrow::sdfs::Scene;
+use arrow::{length, value_noise};
+use glam::{Vec2, Vec3};
+
+pub fn sprenkle(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let y = y + 7f32;
+    trace("y", y.into());
+    round_min(vec![
+        torus(
+            x,
+            y - 12f32,
+            modulo(z, 8f32) - 4f32,
+            7f32 + 3f32 * smoothstep(9f32, 15f32, y) + 4f32 * value_noise!(x, y, z, 0.3f32, 1f32),
+            2.7f32 - 2f32 * smoothstep(9f32, 15f32, y) + value_noise!(x, y, z, 0.2f32, 2f32),
+        ),
+        length!(x, y + 83f32) - 90f32,
+        1f32,
+    ]) - 0.1f32 * smoothstep(0f32, 0.15f32, abs(value_noise!(x, y, z, 2f32, 0f32, 3f32)))
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "sprenkle",
+        sdf: sprenkle,
+    }
+}
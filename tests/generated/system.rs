@@ -0,0 +1,451 @@
+// Generated by `arrow::codegen` from the `system` scene.
+
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::{atan2, floor, hash, min, mix, modulo, pow, rot0, sqrt};
+use arrow::noise::fbm_value;
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use arrow::{box3, value_noise};
+use glam::Vec3;
+
+pub fn system(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.2f32;
+    let _p_i = std::f32::consts::PI;
+    trace("PI", _p_i.into());
+    let [z, y] = rot0(z, y, a0);
+    trace("z", z.into());
+    trace("y", y.into());
+    let z = z + 10f32;
+    trace("z", z.into());
+    let r = sqrt(x * x + y * y);
+    trace("r", r.into());
+    let t = atan2(y, x) / (2f32 * _p_i);
+    trace("t", t.into());
+    let d = 10000f32;
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 1f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                1f32 * 1.2f32 + t * mix(1f32, 200f32, pow(hash(1f32, 1f32, 1f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 2f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                2f32 * 1.2f32 + t * mix(1f32, 200f32, pow(hash(2f32, 2f32, 2f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 3f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                3f32 * 1.2f32 + t * mix(1f32, 200f32, pow(hash(3f32, 3f32, 3f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 4f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                4f32 * 1.2f32 + t * mix(1f32, 200f32, pow(hash(4f32, 4f32, 4f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 5f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                5f32 * 1.2f32 + t * mix(1f32, 200f32, pow(hash(5f32, 5f32, 5f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 6f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                6f32 * 1.2f32 + t * mix(1f32, 200f32, pow(hash(6f32, 6f32, 6f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 7f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                7f32 * 1.2f32 + t * mix(1f32, 200f32, pow(hash(7f32, 7f32, 7f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 8f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                8f32 * 1.2f32 + t * mix(1f32, 200f32, pow(hash(8f32, 8f32, 8f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 9f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                9f32 * 1.2f32 + t * mix(1f32, 200f32, pow(hash(9f32, 9f32, 9f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 10f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                10f32 * 1.2f32
+                    + t * mix(1f32, 200f32, pow(hash(10f32, 10f32, 10f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 11f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                11f32 * 1.2f32
+                    + t * mix(1f32, 200f32, pow(hash(11f32, 11f32, 11f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 12f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                12f32 * 1.2f32
+                    + t * mix(1f32, 200f32, pow(hash(12f32, 12f32, 12f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 13f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                13f32 * 1.2f32
+                    + t * mix(1f32, 200f32, pow(hash(13f32, 13f32, 13f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 14f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                14f32 * 1.2f32
+                    + t * mix(1f32, 200f32, pow(hash(14f32, 14f32, 14f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 15f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                15f32 * 1.2f32
+                    + t * mix(1f32, 200f32, pow(hash(15f32, 15f32, 15f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 16f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                16f32 * 1.2f32
+                    + t * mix(1f32, 200f32, pow(hash(16f32, 16f32, 16f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 17f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                17f32 * 1.2f32
+                    + t * mix(1f32, 200f32, pow(hash(17f32, 17f32, 17f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 18f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                18f32 * 1.2f32
+                    + t * mix(1f32, 200f32, pow(hash(18f32, 18f32, 18f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 19f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                19f32 * 1.2f32
+                    + t * mix(1f32, 200f32, pow(hash(19f32, 19f32, 19f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    let d = min(
+        box3!(
+            r - 2f32 * 20f32,
+            t,
+            z,
+            0.8f32,
+            1f32,
+            if floor(modulo(
+                20f32 * 1.2f32
+                    + t * mix(1f32, 200f32, pow(hash(20f32, 20f32, 20f32) + 0.5f32, 2f32)),
+                3f32
+            )) > 0f32
+            {
+                -1f32
+            } else {
+                1f32
+            }
+        ) - 0.2f32,
+        d,
+    );
+    trace("d", d.into());
+    min(d, -z + value_noise!(x, y, x, 0.1f32, 1f32) * 2f32)
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "system",
+        sdf: system,
+    }
+}
@@ -0,0 +1,34 @@
+// Generated by `arrow::codegen` from the `target` scene.
+
+use arrow::eval::Value;
+use arrow::functions::{clamp, corner, smoothstep, triangle, union};
+use arrow::noise::fbm_value;
+use arrow::sdfs::Scene;
+use arrow::{length, value_noise};
+use glam::{Vec2, Vec3};
+
+pub fn target(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let n = value_noise!(x, y, z, 0.4f32, 0f32, 2f32) * 4f32;
+    trace("n", n.into());
+    union(vec![
+        length!(corner(x, -12f32 - z), -corner(2f32 - y, -19f32 - x)) - 0.5f32,
+        length!(
+            triangle(length!(x, y) / 4f32) * 4f32 + n,
+            z - clamp(-3f32, 3f32, z)
+        ) - 0.5f32
+            - 1.5f32 * smoothstep(3f32, -3f32, y)
+            - 3f32 * smoothstep(-3f32, -9f32, y),
+    ])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "target",
+        sdf: target,
+    }
+}
@@ -0,0 +1,127 @@
+// Generated by `arrow::codegen` from the `temple` scene.
+
+use arrow::eval::Value;
+use arrow::functions::{abs, rot0, rot1, round_min, smooth_abs, torus};
+use arrow::noise::fbm_value;
+use arrow::sdfs::Scene;
+use arrow::{length, value_noise};
+use glam::{Vec2, Vec3};
+
+pub fn temple(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.1f32;
+    let a1 = 0.2f32;
+    let d = 99f32;
+    trace("d", d.into());
+    let [y, z] = rot1(y, z, a1);
+    trace("y", y.into());
+    trace("z", z.into());
+    let f = y + abs(value_noise!(x, z, 1f32, 0f32, 3f32)) * 5f32;
+    trace("f", f.into());
+    let [x, y, z] = [y, z, x];
+    trace("x", x.into());
+    trace("y", y.into());
+    trace("z", z.into());
+    let [x, z] = rot0(x, z, a0);
+    trace("x", x.into());
+    trace("z", z.into());
+    let [x, z] = rot1(x, z, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let x = smooth_abs(x, 2f32) - 3f32;
+    trace("x", x.into());
+    let y = smooth_abs(y, 2f32) - 3f32;
+    trace("y", y.into());
+    let z = smooth_abs(z, 2f32) - 3f32;
+    trace("z", z.into());
+    let d = round_min(vec![d, torus(y, z, x, 5f32, 0.5f32 + 1f32 * 0.2f32), 1f32]);
+    trace("d", d.into());
+    let [x, y, z] = [y, z, x];
+    trace("x", x.into());
+    trace("y", y.into());
+    trace("z", z.into());
+    let [x, z] = rot0(x, z, a0);
+    trace("x", x.into());
+    trace("z", z.into());
+    let [x, z] = rot1(x, z, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let x = smooth_abs(x, 2f32) - 3f32;
+    trace("x", x.into());
+    let y = smooth_abs(y, 2f32) - 3f32;
+    trace("y", y.into());
+    let z = smooth_abs(z, 2f32) - 3f32;
+    trace("z", z.into());
+    let d = round_min(vec![d, torus(y, z, x, 5f32, 0.5f32 + 2f32 * 0.2f32), 1f32]);
+    trace("d", d.into());
+    let [x, y, z] = [y, z, x];
+    trace("x", x.into());
+    trace("y", y.into());
+    trace("z", z.into());
+    let [x, z] = rot0(x, z, a0);
+    trace("x", x.into());
+    trace("z", z.into());
+    let [x, z] = rot1(x, z, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let x = smooth_abs(x, 2f32) - 3f32;
+    trace("x", x.into());
+    let y = smooth_abs(y, 2f32) - 3f32;
+    trace("y", y.into());
+    let z = smooth_abs(z, 2f32) - 3f32;
+    trace("z", z.into());
+    let d = round_min(vec![d, torus(y, z, x, 5f32, 0.5f32 + 3f32 * 0.2f32), 1f32]);
+    trace("d", d.into());
+    let [x, y, z] = [y, z, x];
+    trace("x", x.into());
+    trace("y", y.into());
+    trace("z", z.into());
+    let [x, z] = rot0(x, z, a0);
+    trace("x", x.into());
+    trace("z", z.into());
+    let [x, z] = rot1(x, z, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let x = smooth_abs(x, 2f32) - 3f32;
+    trace("x", x.into());
+    let y = smooth_abs(y, 2f32) - 3f32;
+    trace("y", y.into());
+    let z = smooth_abs(z, 2f32) - 3f32;
+    trace("z", z.into());
+    let d = round_min(vec![d, torus(y, z, x, 5f32, 0.5f32 + 4f32 * 0.2f32), 1f32]);
+    trace("d", d.into());
+    let [x, y, z] = [y, z, x];
+    trace("x", x.into());
+    trace("y", y.into());
+    trace("z", z.into());
+    let [x, z] = rot0(x, z, a0);
+    trace("x", x.into());
+    trace("z", z.into());
+    let [x, z] = rot1(x, z, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let x = smooth_abs(x, 2f32) - 3f32;
+    trace("x", x.into());
+    let y = smooth_abs(y, 2f32) - 3f32;
+    trace("y", y.into());
+    let z = smooth_abs(z, 2f32) - 3f32;
+    trace("z", z.into());
+    let d = round_min(vec![d, torus(y, z, x, 5f32, 0.5f32 + 5f32 * 0.2f32), 1f32]);
+    trace("d", d.into());
+    round_min(vec![
+        f,
+        length!(d, value_noise!(x, y, z, 0.5f32, 1f32)) - 0.1f32,
+        0.5f32,
+    ])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "temple",
+        sdf: temple,
+    }
+}
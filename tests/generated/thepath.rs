@@ -0,0 +1,167 @@
+// Generated by `arrow::codegen` from the `thepath` scene.
+
+use arrow::core::v3;
+use arrow::eval::Value;
+use arrow::functions::{abs, floor, hash, modulo, rot0, rot1, round_max, sin, union};
+use arrow::noise::fbm_value;
+use arrow::sdfs::Scene;
+use arrow::{length, value_noise};
+use glam::Vec3;
+
+pub fn thepath(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.1f32;
+    let a1 = 0.2f32;
+    let xm = modulo(x, 20f32) - 10f32;
+    trace("xm", xm.into());
+    let xi = floor(x / 20f32);
+    trace("xi", xi.into());
+    let ym = modulo(y, 20f32) - 10f32;
+    trace("ym", ym.into());
+    let yi = floor(y / 20f32);
+    trace("yi", yi.into());
+    let zm = modulo(z, 20f32) - 10f32;
+    trace("zm", zm.into());
+    let zi = floor(z / 20f32);
+    trace("zi", zi.into());
+    let d = 99f32;
+    trace("d", d.into());
+    let g0 = 0.05f32;
+    trace("g0", g0.into());
+    let y = y - 20f32;
+    trace("y", y.into());
+    let [z, x] = rot0(z, x, a0);
+    trace("z", z.into());
+    trace("x", x.into());
+    let n = value_noise!(x, y, z, 0.1f32, 1f32);
+    trace("n", n.into());
+    let n1 = value_noise!(x, y, z, 0.3f32, 2f32, 3f32);
+    trace("n1", n1.into());
+    let x = x - 20f32;
+    trace("x", x.into());
+    let o = 1f32 * 200f32 + 20f32;
+    trace("o", o.into());
+    let e = abs(y + n1 / 2f32 + sin(z * 0.05f32 + o) * 10f32) - 1f32;
+    trace("e", e.into());
+    let e = round_max(vec![
+        e,
+        abs(z + sin(x * 0.05f32 + o) * 25f32) - 5f32 + n1 * 2f32,
+        0.2f32,
+    ]);
+    trace("e", e.into());
+    let x1 = modulo(x + n * 10f32, 3f32) - 1.5f32;
+    trace("x1", x1.into());
+    let z1 = modulo(z + n * 10f32, 3f32) - 1.5f32;
+    trace("z1", z1.into());
+    let e = round_max(vec![e, -(abs(z1) - g0), 0.25f32]);
+    trace("e", e.into());
+    let e = round_max(vec![e, -(abs(x1) - g0), 0.25f32]);
+    trace("e", e.into());
+    let d = union(vec![d, e]);
+    trace("d", d.into());
+    let [x, z] = rot1(z, x, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let y = y + 20f32;
+    trace("y", y.into());
+    let x = x - 20f32;
+    trace("x", x.into());
+    let o = 2f32 * 200f32 + 20f32;
+    trace("o", o.into());
+    let e = abs(y + n1 / 2f32 + sin(z * 0.05f32 + o) * 10f32) - 1f32;
+    trace("e", e.into());
+    let e = round_max(vec![
+        e,
+        abs(z + sin(x * 0.05f32 + o) * 25f32) - 5f32 + n1 * 2f32,
+        0.2f32,
+    ]);
+    trace("e", e.into());
+    let x1 = modulo(x + n * 10f32, 3f32) - 1.5f32;
+    trace("x1", x1.into());
+    let z1 = modulo(z + n * 10f32, 3f32) - 1.5f32;
+    trace("z1", z1.into());
+    let e = round_max(vec![e, -(abs(z1) - g0), 0.25f32]);
+    trace("e", e.into());
+    let e = round_max(vec![e, -(abs(x1) - g0), 0.25f32]);
+    trace("e", e.into());
+    let d = union(vec![d, e]);
+    trace("d", d.into());
+    let [x, z] = rot1(z, x, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let y = y + 20f32;
+    trace("y", y.into());
+    let x = x - 20f32;
+    trace("x", x.into());
+    let o = 3f32 * 200f32 + 20f32;
+    trace("o", o.into());
+    let e = abs(y + n1 / 2f32 + sin(z * 0.05f32 + o) * 10f32) - 1f32;
+    trace("e", e.into());
+    let e = round_max(vec![
+        e,
+        abs(z + sin(x * 0.05f32 + o) * 25f32) - 5f32 + n1 * 2f32,
+        0.2f32,
+    ]);
+    trace("e", e.into());
+    let x1 = modulo(x + n * 10f32, 3f32) - 1.5f32;
+    trace("x1", x1.into());
+    let z1 = modulo(z + n * 10f32, 3f32) - 1.5f32;
+    trace("z1", z1.into());
+    let e = round_max(vec![e, -(abs(z1) - g0), 0.25f32]);
+    trace("e", e.into());
+    let e = round_max(vec![e, -(abs(x1) - g0), 0.25f32]);
+    trace("e", e.into());
+    let d = union(vec![d, e]);
+    trace("d", d.into());
+    let [x, z] = rot1(z, x, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let y = y + 20f32;
+    trace("y", y.into());
+    let x = x - 20f32;
+    trace("x", x.into());
+    let o = 4f32 * 200f32 + 20f32;
+    trace("o", o.into());
+    let e = abs(y + n1 / 2f32 + sin(z * 0.05f32 + o) * 10f32) - 1f32;
+    trace("e", e.into());
+    let e = round_max(vec![
+        e,
+        abs(z + sin(x * 0.05f32 + o) * 25f32) - 5f32 + n1 * 2f32,
+        0.2f32,
+    ]);
+    trace("e", e.into());
+    let x1 = modulo(x + n * 10f32, 3f32) - 1.5f32;
+    trace("x1", x1.into());
+    let z1 = modulo(z + n * 10f32, 3f32) - 1.5f32;
+    trace("z1", z1.into());
+    let e = round_max(vec![e, -(abs(z1) - g0), 0.25f32]);
+    trace("e", e.into());
+    let e = round_max(vec![e, -(abs(x1) - g0), 0.25f32]);
+    trace("e", e.into());
+    let d = union(vec![d, e]);
+    trace("d", d.into());
+    let [x, z] = rot1(z, x, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let y = y + 20f32;
+    trace("y", y.into());
+    union(vec![
+        d,
+        if hash(xi, yi, zi) > 0.4f32 && length!(xi, yi, zi) > 3f32 {
+            length!(xm, ym, zm) - 2f32
+        } else {
+            10f32
+        },
+    ])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "thepath",
+        sdf: thepath,
+    }
+}
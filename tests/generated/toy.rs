@@ -0,0 +1,103 @@
+// Generated by `arrow::codegen` from the `toy` scene.
+
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::{rot0, rot1, round_max, round_min, smooth_abs, torus};
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use arrow::{box3, length};
+use glam::{Vec2, Vec3};
+
+pub fn toy(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let a0 = 0.1f32;
+    let a1 = 0.2f32;
+    let s = 1f32;
+    trace("s", s.into());
+    let x1 = x - 0.25f32;
+    trace("x1", x1.into());
+    let y1 = y - 4.6f32;
+    trace("y1", y1.into());
+    let z1 = z;
+    trace("z1", z1.into());
+    let x = x1;
+    trace("x", x.into());
+    let y = y1;
+    trace("y", y.into());
+    let [x, y] = rot0(x, y, a0);
+    trace("x", x.into());
+    trace("y", y.into());
+    let [x, z] = rot1(x, z, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let x = smooth_abs(x * 2f32, 0.1f32) - 4f32;
+    trace("x", x.into());
+    let y = smooth_abs(y * 2f32, 0.1f32) - 4f32;
+    trace("y", y.into());
+    let z = smooth_abs(z * 2f32, 0.1f32) - 4f32;
+    trace("z", z.into());
+    let s = s * 0.4f32;
+    trace("s", s.into());
+    let [x, y] = rot0(x, y, a0);
+    trace("x", x.into());
+    trace("y", y.into());
+    let [x, z] = rot1(x, z, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let x = smooth_abs(x * 2f32, 0.1f32) - 4f32;
+    trace("x", x.into());
+    let y = smooth_abs(y * 2f32, 0.1f32) - 4f32;
+    trace("y", y.into());
+    let z = smooth_abs(z * 2f32, 0.1f32) - 4f32;
+    trace("z", z.into());
+    let s = s * 0.4f32;
+    trace("s", s.into());
+    let [x, y] = rot0(x, y, a0);
+    trace("x", x.into());
+    trace("y", y.into());
+    let [x, z] = rot1(x, z, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let x = smooth_abs(x * 2f32, 0.1f32) - 4f32;
+    trace("x", x.into());
+    let y = smooth_abs(y * 2f32, 0.1f32) - 4f32;
+    trace("y", y.into());
+    let z = smooth_abs(z * 2f32, 0.1f32) - 4f32;
+    trace("z", z.into());
+    let s = s * 0.4f32;
+    trace("s", s.into());
+    let [x, y] = rot0(x, y, a0);
+    trace("x", x.into());
+    trace("y", y.into());
+    let [x, z] = rot1(x, z, a1);
+    trace("x", x.into());
+    trace("z", z.into());
+    let x = smooth_abs(x * 2f32, 0.1f32) - 4f32;
+    trace("x", x.into());
+    let y = smooth_abs(y * 2f32, 0.1f32) - 4f32;
+    trace("y", y.into());
+    let z = smooth_abs(z * 2f32, 0.1f32) - 4f32;
+    trace("z", z.into());
+    let s = s * 0.4f32;
+    trace("s", s.into());
+    round_min(vec![
+        round_min(vec![
+            box3!(x, y, z, 4f32) * s - 0.01f32,
+            round_max(vec![length!(x, y) - 1.75f32, box3!(x, y, z, 4f32) * 0.2f32]),
+            0.75f32,
+        ]),
+        torus(x1, y1, z1, 9f32, 0.75f32),
+        2.75f32,
+    ])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "toy",
+        sdf: toy,
+    }
+}
@@ -0,0 +1,71 @@
+// Generated by `arrow::codegen` from the `transforms` scene.
+
+use arrow::box3;
+use arrow::core::{v3, I, ZERO3};
+use arrow::eval::Value;
+use arrow::functions::{hex_prism, link, octahedron, pyramid, rotate, scale, translate, union};
+use arrow::sdf::sd_box;
+use arrow::sdfs::Scene;
+use glam::Vec3;
+
+pub fn transforms(p: Vec3) -> f32 {
+    traced(p, &mut |_, _| {})
+}
+
+pub fn traced(p: Vec3, trace: &mut dyn FnMut(&'static str, Value)) -> f32 {
+    let Vec3 { x, y, z } = p;
+    let [a, b, c] = rotate(x + 8f32, y - 4f32, z, 1f32, 1f32, 0f32, 0.8f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let d1 = box3!(a, b, c, 2f32, 1f32, 1f32);
+    trace("d1", d1.into());
+    let [a, b, c] = scale(x, y - 4f32, z, 2f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let d2 = octahedron(a, b, c, 1f32) * 2f32;
+    trace("d2", d2.into());
+    let [a, b, c] = translate(x, y, z, 8f32, 4f32, 0f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let [a, b, c] = rotate(a, b, c, 0f32, 0f32, 1f32, 0.5f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let d3 = link(a, b, c, 1f32, 1.2f32, 0.3f32);
+    trace("d3", d3.into());
+    let [a, b, c] = scale(x + 8f32, y + 4f32, z, 0.5f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let [a, b, c] = rotate(a, b, c, 1f32, 0f32, 0f32, 1f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let d4 = hex_prism(a, b, c, 2f32, 3f32) * 0.5f32;
+    trace("d4", d4.into());
+    let [a, b, c] = translate(x, y, z, 0f32, -4f32, 0f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let [a, b, c] = rotate(a, b, c, 0f32, 1f32, 0f32, 0.7f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let [a, b, c] = scale(a, b, c, 1.5f32);
+    trace("a", a.into());
+    trace("b", b.into());
+    trace("c", c.into());
+    let d5 = pyramid(a, b + 0.5f32, c, 1f32) * 1.5f32;
+    trace("d5", d5.into());
+    union(vec![d1, d2, d3, d4, d5])
+}
+
+pub fn scene() -> Scene {
+    Scene {
+        name: "transforms",
+        sdf: transforms,
+    }
+}
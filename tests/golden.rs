@@ -0,0 +1,197 @@
+// Renders every scene in `sdfs` and a few of the primitives in `sdf` at a
+// small size and compares them with the images in tests/golden. On failure the
+// rendered image and a diff, with differing pixels in red, are written next to
+// each other in the target directory. Run with ARROW_BLESS=1 to replace the
+// golden images after an intended change to the renderer. The library scenes
+// with a bound are also rendered with and without it, which must agree as
+// closely.
+use arrow::core::{union, v3, Light, Sdf, I, ZERO3};
+use arrow::march::{render, render_view};
+use arrow::scene::library;
+use arrow::sdf::{
+    sd_box, sd_capped_torus, sd_capsule, sd_cone, sd_cylinder, sd_ellipsoid, sd_extrude,
+    sd_hexagon, sd_link, sd_octahedron, sd_plane, sd_round_box, sd_sphere, sd_torus,
+};
+use arrow::sdfs::SCENES;
+use glam::Vec3;
+use image::ColorType;
+use std::path::{Path, PathBuf};
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 48;
+const BACKGROUND: f32 = 0.75;
+// A pixel differs if it is off by more than PIXEL_TOLERANCE levels. An image
+// fails if more than BAD_PIXELS of its pixels differ, or if it is darker or
+// lighter overall by more than MEAN_TOLERANCE levels.
+const PIXEL_TOLERANCE: u8 = 16;
+const BAD_PIXELS: f32 = 0.005;
+const MEAN_TOLERANCE: f32 = 1.0;
+
+fn lights() -> [Light; 2] {
+    [
+        Light::new(v3(0.0, 0.0, -50.0), 1.0),
+        Light::new(v3(0.0, 10.0, 40.0), 1.0),
+    ]
+}
+
+fn primitives() -> Vec<(&'static str, Sdf)> {
+    vec![
+        ("sphere", sd_sphere(1.0, ZERO3, I)),
+        ("box", sd_box(v3(1.0, 0.7, 0.5), ZERO3, I)),
+        ("round_box", sd_round_box(v3(0.8, 0.5, 0.3), 0.2, ZERO3, I)),
+        ("torus", sd_torus(1.0, 0.3, ZERO3, I)),
+        (
+            "capsule",
+            sd_capsule(0.4, ZERO3, v3(-1.0, -0.5, 0.0), v3(1.0, 0.5, 0.0), I),
+        ),
+        (
+            "cylinder",
+            sd_cylinder(0.6, ZERO3, v3(0.0, -1.0, 0.0), v3(0.0, 1.0, 0.0), I),
+        ),
+        ("cone", sd_cone(0.5, 2.0, v3(0.0, 1.0, 0.0), I)),
+        ("ellipsoid", sd_ellipsoid(v3(1.2, 0.7, 0.5), ZERO3, I)),
+        ("octahedron", sd_octahedron(1.2, ZERO3, I)),
+        ("link", sd_link(0.5, 0.6, 0.2, ZERO3, I)),
+        ("capped_torus", sd_capped_torus(2.0, 1.0, 0.25, ZERO3, I)),
+        ("hex_prism", sd_extrude(sd_hexagon(0.8), 0.3, ZERO3, I)),
+    ]
+}
+
+struct Comparison {
+    bad: usize,
+    mean: f32,
+}
+
+impl Comparison {
+    fn new(expected: &[u8], actual: &[u8]) -> Self {
+        let bad = expected
+            .iter()
+            .zip(actual)
+            .filter(|(e, a)| e.abs_diff(**a) > PIXEL_TOLERANCE)
+            .count();
+        let sum = |img: &[u8]| img.iter().map(|&v| v as f32).sum::<f32>();
+        let mean = (sum(actual) - sum(expected)) / expected.len() as f32;
+        Comparison { bad, mean }
+    }
+
+    fn passes(&self) -> bool {
+        self.bad as f32 <= BAD_PIXELS * (WIDTH * HEIGHT) as f32 && self.mean.abs() <= MEAN_TOLERANCE
+    }
+}
+
+// The expected image in grey, darkened, with every pixel that differs in red.
+fn diff_image(expected: &[u8], actual: &[u8]) -> Vec<u8> {
+    expected
+        .iter()
+        .zip(actual)
+        .flat_map(|(&e, &a)| match e.abs_diff(a) {
+            0 => [e / 2, e / 2, e / 2],
+            d => [128u8.saturating_add(d.saturating_mul(4)), 0, 0],
+        })
+        .collect()
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+// Returns a description of the failure, if any.
+fn check(name: &str, sdf: &Sdf, camera: Vec3) -> Option<String> {
+    let actual = render(sdf, camera, ZERO3, &lights(), BACKGROUND, WIDTH, HEIGHT, 1);
+    let path = golden_dir().join(format!("{}.png", name));
+    if std::env::var("ARROW_BLESS").is_ok() {
+        image::save_buffer(&path, &actual, WIDTH, HEIGHT, ColorType::L8).unwrap();
+        return None;
+    }
+    let expected = match image::open(&path) {
+        Ok(img) => img.into_luma8().into_raw(),
+        Err(e) => return Some(format!("{}: {}", name, e)),
+    };
+    let comparison = Comparison::new(&expected, &actual);
+    if comparison.passes() {
+        return None;
+    }
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    std::fs::create_dir_all(&out).unwrap();
+    let actual_path = out.join(format!("{}.png", name));
+    let diff_path = out.join(format!("{}.diff.png", name));
+    image::save_buffer(&actual_path, &actual, WIDTH, HEIGHT, ColorType::L8).unwrap();
+    let diff = diff_image(&expected, &actual);
+    image::save_buffer(&diff_path, &diff, WIDTH, HEIGHT, ColorType::Rgb8).unwrap();
+    Some(format!(
+        "{}: {} pixels differ, mean changed by {:.2}, see {}",
+        name,
+        comparison.bad,
+        comparison.mean,
+        diff_path.display()
+    ))
+}
+
+#[test]
+fn scenes() {
+    let library = library();
+    let failures: Vec<String> = SCENES
+        .iter()
+        .filter_map(|s| {
+            let sdf: Sdf = Box::new(s.sdf);
+            let camera = library[s.name].camera.position;
+            check(&format!("scene_{}", s.name), &sdf, camera)
+        })
+        .collect();
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn primitives_on_a_plane() {
+    let failures: Vec<String> = primitives()
+        .into_iter()
+        .filter_map(|(name, sdf)| {
+            let sdf = union(sdf, sd_plane(v3(0.0, 1.0, 0.0), 1.5, ZERO3, I));
+            check(&format!("sd_{}", name), &sdf, v3(1.5, 2.0, -3.5))
+        })
+        .collect();
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn comparison() {
+    let expected = vec![100u8; (WIDTH * HEIGHT) as usize];
+    let mut actual = expected.clone();
+    actual[0] = 110;
+    assert!(Comparison::new(&expected, &actual).passes());
+    actual[..20].fill(200);
+    let comparison = Comparison::new(&expected, &actual);
+    assert_eq!(comparison.bad, 20);
+    assert!(!comparison.passes());
+    let diff = diff_image(&expected, &actual);
+    assert_eq!(diff[..3], [255, 0, 0]);
+    assert_eq!(diff[60..63], [50, 50, 50]);
+}
+
+// Bounding the march mustn't change the picture, for every scene a bound is
+// estimated for.
+#[test]
+fn bounded_scenes() {
+    let failures: Vec<String> = library()
+        .iter()
+        .filter_map(|scene| {
+            let bound = scene.bound()?;
+            let sdf = scene.sdf();
+            let mut view = scene.view(&sdf);
+            view.width = WIDTH;
+            view.height = HEIGHT;
+            view.bound = None;
+            let unbounded = render_view(&view, 1);
+            view.bound = Some(bound);
+            let bounded = render_view(&view, 1);
+            let comparison = Comparison::new(&unbounded, &bounded);
+            (!comparison.passes()).then(|| {
+                format!(
+                    "{}: {} pixels differ with the bound, mean changed by {:.2}",
+                    scene.name, comparison.bad, comparison.mean
+                )
+            })
+        })
+        .collect();
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
@@ -9,8 +9,7 @@ pub fn build_sdf(ast: &Statement, a0: f32, a1: f32, p: Vec3) -> f32 {
     let mut env = HashMap::new();
     env.insert("a0".to_string(), Value::ScalarVal(a0));
     env.insert("a1".to_string(), Value::ScalarVal(a1));
-    eval(&mut env, &ast, p);
-    println!("Env: {:?}", env);
+    eval(&mut env, ast, p);
     let v = env.get("#").unwrap();
     match v {
         Value::ScalarVal(s) => *s,
@@ -20,11 +19,17 @@ pub fn build_sdf(ast: &Statement, a0: f32, a1: f32, p: Vec3) -> f32 {
 
 #[test]
 fn sdf() {
-    // let mut input = "[x,z]=r0(x-20,z), bx3(x,mod(y,1)-.5,mod(z,1)-.5,.45)";
-    // let mut rot_cube = "[a,b]=r0(x,y-9); bx3(a,b,z,4)-.5";
-    let mut  apollonius = "s=2.5,h=s/2,d=(s+h)/2,q=20,y-=10,[x,y]=r0(x,y),@xyz{$/=q,}c=1,t=0,@7{@xyz{$=mod($-h,s)-h,}t=d/D([x,y,z],[x,y,z]),@xyzc{$*=t,}}d=L(x,y,z)/c*2.-.025";
+    let mut apollonius = "s=2.5,h=s/2,d=(s+h)/2,q=20,y-=10,[x,y]=r0(x,y),@xyz{$/=q,}c=1,t=0,@7{@xyz{$=mod($-h,s)-h,}t=d/D([x,y,z],[x,y,z]),@xyzc{$*=t,}}d=L(x,y,z)/c*2.-.025";
     let ast = parse(&mut apollonius);
-    let sdf = |p| build_sdf(&ast, 0.125, 0.2, p);
-    println!("Sdf {:?}", sdf(v3(0.0, 0.0, -50.0)));
-    println!("Ast: {:?}", &ast);
+    // The hand written apollonius in sdfs is the same shape with a0 = 0.1.
+    for p in [v3(0.0, 0.0, -50.0), v3(3.0, -2.0, 1.0), v3(-7.5, 12.0, 4.0)] {
+        let (interpreted, compiled) = (build_sdf(&ast, 0.1, 0.2, p), arrow::sdfs::apollonius(p));
+        assert!(
+            (interpreted - compiled).abs() < 1e-4,
+            "{} != {} at {}",
+            interpreted,
+            compiled,
+            p
+        );
+    }
 }